@@ -1,18 +1,34 @@
 // Agent compute shader using layer-based pheromone array
+// AGENT_WORKGROUP_SIZE is a shader def supplied by `render::workgroup_shader_defs`
 
 @group(0) @binding(0) var<storage, read_write> agents: array<Agent>;
 @group(0) @binding(4) var<uniform> globals: GlobalUniforms;
 @group(0) @binding(5) var<storage, read> species: array<SpeciesSettings>;
 // Array-based pheromone field: one layer per pheromone (read/write for sensing and deposit)
 @group(0) @binding(6) var phero_array: texture_storage_2d_array<r32float, read_write>;
-// Extended pheromone controls and per-species profiles (dense). Not used yet; bound with dummies.
+// Per-species sensing weights, `layer_count` floats per species indexed by
+// species_index * layer_count + layer (built by `species::dense_layer_weights`)
 @group(0) @binding(7) var<storage, read> species_weights: array<f32>;
 @group(0) @binding(9) var<uniform> phero_ctrl: PheroControl;
+// Obstacle mask: 1.0 = wall (agents never enter or deposit there)
+@group(0) @binding(10) var obstacle_mask: texture_storage_2d<r32float, read>;
+// Independent regions (min.xy, max.xy); count 0 = whole field
+@group(0) @binding(11) var<uniform> regions: Regions;
+// Per-layer params (only max_value is used here, to cap deposits)
+@group(0) @binding(12) var<storage, read> layer_params: array<PheromoneLayerParam>;
+// Same-species avoidance grid: agent count per (cell, species slot), rebuilt
+// each frame by bin_agents before update_agents reads it
+@group(0) @binding(14) var<storage, read_write> agent_grid: array<atomic<u32>>;
 
 struct Agent {
     position: vec2<f32>,
     angle: f32,
     species_index: u32,
+    // Seconds since (re)spawn
+    age: f32,
+    // Deposit fatigue in [0, 1]; scales deposits by 1 - fatigue
+    fatigue: f32,
+    _pad: array<u32, 2>,
 };
 
 struct SpeciesSettings {
@@ -21,15 +37,57 @@ struct SpeciesSettings {
     sensor_angle_degrees: f32,
     sensor_offset_dst: f32,
 
+    // Radius in texels; readings average a (2r+1)^2 block, 0 = one texel
     sensor_size: f32,
-    _pad0: f32,
-    _pad1: f32,
-    _pad2: f32,
+    sensor_count: u32,
+    // 0 = constant, 1 = scaled by distance actually moved this step
+    deposit_mode: u32,
+    // Max random heading change per second (radians); 0 = deterministic
+    wander_strength: f32,
 
     color: vec4<f32>,
     emit_layer: u32,
     emit_amount: f32,
-    _pad_emit: vec2<u32>,
+    // Consumed on the CPU (per-layer decay); kept for layout parity
+    emit_decay_bias: f32,
+    // Nonzero: offset slides from sensor_offset_dst (strong field) to
+    // sensor_offset_max (weak field) with the forward reading
+    sensor_adaptive: u32,
+
+    sensor_offset_max: f32,
+    // Cap on heading change per step (radians); default is effectively none
+    max_turn_per_step: f32,
+    // 0 = discrete sensors, 1 = follow the weighted field gradient
+    steer_mode: u32,
+    // Per-agent speed spread: move_speed * (1 + j * r), r in [-1, 1] fixed per agent
+    speed_jitter: f32,
+
+    // Further deposits next to emit_layer (amount 0 = unused slot)
+    extra_emit_layers: vec4<u32>,
+    extra_emit_amounts: vec4<f32>,
+
+    // Lifespan in seconds; 0 = immortal
+    max_age: f32,
+    // Heading drift in radians/s added after steering (positive = clockwise)
+    turn_bias: f32,
+    // Deposit disc radius in texels; < 1 = single texel
+    deposit_radius: f32,
+    // 0 = linear, 1 = sqrt, 2 = ln(1 + v); shapes each layer value before weighting
+    response_curve: u32,
+
+    // Max turn away from same-species crowding (radians/s); 0 = off
+    avoid_strength: f32,
+    // Deposit only while the weighted reading ahead is >= this; 0 = always
+    deposit_threshold: f32,
+    // Zero: agents skip movement and deposition (paused species)
+    enabled: u32,
+    // Fatigue build-up / recovery rate per second on strong / weak emit layer; 0 = off
+    deposit_fatigue: f32,
+    // Half-arc the sensors spread over (degrees); 0 = sensor_angle_degrees
+    sensor_spread_degrees: f32,
+    // Deposit into phero_ctrl.shared_layer alongside the emit layer; 0 = none
+    shared_emit_amount: f32,
+    _pad: array<u32, 2>,
 };
 
 
@@ -42,13 +100,60 @@ struct GlobalUniforms {
     right_button_pressed: u32,
     species_offset: u32,
     species_count: u32,
+    wrap_edges: u32,
+    wrap_diffusion: u32,
+};
+
+const MAX_REGIONS: u32 = 16u;
+struct Regions {
+    count: u32,
+    _pad: vec3<u32>,
+    rects: array<vec4<f32>, 16>,
+};
+
+struct PheromoneLayerParam {
+    diffusion: f32,
+    decay: f32,
+    kernel: u32,
+    frozen: u32,
+    color: vec4<f32>,
+    // <= 0 = unbounded
+    max_value: f32,
+    diagonal_weight: f32,
+    diffuse_enabled: u32,
+    decay_enabled: u32,
+    flow_dir: vec2<f32>,
+    flow_strength: f32,
+    decay_floor: f32,
+    brush_amount: f32,
+    conserve_mass: u32,
+    _pad: array<u32, 2>,
 };
 
+// Clamp a layer value to the layer's max_value (<= 0 leaves it unbounded)
+fn cap_layer(value: f32, layer: u32) -> f32 {
+    let m = layer_params[layer].max_value;
+    return select(value, min(value, m), m > 0.0);
+}
+
 struct PheroControl {
     layer_count: u32,
-    _pad: vec3<u32>,
+    // Nonzero: sensors blend the 4 texels around their sub-pixel position
+    bilinear_sensing: u32,
+    // Bit i set: layer i is paint-only (universal love/hate); no deposits
+    paint_only_mask: u32,
+    // Entries in the species buffer; see species_slot
+    species_count: u32,
+    // Layer every species deposits shared_emit_amount into; NO_SHARED_LAYER = none
+    shared_layer: u32,
+    // Agent dot splat: side in pixels, additive flag, additive color scale
+    dot_size: u32,
+    dot_additive: u32,
+    dot_strength: f32,
 };
 
+const NO_SHARED_LAYER: u32 = 0xffffffffu;
+
 
 fn hash_u32(value: u32) -> u32 {
     var state = value;
@@ -69,107 +174,600 @@ fn weight_base(species_index: u32, layer_count: u32) -> u32 {
     return species_index * layer_count;
 }
 
+fn is_wall(pos: vec2<i32>) -> bool {
+    return textureLoad(obstacle_mask, pos).x > 0.5;
+}
+
+const RESPONSE_SQRT: u32 = 1u;
+const RESPONSE_LOG: u32 = 2u;
+
+// Response curve of the species being updated; set once per invocation in
+// update_agents so the sampling helpers don't all need another parameter
+var<private> response_curve: u32 = 0u;
+
+// Sensed layer value after the species' response curve. Negative values
+// (shouldn't occur, but brushes can overshoot) keep their sign.
+fn apply_response(v: f32) -> f32 {
+    if (response_curve == RESPONSE_SQRT) {
+        return sign(v) * sqrt(abs(v));
+    }
+    if (response_curve == RESPONSE_LOG) {
+        return sign(v) * log(1.0 + abs(v));
+    }
+    return v;
+}
+
+// Weighted signal at one texel: sum over all lc layers of the (response-
+// shaped) value times species_weights[base + layer], with base from weight_base
+// (dense layout built by `species::dense_layer_weights`)
 fn sample_signal_ext_with_base(pos: vec2<i32>, base: u32, lc: u32) -> f32 {
     var sum = 0.0;
     for (var li: u32 = 0u; li < lc; li = li + 1u) {
-        let v = textureLoad(phero_array, pos, i32(li)).x;
+        let v = apply_response(textureLoad(phero_array, pos, i32(li)).x);
         let w = species_weights[base + li];
         sum = sum + v * w;
     }
     return sum;
 }
 
-@compute @workgroup_size(256)
+// `species_index` of unused agent buffer slots (see `agents::INACTIVE_AGENT`)
+const INACTIVE_AGENT: u32 = 0xffffffffu;
+
+// Species buffer slot for an agent: its index rotated by species_offset, then
+// clamped to the bound buffer in case globals.species_count and the buffer
+// disagree (e.g. mid species reload). Callers skip agents when the buffer is empty.
+fn species_slot(agent_species: u32) -> u32 {
+    let rotated = (agent_species + globals.species_offset) % max(globals.species_count, 1u);
+    return min(rotated, phero_ctrl.species_count - 1u);
+}
+
+// Field texel a sensor tap at `c` reads: wrapped to the opposite edge when
+// agents wrap (so sensing sees across the seam, like wrapped diffusion),
+// clamped to the edge otherwise
+fn sense_texel(c: vec2<i32>) -> vec2<i32> {
+    let size = max(vec2<i32>(globals.screen_size), vec2<i32>(1, 1));
+    if (globals.wrap_edges != 0u) {
+        return (c % size + size) % size;
+    }
+    return clamp(c, vec2<i32>(0, 0), size - vec2<i32>(1, 1));
+}
+
+// Manual 4-tap bilinear read of the weighted signal (storage textures can't
+// use a sampler). Texel centres sit at +0.5; taps go through sense_texel.
+fn sample_signal_bilinear(p: vec2<f32>, base: u32, lc: u32) -> f32 {
+    let q = p - vec2<f32>(0.5, 0.5);
+    let i0 = vec2<i32>(floor(q));
+    let f = q - floor(q);
+    let a = sense_texel(i0);
+    let b = sense_texel(i0 + vec2<i32>(1, 1));
+    let s00 = sample_signal_ext_with_base(vec2<i32>(a.x, a.y), base, lc);
+    let s10 = sample_signal_ext_with_base(vec2<i32>(b.x, a.y), base, lc);
+    let s01 = sample_signal_ext_with_base(vec2<i32>(a.x, b.y), base, lc);
+    let s11 = sample_signal_ext_with_base(vec2<i32>(b.x, b.y), base, lc);
+    return mix(mix(s00, s10, f.x), mix(s01, s11, f.x), f.y);
+}
+
+// Upper bound on per-species sensors (keeps the sensing loop bounded);
+// matches MAX_SENSORS on the CPU
+const MAX_SENSORS: u32 = 16u;
+// Matches MAX_SENSOR_SIZE on the CPU
+const MAX_SENSOR_RADIUS: i32 = 8;
+
+// Index of the first region containing p, or -1 (regions off / outside all)
+fn region_of(p: vec2<f32>) -> i32 {
+    for (var i = 0u; i < min(regions.count, MAX_REGIONS); i++) {
+        let rect = regions.rects[i];
+        if (p.x >= rect.x && p.y >= rect.y && p.x < rect.z && p.y < rect.w) { return i32(i); }
+    }
+    return -1;
+}
+
+fn in_region(p: vec2<f32>, region: i32) -> bool {
+    if (region < 0) { return true; }
+    let rect = regions.rects[region];
+    return p.x >= rect.x && p.y >= rect.y && p.x < rect.z && p.y < rect.w;
+}
+
+// Weighted signal averaged over a square (2r+1)^2 mask centred on `p`, so the
+// reading's scale doesn't depend on sensor size. r = 0 reads a single texel
+// (bilinear at sub-pixel positions). Sensors outside the agent's region read
+// like walls.
+fn sense_at(p: vec2<f32>, r: i32, base: u32, lc: u32, region: i32) -> f32 {
+    if (!in_region(p, region)) { return -1.0e6; }
+    let c = sense_texel(vec2<i32>(floor(p)));
+    let cx = c.x;
+    let cy = c.y;
+    // Sensors inside a wall read as strongly repulsive so agents steer away
+    if (is_wall(c)) { return -1.0e6; }
+    let bilinear = phero_ctrl.bilinear_sensing != 0u;
+    if (r == 0) {
+        if (bilinear) { return sample_signal_bilinear(p, base, lc); }
+        return sample_signal_ext_with_base(vec2<i32>(cx, cy), base, lc);
+    }
+    var sum = 0.0;
+    for (var ox = -r; ox <= r; ox++) {
+        for (var oy = -r; oy <= r; oy++) {
+            if (bilinear) {
+                sum += sample_signal_bilinear(p + vec2<f32>(f32(ox), f32(oy)), base, lc);
+            } else {
+                sum += sample_signal_ext_with_base(sense_texel(vec2<i32>(cx + ox, cy + oy)), base, lc);
+            }
+        }
+    }
+    let side = f32(2 * r + 1);
+    return sum / (side * side);
+}
+
+const STEER_GRADIENT: u32 = 1u;
+
+// Central-difference gradient of the weighted signal around p with step h
+fn signal_gradient(p: vec2<f32>, h: f32, r: i32, base: u32, lc: u32, region: i32) -> vec2<f32> {
+    let dx = vec2<f32>(h, 0.0);
+    let dy = vec2<f32>(0.0, h);
+    let gx = sense_at(p + dx, r, base, lc, region) - sense_at(p - dx, r, base, lc, region);
+    let gy = sense_at(p + dy, r, base, lc, region) - sense_at(p - dy, r, base, lc, region);
+    return vec2<f32>(gx, gy) / (2.0 * h);
+}
+
+// Random open spot for a respawning agent, inside its region if it has one
+fn respawn_position(seed: u32, region: i32) -> vec2<f32> {
+    var lo = vec2<f32>(0.0, 0.0);
+    var hi = globals.screen_size;
+    if (region >= 0) {
+        lo = regions.rects[region].xy;
+        hi = regions.rects[region].zw;
+    }
+    var p = lo;
+    // A few tries to avoid walls; a crowded mask may still land in one
+    for (var attempt = 0u; attempt < 4u; attempt++) {
+        let h = hash_u32(seed ^ (attempt * 0x9e3779b9u));
+        let r = vec2<f32>(hash_f32(h), hash_f32(hash_u32(h)));
+        p = min(lo + r * (hi - lo), hi - vec2<f32>(1.0, 1.0));
+        if (!is_wall(vec2<i32>(p))) { break; }
+    }
+    return p;
+}
+
+// Half-arc of a species' sensor fan in radians: the explicit spread when set,
+// else the legacy sensor angle
+fn sensor_fan_angle(s: SpeciesSettings) -> f32 {
+    return radians(select(s.sensor_angle_degrees, s.sensor_spread_degrees, s.sensor_spread_degrees > 0.0));
+}
+
+// Angle of sensor i of n relative to the heading, spread evenly over
+// [-angle, +angle]. Positive offsets are on the left. CPU copy in the
+// `species` tests.
+fn sensor_offset_angle(i: u32, n: u32, angle: f32) -> f32 {
+    if (n <= 1u || 2u * i == n - 1u) { return 0.0; }
+    return -angle + 2.0 * angle * f32(i) / f32(n - 1u);
+}
+
+// Matches AVOID_CELL_SIZE on the CPU
+const AVOID_CELL_SIZE: u32 = 8u;
+
+fn avoid_grid_dims() -> vec2<i32> {
+    let size = max(vec2<u32>(globals.screen_size), vec2<u32>(1u, 1u));
+    return vec2<i32>((size + vec2<u32>(AVOID_CELL_SIZE - 1u)) / AVOID_CELL_SIZE);
+}
+
+fn avoid_cell(p: vec2<f32>) -> vec2<i32> {
+    return clamp(vec2<i32>(floor(p / f32(AVOID_CELL_SIZE))), vec2<i32>(0, 0), avoid_grid_dims() - vec2<i32>(1, 1));
+}
+
+// Cells are species-interleaved (CPU copy in the `agents` tests)
+fn avoid_grid_index(cell: vec2<i32>, slot: u32) -> u32 {
+    return u32(cell.y * avoid_grid_dims().x + cell.x) * phero_ctrl.species_count + slot;
+}
+
+// Count each avoiding agent into its cell. One atomic per agent; species
+// without avoidance are skipped so they cost nothing here.
+@compute @workgroup_size(#{AGENT_WORKGROUP_SIZE})
+fn bin_agents(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.x;
+    if (index >= arrayLength(&agents)) { return; }
+    let agent = agents[index];
+    if (agent.species_index == INACTIVE_AGENT || phero_ctrl.species_count == 0u) { return; }
+    let slot = species_slot(agent.species_index);
+    if (species[slot].avoid_strength <= 0.0 || species[slot].enabled == 0u) { return; }
+    atomicAdd(&agent_grid[avoid_grid_index(avoid_cell(agent.position), slot)], 1u);
+}
+
+// Push away from same-species agents in the 3x3 cells around p: each
+// neighbour cell pushes along (p - its centre), weighted by its count and
+// fading out over two cells. The agent's own entry is discounted.
+fn crowd_push(p: vec2<f32>, slot: u32) -> vec2<f32> {
+    let dims = avoid_grid_dims();
+    let cell = avoid_cell(p);
+    let reach = 2.0 * f32(AVOID_CELL_SIZE);
+    var push = vec2<f32>(0.0, 0.0);
+    for (var dy = -1; dy <= 1; dy++) {
+        for (var dx = -1; dx <= 1; dx++) {
+            let offset_cell = cell + vec2<i32>(dx, dy);
+            var c = offset_cell;
+            if (globals.wrap_edges != 0u) {
+                c = (c + dims) % dims;
+            } else if (any(c < vec2<i32>(0, 0)) || any(c >= dims)) {
+                continue;
+            }
+            var n = f32(atomicLoad(&agent_grid[avoid_grid_index(c, slot)]));
+            if (dx == 0 && dy == 0) { n = n - 1.0; }
+            if (n <= 0.0) { continue; }
+            // Unwrapped centre, so wrapped neighbours push from the right side
+            let away = p - (vec2<f32>(offset_cell) + 0.5) * f32(AVOID_CELL_SIZE);
+            let d = length(away);
+            if (d > 1.0e-3) {
+                push += away / d * n * max(0.0, 1.0 - d / reach);
+            }
+        }
+    }
+    return push;
+}
+
+@compute @workgroup_size(#{AGENT_WORKGROUP_SIZE})
 fn update_agents(@builtin(global_invocation_id) id: vec3<u32>) {
     let index = id.x;
     if (index >= arrayLength(&agents)) { return; }
     var agent = agents[index];
-    let species_index = (agent.species_index + globals.species_offset) % globals.species_count;
+    // Spare slot reserved for the spawn brush
+    if (agent.species_index == INACTIVE_AGENT || phero_ctrl.species_count == 0u) { return; }
+    let species_index = species_slot(agent.species_index);
+    // Region the agent is confined to this step (-1 = unconstrained)
+    let region = region_of(agent.position);
     let s = species[species_index];
+    // Disabled species stay frozen in place: no ageing, movement or deposit
+    if (s.enabled == 0u) { return; }
+    response_curve = s.response_curve;
     let dt = globals.delta_time;
     let px = bitcast<u32>(agent.position.x);
     let py = bitcast<u32>(agent.position.y);
     let seed = px ^ hash_u32(py) ^ globals.frame;
     let random_val = hash_f32(seed);
-    let sensor_angle = radians(s.sensor_angle_degrees);
-    // Always use layer-based sensing
-    let fwdv = vec2<f32>(cos(agent.angle), sin(agent.angle));
-    let leftv = vec2<f32>(cos(agent.angle + sensor_angle), sin(agent.angle + sensor_angle));
-    let rightv = vec2<f32>(cos(agent.angle - sensor_angle), sin(agent.angle - sensor_angle));
-    let p_f = agent.position + fwdv * s.sensor_offset_dst;
-    let p_l = agent.position + leftv * s.sensor_offset_dst;
-    let p_r = agent.position + rightv * s.sensor_offset_dst;
-    let cx_f = clamp(i32(p_f.x), 0, i32(globals.screen_size.x) - 1);
-    let cy_f = clamp(i32(p_f.y), 0, i32(globals.screen_size.y) - 1);
-    let cx_l = clamp(i32(p_l.x), 0, i32(globals.screen_size.x) - 1);
-    let cy_l = clamp(i32(p_l.y), 0, i32(globals.screen_size.y) - 1);
-    let cx_r = clamp(i32(p_r.x), 0, i32(globals.screen_size.x) - 1);
-    let cy_r = clamp(i32(p_r.y), 0, i32(globals.screen_size.y) - 1);
-    // Use a square sensor mask
-    let r = i32(s.sensor_size);
-    var w_forward = 0.0;
-    var w_left = 0.0;
-    var w_right = 0.0;
+    // Lifespan: old agents respawn elsewhere with a fresh heading and skip
+    // this step's sensing, movement and deposit
+    agent.age = agent.age + dt;
+    if (s.max_age > 0.0 && agent.age >= s.max_age) {
+        let respawn_seed = hash_u32(index) ^ hash_u32(globals.frame ^ 0x68bc21ebu);
+        agent.position = respawn_position(respawn_seed, region);
+        agent.angle = hash_f32(hash_u32(respawn_seed)) * 6.28318530718;
+        agent.age = 0.0;
+        agent.fatigue = 0.0;
+        agents[index] = agent;
+        return;
+    }
+    let sensor_angle = sensor_fan_angle(s);
+    // Always use layer-based sensing with a square sensor mask
+    let r = clamp(i32(s.sensor_size), 0, MAX_SENSOR_RADIUS);
     let lc = phero_ctrl.layer_count;
     let base = weight_base(species_index, lc);
-    if (r == 0) {
-        w_forward = sample_signal_ext_with_base(vec2<i32>(cx_f, cy_f), base, lc);
-        w_left    = sample_signal_ext_with_base(vec2<i32>(cx_l, cy_l), base, lc);
-        w_right   = sample_signal_ext_with_base(vec2<i32>(cx_r, cy_r), base, lc);
-    } else {
-        for (var ox = -r; ox <= r; ox++) {
-            for (var oy = -r; oy <= r; oy++) {
-                w_forward += sample_signal_ext_with_base(vec2<i32>(cx_f + ox, cy_f + oy), base, lc);
-                w_left    += sample_signal_ext_with_base(vec2<i32>(cx_l + ox, cy_l + oy), base, lc);
-                w_right   += sample_signal_ext_with_base(vec2<i32>(cx_r + ox, cy_r + oy), base, lc);
-            }
+    // N sensors fan out over [-angle, +angle]. The centre sensor (odd N) is
+    // "forward"; each side keeps its strongest reading, so N = 3 is the
+    // classic left/forward/right rule and N = 1 only ever goes straight.
+    let n = clamp(s.sensor_count, 1u, MAX_SENSORS);
+    let has_forward = (n % 2u) == 1u;
+    let none = -3.4e38;
+    var w_forward = none;
+    var w_left = none;
+    var w_right = none;
+    // Adaptive offset: probe straight ahead at the base offset; a weak reading
+    // (<= 0) searches at sensor_offset_max, a strong one (>= 1) stays close
+    var offset_dst = s.sensor_offset_dst;
+    if (s.sensor_adaptive != 0u) {
+        let ahead = agent.position + vec2<f32>(cos(agent.angle), sin(agent.angle)) * s.sensor_offset_dst;
+        let strength = clamp(sense_at(ahead, r, base, lc, region), 0.0, 1.0);
+        offset_dst = mix(s.sensor_offset_max, s.sensor_offset_dst, strength);
+    }
+    // Gradient mode steers from signal_gradient below instead of the fan
+    let sensor_n = select(n, 0u, s.steer_mode == STEER_GRADIENT);
+    for (var i = 0u; i < sensor_n; i++) {
+        let offset = sensor_offset_angle(i, n, sensor_angle);
+        let a = agent.angle + offset;
+        let p = agent.position + vec2<f32>(cos(a), sin(a)) * offset_dst;
+        let w = sense_at(p, r, base, lc, region);
+        if (has_forward && 2u * i == n - 1u) {
+            w_forward = w;
+        } else if (offset > 0.0) {
+            w_left = max(w_left, w);
+        } else {
+            w_right = max(w_right, w);
         }
     }
+    // Stigmergic gating: the weighted reading straight ahead, from the centre
+    // sensor when there is one, decides below whether this step deposits
+    var gate_reading = w_forward;
+    if (s.deposit_threshold > 0.0 && (!has_forward || sensor_n == 0u)) {
+        let ahead = agent.position + vec2<f32>(cos(agent.angle), sin(agent.angle)) * offset_dst;
+        gate_reading = sense_at(ahead, r, base, lc, region);
+    }
+    let deposit_gated = s.deposit_threshold > 0.0 && gate_reading < s.deposit_threshold;
     var dir = agent.angle;
-    if (w_forward > w_left && w_forward > w_right) {
-    } else if (w_forward < w_left && w_forward < w_right) {
+    if (s.steer_mode == STEER_GRADIENT) {
+        // Turn toward the uphill direction of the weighted field (repellent
+        // layers carry negative weights, so this also turns away from them),
+        // at up to turn_speed; a flat field keeps the heading
+        let g = signal_gradient(agent.position, max(offset_dst, 1.0), r, base, lc, region);
+        if (dot(g, g) > 1.0e-12) {
+            let target_angle = atan2(g.y, g.x);
+            let diff = atan2(sin(target_angle - agent.angle), cos(target_angle - agent.angle));
+            let max_step = s.turn_speed * dt;
+            dir = dir + clamp(diff, -max_step, max_step) * random_val;
+        }
+    } else if (has_forward && w_forward > w_left && w_forward > w_right) {
+    } else if (has_forward && w_forward < w_left && w_forward < w_right) {
         dir = dir + (random_val - 0.5) * 2.0 * s.turn_speed * dt;
     } else if (w_right > w_left) {
         dir = dir - random_val * s.turn_speed * dt;
     } else if (w_left > w_right) {
         dir = dir + random_val * s.turn_speed * dt;
     }
-    agent.angle = dir;
+    // Same-species avoidance: turn away from the crowd, harder the denser it is
+    if (s.avoid_strength > 0.0) {
+        let push = crowd_push(agent.position, species_index);
+        let crowd = length(push);
+        if (crowd > 1.0e-3) {
+            let away_angle = atan2(push.y, push.x);
+            let diff = atan2(sin(away_angle - agent.angle), cos(away_angle - agent.angle));
+            let max_step = s.avoid_strength * dt * min(crowd, 1.0);
+            dir = dir + clamp(diff, -max_step, max_step);
+        }
+    }
+    // Handedness: a constant drift makes agents circle and trails spiral
+    dir = dir + s.turn_bias * dt;
+    // Optional heading jitter; skipped entirely at zero so steering is unchanged
+    if (s.wander_strength > 0.0) {
+        let jitter = hash_f32(hash_u32(index) ^ hash_u32(globals.frame ^ 0x9e3779b9u));
+        dir = dir + (jitter - 0.5) * 2.0 * s.wander_strength * dt;
+    }
+    // Bound the total deflection so long frames can't spin agents around
+    agent.angle = agent.angle + clamp(dir - agent.angle, -s.max_turn_per_step, s.max_turn_per_step);
     let fwd = vec2<f32>(cos(agent.angle), sin(agent.angle));
-    agent.position = agent.position + fwd * s.move_speed * dt;
-    // Bounce from screen edges and clamp slightly inside to prevent sticking
-    agent.angle = bounce_if_needed(agent.position, agent.angle, globals.screen_size);
-    agent.position = keep_inside(agent.position, globals.screen_size);
+    // Seeded by agent index only, so each agent keeps its speed across frames
+    let speed_r = hash_f32(hash_u32(index ^ 0x85ebca6bu)) * 2.0 - 1.0;
+    let speed = s.move_speed * (1.0 + s.speed_jitter * speed_r);
+    let old_position = agent.position;
+    agent.position = agent.position + fwd * speed * dt;
+    // Walls: stay put and reflect off whichever axis crossed into the wall
+    let new_cell = vec2<i32>(agent.position);
+    if (is_wall(new_cell)) {
+        let old_cell = vec2<i32>(old_position);
+        var v = fwd;
+        let hit_x = is_wall(vec2<i32>(new_cell.x, old_cell.y));
+        let hit_y = is_wall(vec2<i32>(old_cell.x, new_cell.y));
+        if (hit_x) { v.x = -v.x; }
+        if (hit_y) { v.y = -v.y; }
+        // Diagonal corner contact: turn straight back
+        if (!hit_x && !hit_y) { v = -v; }
+        agent.angle = atan2(v.y, v.x);
+        agent.position = old_position;
+    }
+    // Fraction of the nominal step covered (0 when a wall stopped the agent)
+    let nominal_step = speed * dt;
+    let step_frac = select(0.0, clamp(length(agent.position - old_position) / nominal_step, 0.0, 1.0), nominal_step > 0.0);
+    if (globals.wrap_edges != 0u) {
+        // Toroidal world: leaving one edge re-enters on the opposite one
+        agent.position = wrap_if_needed(agent.position, globals.screen_size);
+    } else {
+        // Bounce: reflect heading and overshoot per axis (corners flip both)
+        agent.angle = bounce_if_needed(agent.position, agent.angle, globals.screen_size);
+        agent.position = reflect_inside(agent.position, globals.screen_size);
+    }
+    if (region >= 0 && !in_region(agent.position, region)) {
+        // Region edges bounce like the field edges
+        let rect = regions.rects[region];
+        let eps = 0.25;
+        let lo = rect.xy + vec2<f32>(eps, eps);
+        let hi = rect.zw - vec2<f32>(1.0 + eps, 1.0 + eps);
+        var v = vec2<f32>(cos(agent.angle), sin(agent.angle));
+        if ((agent.position.x < lo.x && v.x < 0.0) || (agent.position.x > hi.x && v.x > 0.0)) { v.x = -v.x; }
+        if ((agent.position.y < lo.y && v.y < 0.0) || (agent.position.y > hi.y && v.y > 0.0)) { v.y = -v.y; }
+        agent.angle = atan2(v.y, v.x);
+        agent.position = clamp(agent.position, lo, hi);
+    }
     let coord = vec2<i32>(i32(agent.position.x), i32(agent.position.y));
-    // Deposit only to the species' configured emit layer
-    let el = i32(s.emit_layer);
-    if (el >= 0) {
-        let cur = textureLoad(phero_array, coord, el).x;
-        let add = s.emit_amount * globals.delta_time;
-        textureStore(phero_array, coord, el, vec4<f32>(cur + add, 0.0, 0.0, 0.0));
+    // Deposit fatigue: strong own trail underfoot tires the agent, weak or
+    // fresh ground lets it recover. Off clears any left from a reload. An
+    // out-of-range emit layer is never deposited into (see `deposit`), so it
+    // isn't read either.
+    if (s.deposit_fatigue <= 0.0) {
+        agent.fatigue = 0.0;
+    } else if (!is_wall(coord) && s.emit_layer < phero_ctrl.layer_count) {
+        let level = clamp(textureLoad(phero_array, coord, i32(s.emit_layer)).x, 0.0, 1.0);
+        agent.fatigue = clamp(agent.fatigue + s.deposit_fatigue * (2.0 * level - 1.0) * dt, 0.0, 1.0);
+    }
+    // Deposit to the species' emit layer plus any extra emit slots, taken
+    // as authored: sensing weights play no part in where agents write
+    if (!is_wall(coord) && !deposit_gated) {
+        let deposit_scale = select(1.0, step_frac, s.deposit_mode == 1u) * globals.delta_time * (1.0 - agent.fatigue);
+        deposit_disc(coord, s.emit_layer, s.emit_amount * deposit_scale, s.deposit_radius, region);
+        for (var k = 0u; k < 4u; k++) {
+            if (s.extra_emit_amounts[k] != 0.0) {
+                let amount = s.extra_emit_amounts[k] * deposit_scale;
+                deposit_disc(coord, s.extra_emit_layers[k], amount, s.deposit_radius, region);
+            }
+        }
+        // Common coordination trail shared by all species
+        if (phero_ctrl.shared_layer != NO_SHARED_LAYER && s.shared_emit_amount != 0.0) {
+            let amount = s.shared_emit_amount * deposit_scale;
+            deposit_disc(coord, phero_ctrl.shared_layer, amount, s.deposit_radius, region);
+        }
     }
     agents[index] = agent;
 }
 
+// Agent dot overlay (optional pass after the composite): each live agent
+// splats a dot_size square of its species color onto the display texture,
+// overwriting it or, with dot_additive, adding dot_strength of the color.
+// Additive splats are a plain load + store, so overlapping agents in the same
+// dispatch may drop some of each other's light; the glow only needs to be
+// roughly proportional to density.
+@group(0) @binding(13) var dots_out: texture_storage_2d<rgba32float, read_write>;
+
+@compute @workgroup_size(#{AGENT_WORKGROUP_SIZE})
+fn draw_agent_dots(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.x;
+    if (index >= arrayLength(&agents)) { return; }
+    let agent = agents[index];
+    if (agent.species_index == INACTIVE_AGENT || phero_ctrl.species_count == 0u) { return; }
+    let species_index = species_slot(agent.species_index);
+    let dims = vec2<i32>(textureDimensions(dots_out));
+    let p = vec2<i32>(agent.position);
+    let color = species[species_index].color.rgb;
+    let size = clamp(phero_ctrl.dot_size, 1u, 5u);
+    // Centered on the agent's pixel; even sizes extend one further right/down
+    let lo = p - vec2<i32>(i32((size - 1u) / 2u));
+    for (var dy = 0u; dy < size; dy++) {
+        for (var dx = 0u; dx < size; dx++) {
+            let q = lo + vec2<i32>(i32(dx), i32(dy));
+            if (any(q < vec2<i32>(0, 0)) || any(q >= dims)) { continue; }
+            if (phero_ctrl.dot_additive != 0u) {
+                let prev = textureLoad(dots_out, q).rgb;
+                textureStore(dots_out, q, vec4<f32>(prev + color * phero_ctrl.dot_strength, 1.0));
+            } else {
+                textureStore(dots_out, q, vec4<f32>(color, 1.0));
+            }
+        }
+    }
+}
+
+// Sensor marker overlay (debug, same bindings as the dots): a few agents
+// spread over the buffer mark where their sensor fan samples, mirroring
+// update_agents (at the base sensor_offset_dst; adaptive species may reach
+// further). Left = green, forward = white, right = magenta; the agent itself
+// in its species color. Gradient-steering species show only the agent.
+// Matches SENSOR_MARKER_AGENTS on the CPU
+const SENSOR_MARKER_AGENTS: u32 = 16u;
+
+fn draw_marker(p: vec2<f32>, color: vec3<f32>, dims: vec2<i32>) {
+    let c = vec2<i32>(floor(p));
+    for (var dy = -1; dy <= 1; dy++) {
+        for (var dx = -1; dx <= 1; dx++) {
+            let q = c + vec2<i32>(dx, dy);
+            if (any(q < vec2<i32>(0, 0)) || any(q >= dims)) { continue; }
+            textureStore(dots_out, q, vec4<f32>(color, 1.0));
+        }
+    }
+}
+
+@compute @workgroup_size(#{AGENT_WORKGROUP_SIZE})
+fn draw_sensor_markers(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (id.x >= SENSOR_MARKER_AGENTS) { return; }
+    let stride = max(arrayLength(&agents) / SENSOR_MARKER_AGENTS, 1u);
+    let index = id.x * stride;
+    if (index >= arrayLength(&agents)) { return; }
+    let agent = agents[index];
+    if (agent.species_index == INACTIVE_AGENT || phero_ctrl.species_count == 0u) { return; }
+    let s = species[species_slot(agent.species_index)];
+    let dims = vec2<i32>(textureDimensions(dots_out));
+    if (s.steer_mode != STEER_GRADIENT) {
+        let n = clamp(s.sensor_count, 1u, MAX_SENSORS);
+        let sensor_angle = sensor_fan_angle(s);
+        for (var i = 0u; i < n; i++) {
+            let offset = sensor_offset_angle(i, n, sensor_angle);
+            let a = agent.angle + offset;
+            let p = agent.position + vec2<f32>(cos(a), sin(a)) * s.sensor_offset_dst;
+            var color = vec3<f32>(1.0, 1.0, 1.0);
+            if (offset > 0.0) {
+                color = vec3<f32>(0.2, 1.0, 0.2);
+            } else if (offset < 0.0) {
+                color = vec3<f32>(1.0, 0.2, 1.0);
+            }
+            draw_marker(p, color, dims);
+        }
+    }
+    // Last, so the agent stays visible under a short sensor offset
+    draw_marker(agent.position, s.color.rgb, dims);
+}
+
+// Bound on stored pheromone magnitude; matches pheromones.wgsl
+const MAX_PHEROMONE: f32 = 1e6;
+
+// Non-finite -> 0, otherwise clamped to +/-MAX_PHEROMONE (bit test, since
+// float compares may be assumed NaN-free)
+fn sanitize(v: f32) -> f32 {
+    if ((bitcast<u32>(v) & 0x7f800000u) == 0x7f800000u) { return 0.0; }
+    return clamp(v, -MAX_PHEROMONE, MAX_PHEROMONE);
+}
+
+// Add `amount` to one layer at `coord`, capped by the layer's max_value.
+// Paint-only layers are sensed but never deposited into.
+fn deposit(coord: vec2<i32>, layer: u32, amount: f32) {
+    if (layer >= phero_ctrl.layer_count) { return; }
+    if (layer < 32u && (phero_ctrl.paint_only_mask & (1u << layer)) != 0u) { return; }
+    let cur = textureLoad(phero_array, coord, i32(layer)).x;
+    let value = sanitize(cap_layer(cur + sanitize(amount), layer));
+    textureStore(phero_array, coord, i32(layer), vec4<f32>(value, 0.0, 0.0, 0.0));
+}
+
+// Matches MAX_DEPOSIT_RADIUS on the CPU
+const MAX_DEPOSIT_RADIUS: i32 = 4;
+
+// Whether texel center + (dx, dy) takes a share of a disc stamp: inside the
+// disc, on the field, not a wall, and in the depositing agent's region
+fn in_stamp(center: vec2<i32>, dx: i32, dy: i32, r2: f32, dims: vec2<i32>, region: i32) -> bool {
+    if (f32(dx * dx + dy * dy) > r2) { return false; }
+    let p = center + vec2<i32>(dx, dy);
+    if (any(p < vec2<i32>(0)) || any(p >= dims)) { return false; }
+    return !is_wall(p) && in_region(vec2<f32>(p), region);
+}
+
+// Spread `amount` evenly over the texels of a disc of `radius` around
+// `center`, so the total deposited matches a single-texel deposit.
+//
+// Races: agents run concurrently and `deposit` is a plain load/add/store on
+// a read_write r32float storage texture (no float atomics), so when stamps
+// from different agents overlap a texel within one dispatch, one of the
+// additions can be lost. Overlaps are additive-but-racy: the field may
+// under-count slightly, as single-texel deposits already could, but values
+// never go wrong in any other way. Larger radii overlap more often.
+fn deposit_disc(center: vec2<i32>, layer: u32, amount: f32, radius: f32, region: i32) {
+    if (radius < 1.0) {
+        deposit(center, layer, amount);
+        return;
+    }
+    let r = min(i32(ceil(radius)), MAX_DEPOSIT_RADIUS);
+    let r2 = radius * radius;
+    let dims = vec2<i32>(textureDimensions(phero_array));
+    var count = 0.0;
+    for (var dy = -r; dy <= r; dy++) {
+        for (var dx = -r; dx <= r; dx++) {
+            if (in_stamp(center, dx, dy, r2, dims, region)) { count += 1.0; }
+        }
+    }
+    let share = amount / max(count, 1.0);
+    for (var dy = -r; dy <= r; dy++) {
+        for (var dx = -r; dx <= r; dx++) {
+            if (in_stamp(center, dx, dy, r2, dims, region)) {
+                deposit(center + vec2<i32>(dx, dy), layer, share);
+            }
+        }
+    }
+}
+
 // helpers (movement utils)
 
+// Reflect only the velocity components heading out of bounds, so an agent
+// already turned back inward is never flipped a second time.
 fn bounce_if_needed(position: vec2<f32>, direction: f32, size: vec2<f32>) -> f32 {
-    var dir = direction;
-    if (position.x <= 0.0 || position.x >= size.x - 1.0) {
-        dir = 3.14159265 - dir;
+    var v = vec2<f32>(cos(direction), sin(direction));
+    if ((position.x <= 0.0 && v.x < 0.0) || (position.x >= size.x - 1.0 && v.x > 0.0)) {
+        v.x = -v.x;
     }
-    if (position.y <= 0.0 || position.y >= size.y - 1.0) {
-        dir = -dir;
+    if ((position.y <= 0.0 && v.y < 0.0) || (position.y >= size.y - 1.0 && v.y > 0.0)) {
+        v.y = -v.y;
     }
-    return dir;
+    return atan2(v.y, v.x);
 }
 
-fn keep_inside(position: vec2<f32>, size: vec2<f32>) -> vec2<f32> {
+// Mirror any overshoot back across the edge, then clamp as a guard against
+// steps longer than the field.
+fn reflect_inside(position: vec2<f32>, size: vec2<f32>) -> vec2<f32> {
     let eps = 0.25;
-    return vec2<f32>(
-        clamp(position.x, eps, size.x - 1.0 - eps),
-        clamp(position.y, eps, size.y - 1.0 - eps),
-    );
+    let hi = size - vec2<f32>(1.0 + eps, 1.0 + eps);
+    var pos = position;
+    if (pos.x < eps) { pos.x = 2.0 * eps - pos.x; }
+    if (pos.x > hi.x) { pos.x = 2.0 * hi.x - pos.x; }
+    if (pos.y < eps) { pos.y = 2.0 * eps - pos.y; }
+    if (pos.y > hi.y) { pos.y = 2.0 * hi.y - pos.y; }
+    return clamp(pos, vec2<f32>(eps, eps), hi);
 }
 
 fn wrap_if_needed(position: vec2<f32>, size: vec2<f32>) -> vec2<f32> {
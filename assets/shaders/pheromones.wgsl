@@ -1,4 +1,7 @@
 // Pheromone-specific compute shaders: extract, composite, per-pheromone env passes
+//
+// WORKGROUP_SIZE is a shader def supplied by the pipelines (see
+// `render::workgroup_shader_defs`) so the dispatch math and the shader agree.
 
 struct GlobalUniforms {
     delta_time: f32,
@@ -7,14 +10,28 @@ struct GlobalUniforms {
     screen_size: vec2<f32>,
     left_button_pressed: u32,
     right_button_pressed: u32,
+    species_offset: u32,
+    species_count: u32,
+    wrap_edges: u32,
+    wrap_diffusion: u32, // nonzero: neighbors wrap around the edges (wrap_diffusion or wrapping agents)
 };
 
 struct PheromoneLayerParam {
     diffusion: f32,
     decay: f32,
-    _pad0: f32,
-    _pad1: f32,
+    kernel: u32, // 0 = box (5-tap cross), 1 = 3x3 gaussian
+    frozen: u32, // nonzero: copied through unchanged (static field)
     color: vec4<f32>,
+    max_value: f32, // brush/deposit cap; <= 0 = unbounded
+    diagonal_weight: f32, // relative to edge neighbors; <= 0 = kernel default
+    diffuse_enabled: u32, // zero: no blur (decay only)
+    decay_enabled: u32, // zero: no decay (spread only)
+    flow_dir: vec2<f32>, // drift direction (any length)
+    flow_strength: f32, // drift speed in texels/second; 0 = isotropic
+    decay_floor: f32, // values above decay toward this instead of 0; 0 = plain decay
+    brush_amount: f32, // paint target / beacon multiplier for the brush; 1 = plain
+    conserve_mass: u32, // nonzero: redistribute instead of blur; no decay or flow
+    _pad: array<u32, 2>,
 };
 
 // per-frame factors are now precomputed on CPU and uploaded in layer_params
@@ -28,46 +45,175 @@ struct PheromoneLayerParam {
 @group(0) @binding(1) var next_array: texture_storage_2d_array<r32float, read_write>;
 @group(0) @binding(2) var<uniform> globals_array: GlobalUniforms;
 @group(0) @binding(3) var<storage, read> layer_params_array: array<PheromoneLayerParam>;
-struct BrushControl { target_layer: u32, _mode: u32, _pad: vec2<u32> };
+// falloff: 0 = quadratic, 1 = constant, 2 = linear, 3 = gaussian
+// beacon != 0: left click adds beacon_amplitude/s within beacon_radius instead of painting
+// active != 0 while a brush button is held; target_layer is that button's layer
+struct BrushControl {
+    target_layer: u32,
+    falloff: u32,
+    beacon: u32,
+    border_layer: u32,
+    beacon_radius: f32,
+    beacon_amplitude: f32,
+    active: u32,
+    erase: u32, // nonzero: the stroke erases to 0.0 instead of depositing
+};
 @group(0) @binding(4) var<uniform> brush_ctrl: BrushControl;
+// Independent regions (min.xy, max.xy); count 0 = whole field
+struct Regions { count: u32, _pad: vec3<u32>, rects: array<vec4<f32>, 16> };
+@group(0) @binding(5) var<uniform> regions_env: Regions;
+
+// Index of the first region containing p, or -1 (regions off / outside all)
+fn region_of_env(p: vec2<f32>) -> i32 {
+    for (var i = 0u; i < min(regions_env.count, 16u); i++) {
+        let rect = regions_env.rects[i];
+        if (p.x >= rect.x && p.y >= rect.y && p.x < rect.z && p.y < rect.w) { return i32(i); }
+    }
+    return -1;
+}
+
+// Bound on stored pheromone magnitude; matches agents.wgsl
+const MAX_PHEROMONE: f32 = 1e6;
+
+// Non-finite values become 0 and the rest are clamped to +/-MAX_PHEROMONE, so
+// a pathological rate can't poison the field (or the display) for good.
+// Checked on the bits: the compiler may assume float compares never see NaN.
+fn sanitize(v: f32) -> f32 {
+    if ((bitcast<u32>(v) & 0x7f800000u) == 0x7f800000u) { return 0.0; }
+    return clamp(v, -MAX_PHEROMONE, MAX_PHEROMONE);
+}
+
+// Neighbor texel at coord + offset: wraps per axis when wrap_diffusion is set
+// (a corner's diagonal neighbor is the opposite corner), clamps otherwise.
+// CPU copy in the `pheromones` tests.
+fn neighbor_coord(coord: vec2<i32>, offset: vec2<i32>, dims: vec2<u32>) -> vec2<i32> {
+    let size = vec2<i32>(dims);
+    let n = coord + offset;
+    if (globals_array.wrap_diffusion != 0u) {
+        return (n % size + size) % size;
+    }
+    return clamp(n, vec2<i32>(0), size - 1);
+}
+
+// Value drifting into `coord` this step: a bilinear sample of prev at
+// coord - shift, with shift = flow_dir * flow_strength * dt capped at one
+// texel so the upwind 2x2 cell covers it. Zero flow returns `c` untouched.
+fn flow_source(coord: vec2<i32>, dims: vec2<u32>, l: i32, c: f32, layer: PheromoneLayerParam) -> f32 {
+    let dir_len = length(layer.flow_dir);
+    if (layer.flow_strength == 0.0 || dir_len == 0.0) { return c; }
+    let speed = layer.flow_strength * globals_array.delta_time;
+    let shift = clamp(layer.flow_dir / dir_len * speed, vec2<f32>(-1.0), vec2<f32>(1.0));
+    let step = vec2<i32>(select(1, -1, shift.x > 0.0), select(1, -1, shift.y > 0.0));
+    let nx = neighbor_coord(coord, vec2<i32>(step.x, 0), dims);
+    let ny = neighbor_coord(coord, vec2<i32>(0, step.y), dims);
+    let nd = neighbor_coord(coord, step, dims);
+    let a = abs(shift);
+    let row0 = mix(c, textureLoad(prev_array, nx, l).x, a.x);
+    let row1 = mix(textureLoad(prev_array, ny, l).x, textureLoad(prev_array, nd, l).x, a.x);
+    return mix(row0, row1, a.y);
+}
 
-@compute @workgroup_size(16, 16, 1)
+// One decay step by per-frame factor dec: values above a positive floor lose
+// that fraction of their excess over it, everything else of itself
+fn decay_to_floor(v: f32, dec: f32, floor: f32) -> f32 {
+    if (floor > 0.0 && v > floor) {
+        return floor + (v - floor) * (1.0 - dec);
+    }
+    return v * (1.0 - dec);
+}
+
+@compute @workgroup_size(#{WORKGROUP_SIZE}, #{WORKGROUP_SIZE}, 1)
 fn diffuse_phero_array(@builtin(global_invocation_id) id: vec3<u32>) {
     let dims = textureDimensions(prev_array);
     let x = id.x; let y = id.y; let l = i32(id.z);
     if (x >= dims.x || y >= dims.y) { return; }
     let coord = vec2<i32>(i32(x), i32(y));
     let dt = globals_array.delta_time;
-    let left   = vec2<i32>(i32(max(1u, x)) - 1, i32(y));
-    let right  = vec2<i32>(i32(min(dims.x - 2u, x)) + 1, i32(y));
-    let up     = vec2<i32>(i32(x), i32(min(dims.y - 2u, y)) + 1);
-    let down   = vec2<i32>(i32(x), i32(max(1u, y)) - 1);
+    let left   = neighbor_coord(coord, vec2<i32>(-1, 0), dims);
+    let right  = neighbor_coord(coord, vec2<i32>(1, 0), dims);
+    let up     = neighbor_coord(coord, vec2<i32>(0, 1), dims);
+    let down   = neighbor_coord(coord, vec2<i32>(0, -1), dims);
     let c = textureLoad(prev_array, coord, l).x;
+    // Frozen layers skip diffusion and decay but must still reach the next buffer
+    if (layer_params_array[id.z].frozen != 0u) {
+        textureStore(next_array, coord, l, vec4<f32>(sanitize(c), 0.0, 0.0, 0.0));
+        return;
+    }
+    let layer = layer_params_array[id.z];
+    // Mass-conserving layers trade diffusion/4 of their value with each edge
+    // neighbor, so whatever one texel gives another receives. A clamped edge
+    // neighbor is the texel itself and trades nothing. No decay or flow.
+    // CPU copy in the `pheromones` tests.
+    if (layer.conserve_mass != 0u) {
+        let share = select(0.0, layer.diffusion * 0.25, layer.diffuse_enabled != 0u);
+        let lval = textureLoad(prev_array, left, l).x;
+        let rval = textureLoad(prev_array, right, l).x;
+        let uval = textureLoad(prev_array, up, l).x;
+        let dval = textureLoad(prev_array, down, l).x;
+        let result = c + share * (lval + rval + uval + dval - 4.0 * c);
+        textureStore(next_array, coord, l, vec4<f32>(sanitize(result), 0.0, 0.0, 0.0));
+        return;
+    }
+    // Upwind value for layers with a flow (== c otherwise)
+    let src = flow_source(coord, dims, l, c, layer);
+    // Decay-only layers skip the blur's neighborhood fetches
+    if (layer.diffuse_enabled == 0u) {
+        let kept = select(src, decay_to_floor(src, layer.decay, layer.decay_floor), layer.decay_enabled != 0u);
+        textureStore(next_array, coord, l, vec4<f32>(sanitize(kept), 0.0, 0.0, 0.0));
+        return;
+    }
     let lval = textureLoad(prev_array, left, l).x;
     let rval = textureLoad(prev_array, right, l).x;
     let uval = textureLoad(prev_array, up, l).x;
     let dval = textureLoad(prev_array, down, l).x;
-    let blurred = (c * 4.0 + lval + rval + uval + dval) * 0.125;
-    let layer = layer_params_array[id.z];
+    // Weights relative to an edge neighbor (1). Box: center 4, no diagonals;
+    // 3x3 gaussian: center 2, diagonals 0.5 (the 4-2-1 kernel). A positive
+    // diagonal_weight overrides the diagonal weight of either kernel.
+    let is_gauss = layer.kernel == 1u;
+    let center_w = select(4.0, 2.0, is_gauss);
+    let diag_w = select(select(0.0, 0.5, is_gauss), layer.diagonal_weight, layer.diagonal_weight > 0.0);
+    var sum = c * center_w + lval + rval + uval + dval;
+    if (diag_w > 0.0) {
+        // Axes wrap independently, so corner texels pick up the opposite corner
+        let ul = textureLoad(prev_array, vec2<i32>(left.x, up.y), l).x;
+        let ur = textureLoad(prev_array, vec2<i32>(right.x, up.y), l).x;
+        let dl = textureLoad(prev_array, vec2<i32>(left.x, down.y), l).x;
+        let dr = textureLoad(prev_array, vec2<i32>(right.x, down.y), l).x;
+        sum += (ul + ur + dl + dr) * diag_w;
+    }
+    let blurred = sum / (center_w + 4.0 + 4.0 * diag_w);
     // diffusion and decay fields hold per-frame factors now
     let diff_factor = layer.diffusion;
-    let dec_factor  = layer.decay;
-    let mixed = mix(c, blurred, diff_factor);
-    let result = mixed * (1.0 - dec_factor);
-    textureStore(next_array, coord, l, vec4<f32>(result, 0.0, 0.0, 0.0));
+    let dec_factor  = select(0.0, layer.decay, layer.decay_enabled != 0u);
+    // Isotropic blur plus the drift (src - c is exactly 0 without flow)
+    let mixed = mix(c, blurred, diff_factor) + (src - c);
+    let result = decay_to_floor(mixed, dec_factor, layer.decay_floor);
+    textureStore(next_array, coord, l, vec4<f32>(sanitize(result), 0.0, 0.0, 0.0));
 }
 
 // Input (brush) in-place on next_array
-@compute @workgroup_size(16, 16, 1)
+// Brush strength at normalized distance u = d / radius in [0, 1)
+fn brush_falloff(u: f32) -> f32 {
+    let t = 1.0 - u;
+    switch brush_ctrl.falloff {
+        case 1u: { return 1.0; }
+        case 2u: { return t; }
+        // sigma = radius / 3: exp(-4.5) ~ 1% at the edge
+        case 3u: { return exp(-4.5 * u * u); }
+        default: { return t * t; }
+    }
+}
+
+@compute @workgroup_size(#{WORKGROUP_SIZE}, #{WORKGROUP_SIZE}, 1)
 fn handle_input_phero_array(@builtin(global_invocation_id) id: vec3<u32>) {
     let dims = textureDimensions(next_array);
     let x = id.x; let y = id.y; let l = i32(id.z);
     if (x >= dims.x || y >= dims.y) { return; }
     let coord = vec2<i32>(i32(x), i32(y));
 
-    // Seed "avoid" pheromone on screen edges using a squircle (hate layer = 0)
+    // Seed "avoid" pheromone on screen edges using a squircle (hate layer)
     let border: f32 = 4.0;
-    if (l == 0) {
+    if (u32(l) == brush_ctrl.border_layer) {
         // Squircle formula: distance from edge using superellipse with power=4
         let cx = f32(x) - f32(dims.x) * 0.5;
         let cy = f32(y) - f32(dims.y) * 0.5;
@@ -82,51 +228,284 @@ fn handle_input_phero_array(@builtin(global_invocation_id) id: vec3<u32>) {
     }
 
     // Mouse brush: gate only this section on button state
-    if (globals_array.left_button_pressed == 0u && globals_array.right_button_pressed == 0u) { return; }
+    if (brush_ctrl.active == 0u) { return; }
     if (globals_array.mouse_position.x < -9000.0) { return; }
     let pixel_pos = vec2<f32>(f32(x), f32(y));
-    let brush_radius = 80.0;
+    let is_beacon = brush_ctrl.beacon != 0u;
+    let brush_radius = select(80.0, max(brush_ctrl.beacon_radius, 1.0), is_beacon);
     let d = distance(pixel_pos, globals_array.mouse_position);
     if (d >= brush_radius) { return; }
-    let t = 1.0 - (d / brush_radius);
-    let brush_strength = t * t;
-    // Paint only the selected layer via uniform control; left = deposit to 1.0, right = erase to 0.0
-    let is_left = globals_array.left_button_pressed != 0u;
-    let is_right = globals_array.right_button_pressed != 0u;
-    let should_paint = (l == i32(brush_ctrl.target_layer)) && (is_left || is_right);
-    if (!should_paint) { return; }
+    let brush_strength = brush_falloff(d / brush_radius);
+    // Paint only the held button's layer; deposit toward brush_amount, or erase to 0.0
+    let is_erase = brush_ctrl.erase != 0u;
+    if (l != i32(brush_ctrl.target_layer)) { return; }
+    // With regions, the brush stays inside the region under the cursor
+    if (regions_env.count > 0u && region_of_env(pixel_pos) != region_of_env(globals_array.mouse_position)) { return; }
     let current = textureLoad(next_array, coord, l).x;
-    let brush_val: f32 = select(0.0, 1.0, is_left); // left deposits, right erases to 0.0
-    let altered = mix(current, brush_val, brush_strength);
-    textureStore(next_array, coord, l, vec4<f32>(altered, 0.0, 0.0, 0.0));
+    // Per-layer brush strength: paint pulls toward it instead of 1.0
+    let amount = layer_params_array[brush_ctrl.target_layer].brush_amount;
+    let brush_val: f32 = select(amount, 0.0, is_erase);
+    var altered = mix(current, brush_val, brush_strength);
+    // The beacon accumulates (then diffuses/decays) rather than saturating
+    if (is_beacon && !is_erase) {
+        altered = current + brush_ctrl.beacon_amplitude * amount * brush_strength * globals_array.delta_time;
+    }
+    let max_value = layer_params_array[id.z].max_value;
+    if (max_value > 0.0) { altered = min(altered, max_value); }
+    textureStore(next_array, coord, l, vec4<f32>(sanitize(altered), 0.0, 0.0, 0.0));
+}
+
+// Emitters: persistent point sources stamped after the brush, one invocation
+// per emitter. Overlapping emitters on one layer may race on shared texels;
+// one of the writes wins for that frame, which is harmless for a source.
+struct Emitter {
+    pos: vec2<f32>,
+    layer: u32,
+    amount: f32, // per second at the centre
+};
+
+@group(0) @binding(6) var<storage, read> emitters: array<Emitter>;
+
+// EMITTER_RADIUS in resources.rs, as a shader def
+const EMITTER_RADIUS: i32 = #{EMITTER_RADIUS};
+
+@compute @workgroup_size(#{WORKGROUP_SIZE}, 1, 1)
+fn stamp_emitters(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (id.x >= arrayLength(&emitters)) { return; }
+    let e = emitters[id.x];
+    if (e.amount == 0.0 || e.layer >= textureNumLayers(next_array)) { return; }
+    let dims = vec2<i32>(textureDimensions(next_array));
+    let l = i32(e.layer);
+    let centre = vec2<i32>(round(e.pos));
+    let radius = f32(EMITTER_RADIUS);
+    let max_value = layer_params_array[e.layer].max_value;
+    for (var dy = -EMITTER_RADIUS; dy <= EMITTER_RADIUS; dy++) {
+        for (var dx = -EMITTER_RADIUS; dx <= EMITTER_RADIUS; dx++) {
+            let coord = centre + vec2<i32>(dx, dy);
+            if (any(coord < vec2<i32>(0)) || any(coord >= dims)) { continue; }
+            let d = distance(vec2<f32>(coord), e.pos);
+            if (d >= radius) { continue; }
+            let t = 1.0 - d / radius;
+            var v = textureLoad(next_array, coord, l).x + e.amount * t * t * globals_array.delta_time;
+            if (max_value > 0.0) { v = min(v, max_value); }
+            textureStore(next_array, coord, l, vec4<f32>(sanitize(v), 0.0, 0.0, 0.0));
+        }
+    }
 }
 
 // Composite array -> RGBA display using per-layer colors
 @group(0) @binding(0) var p_in_array: texture_storage_2d_array<r32float, read>;
 @group(0) @binding(1) var rgba_out_array: texture_storage_2d<rgba32float, write>;
 @group(0) @binding(2) var<storage, read> layer_params_comp: array<PheromoneLayerParam>;
+// blend_mode: 0 = weighted average (default), 1 = additive, 2 = max
+// exposure scales the blended color; tonemap != 0 applies Reinhard afterwards.
+// background fills empty pixels (alpha 0 there when transparent_background != 0)
+struct CompositeControl {
+    blend_mode: u32,
+    exposure: f32,
+    tonemap: u32,
+    transparent_background: u32,
+    background: vec4<f32>,
+    // Nonzero: show solo_layer's raw value as grayscale instead of the blend
+    solo: u32,
+    solo_layer: u32,
+    _pad: vec2<u32>,
+};
+@group(0) @binding(3) var<uniform> composite_ctrl: CompositeControl;
 
-@compute @workgroup_size(16, 16, 1)
+@compute @workgroup_size(#{WORKGROUP_SIZE}, #{WORKGROUP_SIZE}, 1)
 fn composite_pheromones_array(@builtin(global_invocation_id) id: vec3<u32>) {
     let dims = textureDimensions(rgba_out_array);
     let x = id.x; let y = id.y;
     if (x >= dims.x || y >= dims.y) { return; }
     let coord = vec2<i32>(i32(x), i32(y));
+    // Guard against a params buffer that doesn't match the array depth
+    let layer_count = min(arrayLength(&layer_params_comp), textureNumLayers(p_in_array));
+    if (composite_ctrl.solo != 0u) {
+        // Debug view: one layer's scalar as opaque grayscale (out of range = black)
+        var v = 0.0;
+        if (composite_ctrl.solo_layer < layer_count) {
+            v = max(textureLoad(p_in_array, coord, i32(composite_ctrl.solo_layer)).x, 0.0);
+        }
+        v = v * composite_ctrl.exposure;
+        if (composite_ctrl.tonemap != 0u) {
+            v = v / (1.0 + v);
+        }
+        textureStore(rgba_out_array, coord, vec4<f32>(v, v, v, 1.0));
+        return;
+    }
     var accum = vec3<f32>(0.0, 0.0, 0.0);
+    var peak = vec3<f32>(0.0, 0.0, 0.0);
     var total = 0.0;
-    let layer_count = arrayLength(&layer_params_comp);
+    // Every layer is colorized by its own param color, independent of species
+    // count
     for (var li: u32 = 0u; li < layer_count; li = li + 1u) {
         let v = textureLoad(p_in_array, coord, i32(li)).x;
         let p = layer_params_comp[li];
-        accum += v * p.color.rgb;
+        let contrib = v * p.color.rgb;
+        accum += contrib;
+        peak = max(peak, contrib);
         total += v;
     }
-    // Normalize color by total intensity to preserve hue
-    let color = select(vec3<f32>(0.0, 0.0, 0.0), accum / total, total > 0.0);
-    // Cheap tone mapping for brightness from total intensity (avoids exp)
-    // approx of 1 - exp(-x): x / (1 + x)
-    let brightness = total / (1.0 + total);
-    textureStore(rgba_out_array, coord, vec4<f32>(color * brightness, 1.0));
+    var out_rgb: vec3<f32>;
+    switch composite_ctrl.blend_mode {
+        case 1u: {
+            out_rgb = accum;
+        }
+        case 2u: {
+            out_rgb = peak;
+        }
+        default: {
+            // Normalize color by total intensity to preserve hue
+            let color = select(vec3<f32>(0.0, 0.0, 0.0), accum / total, total > 0.0);
+            // Cheap tone mapping for brightness from total intensity (avoids exp)
+            // approx of 1 - exp(-x): x / (1 + x)
+            let brightness = total / (1.0 + total);
+            out_rgb = color * brightness;
+        }
+    }
+    out_rgb = out_rgb * composite_ctrl.exposure;
+    if (composite_ctrl.tonemap != 0u) {
+        out_rgb = out_rgb / (vec3<f32>(1.0, 1.0, 1.0) + out_rgb);
+    }
+    // Background shows through where the field is weak; black adds nothing
+    let coverage = max(total, 0.0) / (1.0 + max(total, 0.0));
+    out_rgb = out_rgb + composite_ctrl.background.rgb * (1.0 - coverage);
+    var alpha = 1.0;
+    if (composite_ctrl.transparent_background != 0u && total <= 0.0) {
+        out_rgb = vec3<f32>(0.0, 0.0, 0.0);
+        alpha = 0.0;
+    }
+    textureStore(rgba_out_array, coord, vec4<f32>(out_rgb, alpha));
+}
+
+// Clear: zero every layer of one array (dispatched once per request, z = layer)
+@group(0) @binding(0) var clear_array: texture_storage_2d_array<r32float, write>;
+
+@compute @workgroup_size(#{WORKGROUP_SIZE}, #{WORKGROUP_SIZE}, 1)
+fn clear_phero_array(@builtin(global_invocation_id) id: vec3<u32>) {
+    let dims = textureDimensions(clear_array);
+    let x = id.x; let y = id.y; let l = i32(id.z);
+    if (x >= dims.x || y >= dims.y) { return; }
+    textureStore(clear_array, vec2<i32>(i32(x), i32(y)), l, vec4<f32>(0.0, 0.0, 0.0, 0.0));
+}
+
+// Probe: copy each layer's value under the mouse into a small buffer that the
+// CPU reads back for the overlay (one invocation per layer)
+@group(0) @binding(0) var probe_array: texture_storage_2d_array<r32float, read>;
+@group(0) @binding(1) var<storage, read_write> probe_out: array<f32>;
+@group(0) @binding(2) var<uniform> probe_globals: GlobalUniforms;
+
+@compute @workgroup_size(64, 1, 1)
+fn probe_phero_array(@builtin(global_invocation_id) id: vec3<u32>) {
+    let l = id.x;
+    if (l >= arrayLength(&probe_out)) { return; }
+    let dims = textureDimensions(probe_array);
+    let p = probe_globals.mouse_position;
+    // Layers past the array depth, or a cursor off the field, read as zero
+    let inside = p.x >= 0.0 && p.y >= 0.0 && p.x < f32(dims.x) && p.y < f32(dims.y);
+    if (l >= textureNumLayers(probe_array) || !inside) {
+        probe_out[l] = 0.0;
+        return;
+    }
+    probe_out[l] = textureLoad(probe_array, vec2<i32>(p), i32(l)).x;
+}
+
+// Stats: min / max / mean of one layer (workgroup z) over the whole array,
+// reduced in workgroup memory. Writes [min, max, mean, 0] per layer to the
+// buffer bound as probe_out.
+const STATS_THREADS: u32 = 256u;
+var<workgroup> stats_min: array<f32, 256>;
+var<workgroup> stats_max: array<f32, 256>;
+var<workgroup> stats_sum: array<f32, 256>;
+
+@compute @workgroup_size(256, 1, 1)
+fn reduce_phero_stats(
+    @builtin(local_invocation_index) t: u32,
+    @builtin(workgroup_id) wg: vec3<u32>,
+) {
+    let l = wg.z;
+    let dims = textureDimensions(probe_array);
+    let texels = dims.x * dims.y;
+    var mn = 3.4e38;
+    var mx = -3.4e38;
+    var sum = 0.0;
+    for (var i = t; i < texels; i += STATS_THREADS) {
+        let v = textureLoad(probe_array, vec2<i32>(i32(i % dims.x), i32(i / dims.x)), i32(l)).x;
+        mn = min(mn, v);
+        mx = max(mx, v);
+        sum += v;
+    }
+    stats_min[t] = mn;
+    stats_max[t] = mx;
+    stats_sum[t] = sum;
+    workgroupBarrier();
+    for (var stride = STATS_THREADS / 2u; stride > 0u; stride = stride / 2u) {
+        if (t < stride) {
+            stats_min[t] = min(stats_min[t], stats_min[t + stride]);
+            stats_max[t] = max(stats_max[t], stats_max[t + stride]);
+            stats_sum[t] = stats_sum[t] + stats_sum[t + stride];
+        }
+        workgroupBarrier();
+    }
+    let o = l * 4u;
+    if (t == 0u && o + 3u < arrayLength(&probe_out)) {
+        probe_out[o] = stats_min[0];
+        probe_out[o + 1u] = stats_max[0];
+        probe_out[o + 2u] = stats_sum[0] / f32(max(texels, 1u));
+        probe_out[o + 3u] = 0.0;
+    }
+}
+
+// Display encode: linear Rgba32Float display -> 8-bit sRGB-encoded target the
+// sprite samples through an Rgba8UnormSrgb view (see `SrgbDisplay`)
+@group(0) @binding(0) var display_in: texture_storage_2d<rgba32float, read>;
+@group(0) @binding(1) var display_srgb_out: texture_storage_2d<rgba8unorm, write>;
+
+// A display channel clamped to [0, 1]: NaN -> 0, +inf -> 1, -inf -> 0, the
+// same as `linear_to_srgb_u8` in capture.rs. Non-finite values are caught on
+// the bits, since float compares may be assumed NaN-free.
+fn display_channel(v: f32) -> f32 {
+    let bits = bitcast<u32>(v);
+    if ((bits & 0x7f800000u) == 0x7f800000u) {
+        return select(0.0, 1.0, bits == 0x7f800000u);
+    }
+    return clamp(v, 0.0, 1.0);
+}
+
+fn linear_to_srgb(v: vec3<f32>) -> vec3<f32> {
+    let c = vec3<f32>(display_channel(v.x), display_channel(v.y), display_channel(v.z));
+    let lo = c * 12.92;
+    let hi = 1.055 * pow(c, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(hi, lo, c <= vec3<f32>(0.0031308));
+}
+
+// Agent view: instead of the composite, last frame's display fades toward
+// the background into this frame's, and the agent dots go on top
+@group(0) @binding(0) var fade_in: texture_storage_2d<rgba32float, read>;
+@group(0) @binding(1) var fade_out: texture_storage_2d<rgba32float, write>;
+struct DisplayFade {
+    background: vec4<f32>,
+    keep: f32, // fraction of last frame's color kept
+};
+@group(0) @binding(2) var<uniform> fade_ctrl: DisplayFade;
+
+@compute @workgroup_size(#{WORKGROUP_SIZE}, #{WORKGROUP_SIZE}, 1)
+fn fade_display(@builtin(global_invocation_id) id: vec3<u32>) {
+    let dims = textureDimensions(fade_out);
+    if (id.x >= dims.x || id.y >= dims.y) { return; }
+    let coord = vec2<i32>(i32(id.x), i32(id.y));
+    let previous = textureLoad(fade_in, coord);
+    textureStore(fade_out, coord, mix(fade_ctrl.background, previous, fade_ctrl.keep));
+}
+
+@compute @workgroup_size(#{WORKGROUP_SIZE}, #{WORKGROUP_SIZE}, 1)
+fn encode_display_srgb(@builtin(global_invocation_id) id: vec3<u32>) {
+    let dims = textureDimensions(display_srgb_out);
+    if (id.x >= dims.x || id.y >= dims.y) { return; }
+    let coord = vec2<i32>(i32(id.x), i32(id.y));
+    let c = textureLoad(display_in, coord);
+    textureStore(display_srgb_out, coord, vec4<f32>(linear_to_srgb(c.rgb), clamp(c.a, 0.0, 1.0)));
 }
 
 // Legacy per-pheromone env passes removed; array variants above are the only active path.
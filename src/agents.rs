@@ -12,7 +12,11 @@ use bevy::render::render_resource::{Buffer, ShaderType};
 use bevy::render::render_resource::{BufferInitDescriptor, BufferUsages};
 use bevy::render::renderer::{RenderDevice, RenderQueue};
 use bytemuck::{Pod, Zeroable};
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::species::{AgentSpecies, SpeciesHeading, authored_species_count, species_heading_modes};
 
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable, ShaderType)]
@@ -20,6 +24,52 @@ pub struct Agent {
     pub position: Vec2,
     pub angle: f32,
     pub species_index: u32,
+    /// Seconds since (re)spawn; `agents.wgsl` respawns the agent once this
+    /// reaches its species' `max_age`.
+    pub age: f32,
+    /// Deposit fatigue in [0, 1], built up on the GPU while the agent lingers
+    /// on its own strong trail (see `SpeciesSettings::deposit_fatigue`).
+    pub fatigue: f32,
+    // Keeps the stride at 32 bytes (16-byte aligned)
+    pub _pad: [u32; 2],
+}
+
+// `species_index` of unused buffer slots; `agents.wgsl` skips these
+pub const INACTIVE_AGENT: u32 = u32::MAX;
+
+// Capacity multiplier when runtime spawns outgrow the agent buffer
+pub const AGENT_BUFFER_GROWTH: f32 = 1.5;
+
+// COPY_SRC lets a full buffer be copied into a larger one (see `AgentBufferGrowRequest`)
+const AGENT_BUFFER_USAGES: BufferUsages =
+    BufferUsages::STORAGE.union(BufferUsages::COPY_DST).union(BufferUsages::COPY_SRC);
+
+// Agents whose sensors the sensor overlay marks (`draw_sensors`), spread
+// evenly over the buffer; matches SENSOR_MARKER_AGENTS in agents.wgsl
+pub const SENSOR_MARKER_AGENTS: u32 = 16;
+
+// Side in texels of one avoidance grid cell; matches AVOID_CELL_SIZE in
+// agents.wgsl. Agents react to crowds within about one cell.
+pub const AVOID_CELL_SIZE: u32 = 8;
+
+/// Avoidance grid size in cells covering a `size` field.
+pub fn avoid_grid_dims(size: UVec2) -> UVec2 {
+    size.max(UVec2::ONE).div_ceil(UVec2::splat(AVOID_CELL_SIZE))
+}
+
+/// Entries in the avoidance grid buffer: one count per cell per species slot.
+pub fn avoid_grid_len(size: UVec2, species_count: u32) -> u32 {
+    let dims = avoid_grid_dims(size);
+    dims.x * dims.y * species_count.max(1)
+}
+
+impl Agent {
+    const INACTIVE: Self = Self::new(Vec2::ZERO, 0.0, INACTIVE_AGENT);
+
+    /// A freshly spawned agent (age 0).
+    pub const fn new(position: Vec2, angle: f32, species_index: u32) -> Self {
+        Self { position, angle, species_index, age: 0.0, fatigue: 0.0, _pad: [0; 2] }
+    }
 }
 
 /// Write the CPU `AgentsCpu` list into the GPU `AgentGpuBuffer`.
@@ -30,13 +80,78 @@ pub struct Agent {
 #[derive(Resource)]
 pub struct AgentsCpu {
     pub list: Vec<Agent>,
+    /// First index `sync_agents_to_gpu` uploads. Appends leave it at the old
+    /// length so agents already moving on the GPU are not reset; anything
+    /// that rewrites existing entries sets it to 0.
+    pub upload_from: usize,
 }
 
 #[derive(Resource, Clone, ExtractResource)]
 pub struct AgentGpuBuffer {
     pub buffer: Buffer,
+    /// Slots in `buffer` (live agents plus inactive headroom).
+    pub capacity: u32,
+}
+
+// One-shot copy of the agents already on the GPU from an outgrown buffer into
+// the new `AgentGpuBuffer` (source buffer and byte count). Set by
+// `sync_agents_to_gpu`, consumed by the render node before any agent pass,
+// then reset at the start of the next frame.
+#[derive(Resource, Clone, Default, ExtractResource)]
+pub struct AgentBufferGrowRequest {
+    pub pending: Option<(Buffer, u64)>,
+}
+
+/// Number of simulated agents. The startup value is set in `main.rs`; changing
+/// it at runtime regenerates the CPU list and reallocates the GPU buffer.
+#[derive(Resource, Clone, Copy, Deref, DerefMut, ExtractResource)]
+pub struct AgentPopulation(pub u32);
+
+/// Initial placement/heading layout used by `generate_agents`.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SpawnPattern {
+    /// Uniform disc around the center, every agent facing the center.
+    #[default]
+    DiscInward,
+    /// Uniform over the whole field with random headings.
+    UniformRandom,
+    /// Every agent at the center with a random heading.
+    PointSource,
+    /// On a circle around the center, facing tangentially (counter-clockwise).
+    Ring,
 }
 
+/// Per-species initial heading, applied by `generate_agents` on top of the
+/// `SpawnPattern` placement (authored with `species::SpeciesHeading`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HeadingMode {
+    /// Keep the heading the spawn pattern gives.
+    #[default]
+    Pattern,
+    /// Face the center of the field.
+    Inward,
+    /// Face away from the center of the field.
+    Outward,
+    /// Uniformly random heading.
+    Random,
+}
+
+/// Seed for initial agent generation. The same seed reproduces the same
+/// layout; `Default` picks a random one. Logged whenever agents are generated.
+#[derive(Resource, Clone, Copy, Debug, Deref)]
+pub struct SimSeed(pub u64);
+
+impl Default for SimSeed {
+    fn default() -> Self {
+        Self(rand::random())
+    }
+}
+
+/// Relative population per species index (normalized at generation time).
+/// Empty means an equal split across all species.
+#[derive(Resource, Clone, Default, Deref, DerefMut)]
+pub struct AgentSpeciesMix(pub Vec<f32>);
+
 #[derive(Resource)]
 pub struct SpeciesRotationTimer(pub Timer);
 
@@ -53,52 +168,226 @@ pub fn rotate_agent_species(
     if !timer.0.just_finished() {
         return;
     }
-    globals.species_offset = (globals.species_offset + 1) % crate::NUM_SPECIES;
+    globals.species_offset = (globals.species_offset + 1) % globals.species_count.max(1);
 }
 
 pub fn sync_agents_to_gpu(
-    agents_cpu: Res<AgentsCpu>,
-    agents_gpu: Res<AgentGpuBuffer>,
+    mut agents_cpu: ResMut<AgentsCpu>,
+    mut agents_gpu: ResMut<AgentGpuBuffer>,
+    mut grow: ResMut<AgentBufferGrowRequest>,
+    render_device: Res<RenderDevice>,
     queue: Res<RenderQueue>,
 ) {
     if !agents_cpu.is_changed() {
         return;
     }
 
-    queue.write_buffer(
-        &agents_gpu.buffer,
-        0,
-        bytemuck::cast_slice(&agents_cpu.list),
+    // Outgrown: move to a larger buffer (picked up by `prepare_bind_group`
+    // once extracted), or drop what even the device limit can't hold
+    let len = agents_cpu.list.len();
+    if len > agents_gpu.capacity as usize {
+        let limit = max_agent_capacity(&render_device);
+        let capacity = grown_capacity(agents_gpu.capacity, len, limit);
+        if (capacity as usize) < len {
+            warn!("Agents: {len} exceed the device limit of {capacity}; dropping {}", len - capacity as usize);
+            agents_cpu.list.truncate(capacity as usize);
+        }
+        if capacity > agents_gpu.capacity {
+            info!("Agents: growing buffer {} -> {capacity} slots", agents_gpu.capacity);
+            let bytes = grow_copy_bytes(agents_gpu.capacity, agents_cpu.upload_from);
+            let old = std::mem::replace(&mut *agents_gpu, grow_agent_buffer(&render_device, capacity));
+            grow.pending = (bytes > 0).then_some((old.buffer, bytes));
+        }
+    }
+
+    let from = agents_cpu.upload_from.min(agents_cpu.list.len());
+    if from == 0 {
+        // Full rewrite; the padding also clears slots a longer list used before
+        let padded = pad_agents(&agents_cpu.list, agents_gpu.capacity);
+        queue.write_buffer(&agents_gpu.buffer, 0, &agents_to_gpu_bytes(&padded));
+    } else {
+        let offset = (from * std::mem::size_of::<Agent>()) as u64;
+        queue.write_buffer(
+            &agents_gpu.buffer,
+            offset,
+            bytemuck::cast_slice(&agents_cpu.list[from..]),
+        );
+    }
+    let len = agents_cpu.list.len();
+    agents_cpu.bypass_change_detection().upload_from = len;
+}
+
+/// Capacity for `needed` agents: at least `AGENT_BUFFER_GROWTH` times
+/// `current`, so repeated spawning reallocates rarely, but never above `limit`
+/// (the result is then short of `needed`).
+pub fn grown_capacity(current: u32, needed: usize, limit: u32) -> u32 {
+    let grown = (current as f32 * AGENT_BUFFER_GROWTH).ceil() as u64;
+    grown.max(needed as u64).min(limit as u64) as u32
+}
+
+/// Most agents one storage buffer binding can hold on this device.
+pub fn max_agent_capacity(render_device: &RenderDevice) -> u32 {
+    let limits = render_device.limits();
+    let bytes = (limits.max_storage_buffer_binding_size as u64).min(limits.max_buffer_size);
+    (bytes / std::mem::size_of::<Agent>() as u64).min(u32::MAX as u64) as u32
+}
+
+/// A `capacity`-slot buffer of inactive agents. The agents already on the
+/// GPU are copied in by the render node (`AgentBufferGrowRequest`), after the
+/// last frame that used the old buffer, so they keep the state the shader
+/// gave them.
+fn grow_agent_buffer(render_device: &RenderDevice, capacity: u32) -> AgentGpuBuffer {
+    let buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+        label: Some("Agent buffer"),
+        contents: &agents_to_gpu_bytes(&pad_agents(&[], capacity)),
+        usage: AGENT_BUFFER_USAGES,
+    });
+    AgentGpuBuffer { buffer, capacity }
+}
+
+/// Bytes to carry over from an outgrown `old_capacity`-slot buffer: only the
+/// agents before `upload_from`. The rest are uploaded from the CPU into the
+/// new buffer, and the copy runs after that write, so it must not cover them.
+pub fn grow_copy_bytes(old_capacity: u32, upload_from: usize) -> u64 {
+    upload_from.min(old_capacity as usize) as u64 * std::mem::size_of::<Agent>() as u64
+}
+
+// The request was extracted last frame, so the copy has been dispatched
+pub fn reset_agent_buffer_grow_request(mut grow: ResMut<AgentBufferGrowRequest>) {
+    if grow.pending.is_some() {
+        grow.pending = None;
+    }
+}
+
+/// `agents` followed by inactive slots up to `capacity` (truncated if longer).
+pub fn pad_agents(agents: &[Agent], capacity: u32) -> Vec<Agent> {
+    let mut padded: Vec<Agent> = agents.iter().take(capacity as usize).copied().collect();
+    padded.resize(capacity as usize, Agent::INACTIVE);
+    padded
+}
+
+/// Append `new` agents, dropping any that would exceed `capacity`. Returns
+/// how many were added.
+pub fn append_agents(agents: &mut AgentsCpu, new: &[Agent], capacity: u32) -> usize {
+    let room = (capacity as usize).saturating_sub(agents.list.len());
+    let n = new.len().min(room);
+    agents.list.extend_from_slice(&new[..n]);
+    n
+}
+
+/// `count` agents of `species_index` scattered uniformly over a disc of
+/// `radius` around `center`, with random headings.
+pub fn brush_agents(center: Vec2, radius: f32, count: u32, species_index: u32) -> Vec<Agent> {
+    (0..count)
+        .map(|_| {
+            let r = radius * rand::random::<f32>().sqrt();
+            let theta = rand::random::<f32>() * std::f32::consts::TAU;
+            Agent::new(
+                center + Vec2::new(theta.cos(), theta.sin()) * r,
+                rand::random::<f32>() * std::f32::consts::TAU,
+                species_index,
+            )
+        })
+        .collect()
+}
+
+/// Stored species index for a brush-spawned agent so that it currently acts
+/// as species `layer % species_count`. The shader rotates indices by
+/// `species_offset`, so the offset is undone here.
+pub fn brush_species_index(layer: u32, species_count: u32, species_offset: u32) -> u32 {
+    let n = species_count.max(1);
+    (layer % n + n - species_offset % n) % n
+}
+
+/// Rebuild the CPU agent list and GPU agent buffer when `AgentPopulation`
+/// changes. The new `AgentGpuBuffer` is extracted next frame and picked up by
+/// `prepare_bind_group`, so no explicit bind group invalidation is needed.
+#[allow(clippy::too_many_arguments)]
+pub fn rebuild_agents_on_population_change(
+    mut commands: Commands,
+    population: Res<AgentPopulation>,
+    species_mix: Res<AgentSpeciesMix>,
+    spawn_pattern: Res<SpawnPattern>,
+    seed: Res<SimSeed>,
+    sim_size: Res<crate::resources::SimulationSize>,
+    regions: Res<crate::resources::SimRegions>,
+    render_device: Res<RenderDevice>,
+    species: Query<Option<&SpeciesHeading>, With<AgentSpecies>>,
+) {
+    // Startup already allocated the initial population in `setup::setup`
+    if !population.is_changed() || population.is_added() {
+        return;
+    }
+    info!("Agents: rebuilding buffer for {} agents", population.0);
+    init_agents(
+        &mut commands,
+        &render_device,
+        sim_size.0,
+        population.0,
+        authored_species_count(&species),
+        &species_mix.0,
+        &species_heading_modes(&species),
+        *spawn_pattern,
+        seed.0,
+        &regions.0,
     );
 }
 
 /// Initialize CPU agent list and GPU agent buffer, inserting `AgentsCpu` and `AgentGpuBuffer` resources.
+#[allow(clippy::too_many_arguments)]
 pub fn init_agents(
     commands: &mut Commands,
     render_device: &RenderDevice,
     size: UVec2,
     num_agents: u32,
     species_count: u32,
+    species_weights: &[f32],
+    heading_modes: &[HeadingMode],
+    pattern: SpawnPattern,
+    seed: u64,
+    regions: &[Rect],
 ) {
     // Create agents using the pure helper so we can test the generation logic
     // independently of GPU buffer creation.
-    let agents = generate_agents(size, num_agents, species_count);
+    info!("Agents: seed = {seed}");
+    let mut agents =
+        generate_agents(size, num_agents, species_count, species_weights, heading_modes, pattern, seed);
+    place_agents_in_regions(&mut agents, size, regions);
 
     // Keep CPU copy
     commands.insert_resource(AgentsCpu {
         list: agents.clone(),
+        upload_from: 0,
     });
 
-    // GPU agent buffer
-    let buffer_contents = agents_to_gpu_bytes(&agents);
+    // GPU agent buffer, with inactive headroom for the spawn brush
+    let capacity = num_agents.saturating_add(crate::AGENT_SPAWN_HEADROOM);
+    let buffer_contents = agents_to_gpu_bytes(&pad_agents(&agents, capacity));
     let agent_buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
         label: Some("Agent buffer"),
         contents: &buffer_contents,
-        usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
+        usage: AGENT_BUFFER_USAGES,
     });
     commands.insert_resource(AgentGpuBuffer {
         buffer: agent_buffer,
+        capacity,
     });
+    commands.insert_resource(AgentBufferGrowRequest::default());
+}
+
+/// Spread agents round-robin over `regions`, mapping each agent's position in
+/// the full field proportionally into its region, so every region gets a
+/// scaled copy of the spawn pattern. No-op when `regions` is empty.
+pub fn place_agents_in_regions(agents: &mut [Agent], size: UVec2, regions: &[Rect]) {
+    if regions.is_empty() {
+        return;
+    }
+    let size = size.as_vec2().max(Vec2::ONE);
+    for (i, agent) in agents.iter_mut().enumerate() {
+        let r = regions[i % regions.len()];
+        let t = (agent.position / size).clamp(Vec2::ZERO, Vec2::ONE);
+        agent.position = r.min + t * (r.size() - Vec2::ONE).max(Vec2::ZERO);
+    }
 }
 
 /// Convert a list of `Agent` to GPU-ready bytes.
@@ -107,40 +396,270 @@ pub fn agents_to_gpu_bytes(agents: &[Agent]) -> Vec<u8> {
     bytemuck::cast_slice(agents).to_vec()
 }
 
-/// Generate a deterministic list of `Agent` positioned in a disc
-/// around the center of `size`. This is separated from `init_agents` so we
-/// can unit-test the generation logic without requiring GPU resources.
-pub fn generate_agents(size: UVec2, num_agents: u32, species_count: u32) -> Vec<Agent> {
+/// Split `num_agents` across `species_count` species proportionally to
+/// `species_weights` (missing entries count as 0, extras are ignored). Agents
+/// left over after rounding down go to the largest bucket. Empty or all-zero
+/// weights fall back to an equal split.
+pub fn species_counts(num_agents: u32, species_count: u32, species_weights: &[f32]) -> Vec<u32> {
+    let species_count = species_count.max(1) as usize;
+    let mut weights: Vec<f32> = (0..species_count)
+        .map(|i| species_weights.get(i).copied().unwrap_or(0.0).max(0.0))
+        .collect();
+    let mut total: f32 = weights.iter().sum();
+    if total <= 0.0 || !total.is_finite() {
+        weights = vec![1.0; species_count];
+        total = species_count as f32;
+    }
+    let mut counts: Vec<u32> = weights
+        .iter()
+        .map(|w| ((num_agents as f64) * (*w as f64) / (total as f64)).floor() as u32)
+        .collect();
+    let assigned: u32 = counts.iter().sum();
+    let largest = weights
+        .iter()
+        .enumerate()
+        .fold(0, |best, (i, w)| if *w > weights[best] { i } else { best });
+    counts[largest] += num_agents.saturating_sub(assigned);
+    counts
+}
+
+/// Generate a list of `Agent` laid out according to `pattern` (by default a
+/// disc around the center of `size`, facing inward). This is separated from
+/// `init_agents` so we can unit-test the generation logic without requiring
+/// GPU resources. Species are assigned in proportion to `species_weights`
+/// (see `species_counts`). `heading_modes[i]` overrides the pattern's heading
+/// for species `i` (missing entries keep it). The same `seed` always yields
+/// the same agents.
+pub fn generate_agents(
+    size: UVec2,
+    num_agents: u32,
+    species_count: u32,
+    species_weights: &[f32],
+    heading_modes: &[HeadingMode],
+    pattern: SpawnPattern,
+    seed: u64,
+) -> Vec<Agent> {
     let mut agents: Vec<Agent> = Vec::with_capacity(num_agents as usize);
-    // Use the crate's convenient RNG (renamed API)
-    let mut rng = rand::rng();
+    let species_of: Vec<u32> = species_counts(num_agents, species_count, species_weights)
+        .iter()
+        .enumerate()
+        .flat_map(|(si, &n)| std::iter::repeat_n(si as u32, n as usize))
+        .collect();
+    let mut rng = StdRng::seed_from_u64(seed);
 
     let center = Vec2::new(size.x as f32 * 0.5, size.y as f32 * 0.5);
     let radius = (size.x.min(size.y) as f32) * 0.4;
     for i in 0..num_agents {
+        let index = species_of[i as usize];
         let angle = rng.random_range(0.0..std::f32::consts::TAU);
-        let r = radius * rng.random_range(0.0_f32..1.0_f32).sqrt();
-        let index = i % species_count;
-        let pos = center + Vec2::new(angle.cos() * r, angle.sin() * r);
-        let dir_vec = (center - pos).normalize_or_zero();
-        let dir = dir_vec.y.atan2(dir_vec.x);
-        agents.push(Agent {
-            position: pos,
-            angle: dir,
-            species_index: index,
-        });
+        let (pos, dir) = match pattern {
+            SpawnPattern::DiscInward => {
+                let r = radius * rng.random_range(0.0_f32..1.0_f32).sqrt();
+                let pos = center + Vec2::new(angle.cos() * r, angle.sin() * r);
+                let dir_vec = (center - pos).normalize_or_zero();
+                (pos, dir_vec.y.atan2(dir_vec.x))
+            }
+            SpawnPattern::UniformRandom => {
+                let pos = Vec2::new(
+                    rng.random_range(0.0..(size.x.max(1) - 1).max(1) as f32),
+                    rng.random_range(0.0..(size.y.max(1) - 1).max(1) as f32),
+                );
+                (pos, angle)
+            }
+            SpawnPattern::PointSource => (center, angle),
+            SpawnPattern::Ring => {
+                let pos = center + Vec2::new(angle.cos() * radius, angle.sin() * radius);
+                (pos, angle + std::f32::consts::FRAC_PI_2)
+            }
+        };
+        let to_center = center - pos;
+        let dir = match heading_modes.get(index as usize).copied().unwrap_or_default() {
+            HeadingMode::Pattern => dir,
+            // At the center itself any heading is inward (or outward)
+            HeadingMode::Inward if to_center != Vec2::ZERO => to_center.y.atan2(to_center.x),
+            HeadingMode::Outward if to_center != Vec2::ZERO => (-to_center.y).atan2(-to_center.x),
+            HeadingMode::Inward | HeadingMode::Outward | HeadingMode::Random => {
+                rng.random_range(0.0..std::f32::consts::TAU)
+            }
+        };
+        agents.push(Agent::new(pos, dir, index));
     }
     agents
 }
 
+/// Agents placed proportionally to a painted `density` field (row-major,
+/// `size.x * size.y` texels): each agent picks a texel with probability
+/// proportional to its value, then a random point inside it, with a random
+/// heading. Negative and non-finite texels count as 0. Species are split as
+/// in `generate_agents`. `None` when the field is empty or holds nothing
+/// positive, so callers can fall back to a regular spawn pattern.
+pub fn agents_from_density(
+    density: &[f32],
+    size: UVec2,
+    num_agents: u32,
+    species_count: u32,
+    species_weights: &[f32],
+    seed: u64,
+) -> Option<Vec<Agent>> {
+    let width = size.x as usize;
+    if width == 0 || density.len() < width * size.y as usize {
+        return None;
+    }
+    // Running totals, so a uniform draw in [0, total) maps to a texel by bisection
+    let cumulative: Vec<f64> = density
+        .iter()
+        .scan(0.0f64, |total, &v| {
+            if v.is_finite() && v > 0.0 {
+                *total += v as f64;
+            }
+            Some(*total)
+        })
+        .collect();
+    let total = *cumulative.last()?;
+    if total <= 0.0 {
+        return None;
+    }
+    let species_of = species_counts(num_agents, species_count, species_weights)
+        .into_iter()
+        .enumerate()
+        .flat_map(|(si, n)| std::iter::repeat_n(si as u32, n as usize));
+    let mut rng = StdRng::seed_from_u64(seed);
+    let max = (size.as_vec2() - Vec2::ONE).max(Vec2::ZERO);
+    Some(
+        species_of
+            .map(|species| {
+                let draw = rng.random_range(0.0..total);
+                let texel = cumulative.partition_point(|&c| c <= draw).min(cumulative.len() - 1);
+                let corner = Vec2::new((texel % width) as f32, (texel / width) as f32);
+                let jitter = Vec2::new(rng.random_range(0.0..1.0), rng.random_range(0.0..1.0));
+                let angle = rng.random_range(0.0..std::f32::consts::TAU);
+                Agent::new((corner + jitter).min(max), angle, species)
+            })
+            .collect(),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    // CPU copy of the grid entry `agents.wgsl` counts an agent of
+    // `species_slot` at `position` in: cells are species-interleaved, and
+    // positions off the field clamp to the edge cells
+    fn avoid_grid_index(position: Vec2, size: UVec2, species_slot: u32, species_count: u32) -> u32 {
+        let dims = avoid_grid_dims(size);
+        let max_cell = dims.as_ivec2() - 1;
+        let cell = (position / AVOID_CELL_SIZE as f32).as_ivec2().clamp(IVec2::ZERO, max_cell).as_uvec2();
+        (cell.y * dims.x + cell.x) * species_count.max(1) + species_slot
+    }
+
+    #[test]
+    fn avoid_grid_covers_the_field_per_species() {
+        let size = UVec2::new(20, 9);
+        // 3 x 2 cells (partial cells at the right and bottom edges)
+        assert_eq!(avoid_grid_dims(size), UVec2::new(3, 2));
+        assert_eq!(avoid_grid_len(size, 4), 24);
+        assert_eq!(avoid_grid_index(Vec2::new(0.5, 0.5), size, 0, 4), 0);
+        assert_eq!(avoid_grid_index(Vec2::new(0.5, 0.5), size, 3, 4), 3);
+        assert_eq!(avoid_grid_index(Vec2::new(17.0, 8.5), size, 1, 4), (3 + 2) * 4 + 1);
+        // Off-field positions clamp; every index stays inside the buffer
+        assert_eq!(avoid_grid_index(Vec2::new(-5.0, 100.0), size, 2, 4), 3 * 4 + 2);
+        assert!(avoid_grid_index(Vec2::new(1e6, 1e6), size, 3, 4) < avoid_grid_len(size, 4));
+        assert_eq!(avoid_grid_len(crate::SIZE, 0), 240 * 135);
+    }
+
+    #[test]
+    fn agents_from_density_follow_the_painted_texels() {
+        let size = UVec2::new(4, 3);
+        let mut density = vec![0.0; 12];
+        // Three times as much paint at (3, 2) as at (0, 1); the rest is bare or invalid
+        density[4] = 1.0;
+        density[11] = 3.0;
+        density[6] = -2.0;
+        density[7] = f32::NAN;
+        let agents = agents_from_density(&density, size, 4000, 2, &[], 9).expect("painted field");
+        assert_eq!(agents.len(), 4000);
+        let at_corner = agents.iter().filter(|a| a.position.floor() == Vec2::new(3.0, 2.0)).count();
+        let at_left = agents.iter().filter(|a| a.position.floor() == Vec2::new(0.0, 1.0)).count();
+        assert_eq!(at_corner + at_left, 4000);
+        assert!((2800..3200).contains(&at_corner), "{at_corner} agents on the 3x texel");
+        assert_eq!(agents.iter().filter(|a| a.species_index == 1).count(), 2000);
+        // nothing painted (or a short buffer) means no density spawn
+        assert!(agents_from_density(&[0.0; 12], size, 10, 1, &[], 9).is_none());
+        assert!(agents_from_density(&[1.0; 5], size, 10, 1, &[], 9).is_none());
+    }
+
+    #[test]
+    fn append_agents_caps_at_capacity() {
+        let mut cpu = AgentsCpu {
+            list: generate_agents(UVec2::new(64, 64), 8, 1, &[], &[], SpawnPattern::UniformRandom, 1),
+            upload_from: 8,
+        };
+        let new = brush_agents(Vec2::new(32.0, 32.0), 5.0, 6, 0);
+        assert!(new.iter().all(|a| a.position.distance(Vec2::new(32.0, 32.0)) <= 5.0 + 1e-3));
+        assert_eq!(append_agents(&mut cpu, &new, 12), 4);
+        assert_eq!(cpu.list.len(), 12);
+        assert_eq!(append_agents(&mut cpu, &new, 12), 0);
+
+        let padded = pad_agents(&cpu.list[..3], 5);
+        assert_eq!(padded.len(), 5);
+        assert_eq!(padded[2].species_index, cpu.list[2].species_index);
+        assert!(padded[3..].iter().all(|a| a.species_index == INACTIVE_AGENT));
+    }
+
+    #[test]
+    fn grown_capacity_grows_by_factor_and_respects_limit() {
+        // A few extra agents still grow by the whole factor
+        assert_eq!(grown_capacity(100, 101, 1000), 150);
+        // A big batch gets exactly what it needs
+        assert_eq!(grown_capacity(100, 400, 1000), 400);
+        // The device limit wins, leaving the caller to truncate
+        assert_eq!(grown_capacity(800, 900, 1000), 1000);
+        assert_eq!(grown_capacity(1000, 1200, 1000), 1000);
+    }
+
+    #[test]
+    fn grow_copy_bytes_skips_agents_uploaded_from_the_cpu() {
+        let agent = std::mem::size_of::<Agent>() as u64;
+        // Appended agents are written into the new buffer, not copied
+        assert_eq!(grow_copy_bytes(100, 80), 80 * agent);
+        // A full rewrite leaves nothing to carry over
+        assert_eq!(grow_copy_bytes(100, 0), 0);
+        assert_eq!(grow_copy_bytes(100, 150), 100 * agent);
+    }
+
+    #[test]
+    fn brush_species_index_undoes_rotation() {
+        for offset in 0..6 {
+            for layer in 0..5 {
+                let stored = brush_species_index(layer, 3, offset);
+                assert!(stored < 3);
+                assert_eq!((stored + offset) % 3, layer % 3);
+            }
+        }
+    }
+
+    #[test]
+    fn place_agents_in_regions_round_robin() {
+        let size = UVec2::new(200, 100);
+        let mut agents = generate_agents(size, 400, 3, &[], &[], SpawnPattern::UniformRandom, 3);
+        let regions = [Rect::new(0.0, 0.0, 100.0, 50.0), Rect::new(100.0, 50.0, 200.0, 100.0)];
+        place_agents_in_regions(&mut agents, size, &regions);
+        for (i, a) in agents.iter().enumerate() {
+            let r = regions[i % 2];
+            assert!(r.contains(a.position), "agent {i} at {} outside {r:?}", a.position);
+        }
+        // no regions leaves agents untouched
+        let before = generate_agents(size, 10, 1, &[], &[], SpawnPattern::UniformRandom, 3);
+        let mut after = before.clone();
+        place_agents_in_regions(&mut after, size, &[]);
+        assert!(before.iter().zip(&after).all(|(a, b)| a.position == b.position));
+    }
+
     #[test]
     fn generate_agents_basic() {
         let size = UVec2::new(200, 100);
-        let agents = generate_agents(size, 1000, 3);
+        let agents = generate_agents(size, 1000, 3, &[], &[], SpawnPattern::DiscInward, 7);
         assert_eq!(agents.len(), 1000);
 
         // positions should be within bounds and species index in range
@@ -156,34 +675,126 @@ mod tests {
     #[test]
     fn generate_agents_zero() {
         let size = UVec2::new(100, 100);
-        let agents = generate_agents(size, 0, 3);
+        let agents = generate_agents(size, 0, 3, &[], &[], SpawnPattern::DiscInward, 7);
         assert!(agents.is_empty());
     }
 
     #[test]
     fn generate_agents_species_one() {
         let size = UVec2::new(100, 100);
-        let agents = generate_agents(size, 10, 1);
+        let agents = generate_agents(size, 10, 1, &[], &[], SpawnPattern::DiscInward, 7);
         assert_eq!(agents.len(), 10);
         for a in agents.iter() {
             assert_eq!(a.species_index, 0);
         }
     }
 
+    #[test]
+    fn species_counts_proportional_with_remainder_to_largest() {
+        // 70/15/15 of 101: floors are 70, 15, 15 -> remainder 1 goes to species 0
+        let counts = species_counts(101, 3, &[0.7, 0.15, 0.15]);
+        assert_eq!(counts, vec![71, 15, 15]);
+        // unnormalized weights behave the same
+        assert_eq!(species_counts(100, 3, &[14.0, 3.0, 3.0]), vec![70, 15, 15]);
+        // largest bucket need not be the first
+        assert_eq!(species_counts(10, 3, &[1.0, 1.0, 2.0]), vec![2, 2, 6]);
+    }
+
+    #[test]
+    fn species_counts_fallbacks() {
+        // empty or zero weights split evenly
+        assert_eq!(species_counts(9, 3, &[]), vec![3, 3, 3]);
+        assert_eq!(species_counts(10, 3, &[0.0, 0.0, 0.0]), vec![4, 3, 3]);
+        // missing entries are zero, extras ignored
+        assert_eq!(species_counts(10, 2, &[1.0]), vec![10, 0]);
+        assert_eq!(species_counts(10, 2, &[1.0, 1.0, 5.0]), vec![5, 5]);
+    }
+
+    #[test]
+    fn generate_agents_respects_weights() {
+        let size = UVec2::new(100, 100);
+        let agents = generate_agents(size, 100, 3, &[0.7, 0.15, 0.15], &[], SpawnPattern::DiscInward, 7);
+        let mut per_species = [0u32; 3];
+        for a in agents.iter() {
+            per_species[a.species_index as usize] += 1;
+        }
+        assert_eq!(per_species, [70, 15, 15]);
+    }
+
+    #[test]
+    fn generate_agents_spawn_patterns() {
+        let size = UVec2::new(200, 100);
+        let center = Vec2::new(100.0, 50.0);
+        let radius = 100.0 * 0.4;
+
+        for a in generate_agents(size, 200, 2, &[], &[], SpawnPattern::UniformRandom, 7).iter() {
+            assert!(a.position.x >= 0.0 && a.position.x < size.x as f32);
+            assert!(a.position.y >= 0.0 && a.position.y < size.y as f32);
+        }
+
+        for a in generate_agents(size, 50, 2, &[], &[], SpawnPattern::PointSource, 7).iter() {
+            assert_eq!(a.position, center);
+            assert!(a.angle.is_finite());
+        }
+
+        for a in generate_agents(size, 50, 2, &[], &[], SpawnPattern::Ring, 7).iter() {
+            let offset = a.position - center;
+            assert!((offset.length() - radius).abs() < 1e-3);
+            // heading is perpendicular to the radius
+            let heading = Vec2::new(a.angle.cos(), a.angle.sin());
+            assert!(heading.dot(offset.normalize()).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn generate_agents_heading_modes_per_species() {
+        let size = UVec2::new(200, 100);
+        let center = size.as_vec2() * 0.5;
+        let modes = [HeadingMode::Outward, HeadingMode::Pattern, HeadingMode::Random];
+        let agents = generate_agents(size, 300, 3, &[], &modes, SpawnPattern::Ring, 7);
+        let mut random_radial = 0;
+        for a in agents.iter() {
+            let heading = Vec2::new(a.angle.cos(), a.angle.sin());
+            let radial = heading.dot((a.position - center).normalize());
+            match a.species_index {
+                0 => assert!((radial - 1.0).abs() < 1e-3),
+                // Ring keeps its tangential heading
+                1 => assert!(radial.abs() < 1e-3),
+                _ => random_radial += (radial.abs() > 0.5) as u32,
+            }
+        }
+        // Random headings are neither all tangential nor all radial
+        assert!(random_radial > 10 && random_radial < 90);
+        // Inward at the exact center falls back to a random heading
+        let point = generate_agents(size, 20, 1, &[], &[HeadingMode::Inward], SpawnPattern::PointSource, 7);
+        assert!(point.iter().any(|a| (a.angle - point[0].angle).abs() > 1e-3));
+        let inward = generate_agents(size, 50, 1, &[], &[HeadingMode::Inward], SpawnPattern::UniformRandom, 7);
+        for a in inward.iter().filter(|a| a.position != center) {
+            let heading = Vec2::new(a.angle.cos(), a.angle.sin());
+            assert!((heading.dot((center - a.position).normalize()) - 1.0).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn generate_agents_seed_reproducible() {
+        let size = UVec2::new(200, 100);
+        let a = generate_agents(size, 100, 3, &[], &[], SpawnPattern::UniformRandom, 42);
+        let b = generate_agents(size, 100, 3, &[], &[], SpawnPattern::UniformRandom, 42);
+        let c = generate_agents(size, 100, 3, &[], &[], SpawnPattern::UniformRandom, 43);
+        assert_eq!(agents_to_gpu_bytes(&a), agents_to_gpu_bytes(&b));
+        assert_ne!(agents_to_gpu_bytes(&a), agents_to_gpu_bytes(&c));
+    }
+
     #[test]
     fn agents_to_gpu_bytes_roundtrip() {
         let agents = vec![
-            Agent {
-                position: Vec2::new(10.0, 20.0),
-                angle: std::f32::consts::FRAC_PI_2,
-                species_index: 0,
-            },
-            Agent {
-                position: Vec2::new(30.0, 40.0),
-                angle: std::f32::consts::PI,
-                species_index: 1,
-            },
+            Agent::new(Vec2::new(10.0, 20.0), std::f32::consts::FRAC_PI_2, 0),
+            Agent::new(Vec2::new(30.0, 40.0), std::f32::consts::PI, 1),
         ];
+        // GPU stride stays a multiple of 16 bytes
+        assert_eq!(std::mem::size_of::<Agent>(), 32);
+        assert_eq!(agents[0].age, 0.0);
+        assert_eq!(agents[0].fatigue, 0.0);
 
         let bytes = agents_to_gpu_bytes(&agents);
         // bytes should be non-empty and equal in size to the raw cast
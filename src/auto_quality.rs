@@ -0,0 +1,167 @@
+// Frame-time-driven quality scaling for weaker GPUs.
+//
+// While `AutoQuality.enabled` (toggled with `Q`), a controller checks the
+// overlay's `FpsCounter` about once a second and moves `QualityLevel`, the
+// cap on diffusion passes per frame the render node honours. Below
+// `target_fps` the cap halves; only above `target_fps * (1 + headroom)` does
+// it grow back, one pass at a time, so the band between the two thresholds
+// (and the slow climb) keeps it from oscillating. The cap stays within
+// `min_diffuse_iterations` and the requested `AgentSimRunConfig`
+// iterations, so it never runs more passes than asked for.
+
+use bevy::prelude::*;
+use bevy::render::extract_resource::ExtractResource;
+
+use crate::resources::AgentSimRunConfig;
+use crate::setup::{FpsCounter, OverlayNotice, update_layer_params_buffer};
+
+/// Controller settings; insert a different value in `main.rs` to tune (or
+/// start enabled).
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct AutoQuality {
+    pub enabled: bool,
+    /// Frame rate to hold; lower readings drop quality.
+    pub target_fps: f32,
+    /// Fraction above `target_fps` required before quality goes back up.
+    pub headroom: f32,
+    /// Fewest diffusion passes the controller drops to.
+    pub min_diffuse_iterations: u32,
+    /// Seconds between adjustments.
+    pub interval_secs: f32,
+}
+
+impl Default for AutoQuality {
+    fn default() -> Self {
+        Self { enabled: false, target_fps: 50.0, headroom: 0.1, min_diffuse_iterations: 1, interval_secs: 1.0 }
+    }
+}
+
+/// Diffusion passes per frame the render node may run at most (see
+/// `AgentSimRunConfig::diffuse_steps`); `u32::MAX` = uncapped.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq, ExtractResource)]
+pub struct QualityLevel {
+    pub max_diffuse_iterations: u32,
+}
+
+impl Default for QualityLevel {
+    fn default() -> Self {
+        Self { max_diffuse_iterations: u32::MAX }
+    }
+}
+
+impl QualityLevel {
+    /// Passes to run when `requested` are asked for.
+    pub fn diffuse_steps(&self, requested: u32) -> u32 {
+        requested.min(self.max_diffuse_iterations).max(1)
+    }
+}
+
+#[derive(Resource)]
+struct AutoQualityTimer(Timer);
+
+pub struct AutoQualityPlugin;
+impl Plugin for AutoQualityPlugin {
+    fn build(&self, app: &mut App) {
+        // `init_resource` keeps settings `main.rs` inserted
+        app.init_resource::<AutoQuality>()
+            .init_resource::<QualityLevel>()
+            .add_systems(Startup, init_auto_quality_timer)
+            // Same frame as the decay split, which divides by the capped pass count
+            .add_systems(
+                Update,
+                (handle_auto_quality_hotkey, adjust_quality).chain().before(update_layer_params_buffer),
+            );
+    }
+}
+
+fn init_auto_quality_timer(mut commands: Commands, settings: Res<AutoQuality>) {
+    commands.insert_resource(AutoQualityTimer(Timer::from_seconds(
+        settings.interval_secs.max(0.1),
+        TimerMode::Repeating,
+    )));
+}
+
+fn handle_auto_quality_hotkey(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<AutoQuality>,
+    mut level: ResMut<QualityLevel>,
+    mut notice: ResMut<OverlayNotice>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyQ) {
+        return;
+    }
+    settings.enabled = !settings.enabled;
+    // Off restores the requested quality; on starts from it
+    *level = QualityLevel::default();
+    let state = if settings.enabled { "on" } else { "off" };
+    info!("Auto quality: {state} (target {:.0} fps)", settings.target_fps);
+    notice.flash(format!("Auto quality: {state}"));
+}
+
+fn adjust_quality(
+    time: Res<Time>,
+    settings: Res<AutoQuality>,
+    fps: Res<FpsCounter>,
+    run_config: Res<AgentSimRunConfig>,
+    mut timer: ResMut<AutoQualityTimer>,
+    mut level: ResMut<QualityLevel>,
+    mut notice: ResMut<OverlayNotice>,
+) {
+    if !settings.enabled || !timer.0.tick(time.delta()).just_finished() || fps.fps <= 0.0 {
+        return;
+    }
+    let requested = run_config.diffuse_steps();
+    let current = level.diffuse_steps(requested);
+    let next = next_diffuse_cap(current, fps.fps, requested, &settings);
+    if next != current {
+        level.max_diffuse_iterations = next;
+        info!("Auto quality: {:.0} fps, diffusion x{current} -> x{next}", fps.fps);
+        notice.flash(format!("Auto quality: diffusion x{next}"));
+    }
+}
+
+/// Controller step: halve `current` below the target, add one above the
+/// headroom band, else hold. The result stays within
+/// `min_diffuse_iterations..=requested`.
+pub fn next_diffuse_cap(current: u32, fps: f32, requested: u32, settings: &AutoQuality) -> u32 {
+    let hi = requested.max(1);
+    let lo = settings.min_diffuse_iterations.clamp(1, hi);
+    let current = current.clamp(lo, hi);
+    if fps < settings.target_fps {
+        (current / 2).max(lo)
+    } else if fps > settings.target_fps * (1.0 + settings.headroom) {
+        (current + 1).min(hi)
+    } else {
+        current
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cap_halves_when_slow_and_climbs_back_with_hysteresis() {
+        let s = AutoQuality { min_diffuse_iterations: 2, ..Default::default() };
+        // Slow: halve, never below the minimum
+        assert_eq!(next_diffuse_cap(8, 30.0, 8, &s), 4);
+        assert_eq!(next_diffuse_cap(3, 30.0, 8, &s), 2);
+        // Inside the band (50..55 fps): hold
+        assert_eq!(next_diffuse_cap(4, 52.0, 8, &s), 4);
+        // Headroom: one pass at a time, never above the requested count
+        assert_eq!(next_diffuse_cap(4, 60.0, 8, &s), 5);
+        assert_eq!(next_diffuse_cap(8, 60.0, 8, &s), 8);
+        // The user lowered the request below the cap or the minimum
+        assert_eq!(next_diffuse_cap(8, 52.0, 3, &s), 3);
+        assert_eq!(next_diffuse_cap(1, 30.0, 1, &s), 1);
+    }
+
+    #[test]
+    fn quality_level_caps_requested_steps() {
+        assert_eq!(QualityLevel::default().diffuse_steps(6), 6);
+        let capped = QualityLevel { max_diffuse_iterations: 2 };
+        assert_eq!(capped.diffuse_steps(6), 2);
+        assert_eq!(capped.diffuse_steps(1), 1);
+        assert_eq!(QualityLevel { max_diffuse_iterations: 0 }.diffuse_steps(4), 1);
+    }
+}
@@ -0,0 +1,106 @@
+// Headless benchmark mode.
+//
+// `--bench <frames>` (or `--bench=<frames>`) runs the app without a window
+// (winit disabled, schedule runner looping as fast as possible) so only the
+// compute graph in `render.rs` does work. After a short warmup that covers
+// pipeline compilation, `BenchPlugin` times the requested number of frames,
+// prints the average frame time, and exits. The simulation steps a fixed
+// 1/60 s per frame (`SimTimestep::Fixed`), so every run does the same work.
+
+use bevy::prelude::*;
+use std::time::{Duration, Instant};
+
+// Frames skipped before timing starts (shader compile, first uploads)
+const WARMUP_FRAMES: u32 = 120;
+
+/// Parse `--bench <frames>` / `--bench=<frames>` from the process arguments.
+/// Returns `None` when the flag is absent or the frame count is invalid.
+pub fn parse_bench_frames<I: IntoIterator<Item = String>>(args: I) -> Option<u32> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let value = if arg == "--bench" {
+            args.next()
+        } else if let Some(v) = arg.strip_prefix("--bench=") {
+            Some(v.to_string())
+        } else {
+            continue;
+        };
+        return value.and_then(|v| v.parse().ok()).filter(|&n: &u32| n > 0);
+    }
+    None
+}
+
+pub struct BenchPlugin {
+    pub frames: u32,
+}
+
+impl Plugin for BenchPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(BenchState {
+            warmup_left: WARMUP_FRAMES,
+            frames: self.frames,
+            measured: 0,
+            start: None,
+        })
+        .add_systems(Last, tick_bench);
+    }
+}
+
+#[derive(Resource)]
+struct BenchState {
+    warmup_left: u32,
+    frames: u32,
+    measured: u32,
+    start: Option<Instant>,
+}
+
+fn tick_bench(mut state: ResMut<BenchState>, mut exit: MessageWriter<AppExit>) {
+    if state.warmup_left > 0 {
+        state.warmup_left -= 1;
+        if state.warmup_left == 0 {
+            info!("Bench: warmup done, timing {} frames", state.frames);
+            state.start = Some(Instant::now());
+        }
+        return;
+    }
+    state.measured += 1;
+    if state.measured < state.frames {
+        return;
+    }
+    let elapsed = state.start.map(|s| s.elapsed()).unwrap_or_default();
+    println!("{}", bench_report(state.measured, elapsed));
+    exit.write(AppExit::Success);
+}
+
+/// Summary line printed when the run finishes.
+pub fn bench_report(frames: u32, elapsed: Duration) -> String {
+    let avg_ms = elapsed.as_secs_f64() * 1000.0 / frames.max(1) as f64;
+    let fps = if avg_ms > 0.0 { 1000.0 / avg_ms } else { 0.0 };
+    format!("Bench: {frames} frames in {:.3} s, avg {avg_ms:.3} ms/frame ({fps:.1} fps)", elapsed.as_secs_f64())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_bench_frames_forms() {
+        assert_eq!(parse_bench_frames(args(&["slime", "--bench", "500"])), Some(500));
+        assert_eq!(parse_bench_frames(args(&["slime", "--bench=42"])), Some(42));
+        assert_eq!(parse_bench_frames(args(&["slime"])), None);
+        assert_eq!(parse_bench_frames(args(&["slime", "--bench"])), None);
+        assert_eq!(parse_bench_frames(args(&["slime", "--bench", "x"])), None);
+        assert_eq!(parse_bench_frames(args(&["slime", "--bench=0"])), None);
+    }
+
+    #[test]
+    fn bench_report_averages() {
+        let line = bench_report(100, Duration::from_millis(500));
+        assert!(line.contains("avg 5.000 ms/frame"));
+        assert!(line.contains("200.0 fps"));
+    }
+}
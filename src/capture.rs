@@ -0,0 +1,317 @@
+// Capture of the live display texture to PNG.
+//
+// Pressing `P` spawns a one-shot GPU readback of whichever RGBA display
+// texture the sprite currently shows (see `setup::switch_textures`). With
+// `SrgbDisplay` on that is the 8-bit sRGB target, whose rows go straight into
+// the PNG; otherwise the `Rgba32Float` pixels are clamped and encoded to 8-bit
+// sRGB first. The PNG is written on the IO task pool so neither the main world
+// nor the render thread blocks on disk access.
+//
+// `V` toggles `Recording`: while active, every `every_n`-th frame goes
+// through the same path into a zero-padded sequence (`frame_00000.png`, ...)
+// under `output_dir`, ready for assembling a GIF or video externally.
+
+use bevy::asset::RenderAssetUsages;
+use bevy::prelude::*;
+use bevy::render::gpu_readback::{Readback, ReadbackComplete};
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::tasks::IoTaskPool;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::resources::{PheromoneImages, SimulationSize};
+
+// wgpu requires buffer copy rows to be aligned to this many bytes
+const COPY_BYTES_PER_ROW_ALIGNMENT: usize = 256;
+// Rgba32Float
+const BYTES_PER_PIXEL: usize = 16;
+// Rgba8Unorm (the `SrgbDisplay` target)
+const SRGB_BYTES_PER_PIXEL: usize = 4;
+
+pub struct CapturePlugin;
+impl Plugin for CapturePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Recording>()
+            .add_systems(Update, (handle_screenshot_hotkey, handle_record_hotkey, record_frames).chain());
+    }
+}
+
+// Default directory for recorded sequences
+const RECORDING_DIR: &str = "recording";
+
+/// Image-sequence recording state. `counter` counts frames since recording
+/// started; every `every_n`-th one is saved.
+#[derive(Resource, Debug, Clone)]
+pub struct Recording {
+    pub active: bool,
+    pub every_n: u32,
+    pub counter: u32,
+    pub output_dir: PathBuf,
+}
+
+impl Default for Recording {
+    fn default() -> Self {
+        Self {
+            active: false,
+            every_n: 2,
+            counter: 0,
+            output_dir: PathBuf::from(RECORDING_DIR),
+        }
+    }
+}
+
+impl Recording {
+    /// Sequence index of the frame at `counter`, if that frame is recorded.
+    pub fn frame_index(&self, counter: u32) -> Option<u32> {
+        let every_n = self.every_n.max(1);
+        counter.is_multiple_of(every_n).then_some(counter / every_n)
+    }
+}
+
+/// Path of the `index`-th frame in a recorded sequence.
+pub fn sequence_frame_path(dir: &std::path::Path, index: u32) -> PathBuf {
+    dir.join(format!("frame_{index:05}.png"))
+}
+
+fn handle_screenshot_hotkey(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    sprite: Single<&Sprite>,
+    images: Res<PheromoneImages>,
+    sim_size: Res<SimulationSize>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyP) {
+        return;
+    }
+    let encoded = sprite.image == images.display_srgb;
+    spawn_png_capture(&mut commands, sprite.image.clone(), encoded, sim_size.0, timestamped_png_path());
+}
+
+fn handle_record_hotkey(keyboard_input: Res<ButtonInput<KeyCode>>, mut recording: ResMut<Recording>) {
+    if !keyboard_input.just_pressed(KeyCode::KeyV) {
+        return;
+    }
+    if recording.active {
+        recording.active = false;
+        info!(
+            "Recording stopped after {} frames",
+            recording.counter.div_ceil(recording.every_n.max(1))
+        );
+        return;
+    }
+    if let Err(err) = std::fs::create_dir_all(&recording.output_dir) {
+        error!("Recording: cannot create {}: {err}", recording.output_dir.display());
+        return;
+    }
+    recording.active = true;
+    recording.counter = 0;
+    info!(
+        "Recording every {} frame(s) to {}",
+        recording.every_n.max(1),
+        recording.output_dir.display()
+    );
+}
+
+fn record_frames(
+    mut commands: Commands,
+    mut recording: ResMut<Recording>,
+    sprite: Single<&Sprite>,
+    images: Res<PheromoneImages>,
+    sim_size: Res<SimulationSize>,
+) {
+    if !recording.active {
+        return;
+    }
+    if let Some(index) = recording.frame_index(recording.counter) {
+        let path = sequence_frame_path(&recording.output_dir, index);
+        let encoded = sprite.image == images.display_srgb;
+        spawn_png_capture(&mut commands, sprite.image.clone(), encoded, sim_size.0, path);
+    }
+    recording.counter += 1;
+}
+
+/// Read back `image` once and write it to `path` as an 8-bit sRGB PNG.
+/// `encoded` marks the 8-bit sRGB display target, which needs no conversion.
+fn spawn_png_capture(
+    commands: &mut Commands,
+    image: Handle<Image>,
+    encoded: bool,
+    size: UVec2,
+    path: PathBuf,
+) {
+    commands
+        .spawn(Readback::texture(image))
+        .observe(move |event: On<ReadbackComplete>, mut commands: Commands| {
+            // Readback repeats every frame while the entity lives; we only want one
+            commands.entity(event.entity).despawn();
+            let width = size.x as usize;
+            let rgba8 = if encoded {
+                unpad_rows(&event.data, width, SRGB_BYTES_PER_PIXEL)
+            } else {
+                rgba32f_to_rgba8_srgb(&unpad_rows(&event.data, width, BYTES_PER_PIXEL))
+            };
+            let path = path.clone();
+            IoTaskPool::get()
+                .spawn(async move { save_png(&path, size, rgba8) })
+                .detach();
+        });
+}
+
+fn timestamped_png_path() -> PathBuf {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    PathBuf::from(format!("slime_{millis}.png"))
+}
+
+fn save_png(path: &std::path::Path, size: UVec2, rgba8: Vec<u8>) {
+    let image = Image::new(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        rgba8,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::MAIN_WORLD,
+    );
+    match image.try_into_dynamic() {
+        Ok(dynamic) => match dynamic.to_rgba8().save(path) {
+            Ok(()) => info!("Saved capture to {}", path.display()),
+            Err(err) => error!("Failed to save capture {}: {err}", path.display()),
+        },
+        Err(err) => error!("Failed to convert capture: {err:?}"),
+    }
+}
+
+/// Strip the per-row padding wgpu adds to texture->buffer copies. Returns the
+/// input unchanged when rows are already tightly packed.
+pub fn unpad_rows(data: &[u8], width: usize, bytes_per_pixel: usize) -> Vec<u8> {
+    let row_bytes = width * bytes_per_pixel;
+    let padded_row_bytes = row_bytes.div_ceil(COPY_BYTES_PER_ROW_ALIGNMENT) * COPY_BYTES_PER_ROW_ALIGNMENT;
+    if padded_row_bytes == row_bytes {
+        return data.to_vec();
+    }
+    data.chunks(padded_row_bytes)
+        .flat_map(|row| &row[..row_bytes.min(row.len())])
+        .copied()
+        .collect()
+}
+
+/// Convert tightly packed `Rgba32Float` bytes (linear) to 8-bit sRGB RGBA.
+/// Values are clamped to [0, 1]; the composite pass already tone maps, so this
+/// only guards against out-of-range texels.
+pub fn rgba32f_to_rgba8_srgb(data: &[u8]) -> Vec<u8> {
+    data.chunks_exact(BYTES_PER_PIXEL)
+        .flat_map(|px| {
+            let channel = |i: usize| f32::from_le_bytes([px[i], px[i + 1], px[i + 2], px[i + 3]]);
+            let (r, g, b, a) = (channel(0), channel(4), channel(8), channel(12));
+            [
+                linear_to_srgb_u8(r),
+                linear_to_srgb_u8(g),
+                linear_to_srgb_u8(b),
+                (a.clamp(0.0, 1.0) * 255.0).round() as u8,
+            ]
+        })
+        .collect()
+}
+
+fn linear_to_srgb_u8(v: f32) -> u8 {
+    // NaN clamps to 0 so a corrupted texel cannot poison the encode; +inf is
+    // white. Matches `display_channel` in pheromones.wgsl
+    let v = if v.is_nan() { 0.0 } else { v.clamp(0.0, 1.0) };
+    let srgb = if v <= 0.003_130_8 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    };
+    (srgb * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixel_bytes(px: [f32; 4]) -> Vec<u8> {
+        px.iter().flat_map(|c| c.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn rgba32f_converts_and_clamps() {
+        let mut data = pixel_bytes([0.0, 1.0, 2.0, 1.0]);
+        data.extend(pixel_bytes([-1.0, f32::NAN, 0.5, 0.0]));
+        let out = rgba32f_to_rgba8_srgb(&data);
+        assert_eq!(out.len(), 8);
+        assert_eq!(&out[0..4], &[0, 255, 255, 255]);
+        assert_eq!(out[4], 0);
+        assert_eq!(out[5], 0);
+        // linear 0.5 is ~188 in sRGB
+        assert_eq!(out[6], 188);
+        assert_eq!(out[7], 0);
+    }
+
+    #[test]
+    fn non_finite_channels_match_the_display_encode() {
+        assert_eq!(linear_to_srgb_u8(f32::NAN), 0);
+        assert_eq!(linear_to_srgb_u8(f32::INFINITY), 255);
+        assert_eq!(linear_to_srgb_u8(f32::NEG_INFINITY), 0);
+    }
+
+    #[test]
+    fn unpad_rows_strips_alignment_padding() {
+        // 3 px * 16 B = 48 B per row, padded to 256
+        let width = 3;
+        let mut data = Vec::new();
+        for row in 0..2u8 {
+            data.extend(std::iter::repeat_n(row + 1, width * BYTES_PER_PIXEL));
+            data.extend(std::iter::repeat_n(0xAA, COPY_BYTES_PER_ROW_ALIGNMENT - width * BYTES_PER_PIXEL));
+        }
+        let out = unpad_rows(&data, width, BYTES_PER_PIXEL);
+        assert_eq!(out.len(), 2 * width * BYTES_PER_PIXEL);
+        assert!(out[..48].iter().all(|&b| b == 1));
+        assert!(out[48..].iter().all(|&b| b == 2));
+    }
+
+    #[test]
+    fn recording_picks_every_nth_frame() {
+        let rec = Recording { every_n: 3, ..Default::default() };
+        let picked: Vec<_> = (0..8).filter_map(|c| rec.frame_index(c)).collect();
+        assert_eq!(picked, vec![0, 1, 2]);
+        assert_eq!(rec.frame_index(4), None);
+        // every_n of 0 behaves like 1
+        let all = Recording { every_n: 0, ..Default::default() };
+        assert_eq!(all.frame_index(5), Some(5));
+    }
+
+    #[test]
+    fn sequence_frame_path_is_zero_padded() {
+        let path = sequence_frame_path(std::path::Path::new("out"), 42);
+        assert_eq!(path, PathBuf::from("out").join("frame_00042.png"));
+    }
+
+    #[test]
+    fn unpad_rows_noop_when_aligned() {
+        // 16 px * 16 B = 256 B, already aligned
+        let data = vec![7u8; 16 * BYTES_PER_PIXEL * 2];
+        assert_eq!(unpad_rows(&data, 16, BYTES_PER_PIXEL), data);
+    }
+
+    #[test]
+    fn unpad_rows_handles_srgb_rows() {
+        // 10 px * 4 B = 40 B per row, padded to 256
+        let width = 10;
+        let mut data = Vec::new();
+        for row in 0..3u8 {
+            data.extend(std::iter::repeat_n(row + 1, width * SRGB_BYTES_PER_PIXEL));
+            data.extend(std::iter::repeat_n(0xAA, COPY_BYTES_PER_ROW_ALIGNMENT - width * SRGB_BYTES_PER_PIXEL));
+        }
+        let out = unpad_rows(&data, width, SRGB_BYTES_PER_PIXEL);
+        assert_eq!(out.len(), 3 * width * SRGB_BYTES_PER_PIXEL);
+        assert!(out[80..].iter().all(|&b| b == 3));
+        // 64 px * 4 B = 256 B, already aligned
+        let aligned = vec![5u8; 64 * SRGB_BYTES_PER_PIXEL];
+        assert_eq!(unpad_rows(&aligned, 64, SRGB_BYTES_PER_PIXEL), aligned);
+    }
+}
@@ -0,0 +1,139 @@
+// Color authoring helpers for pheromone layers.
+//
+// Layer colors are picked in HSV (perceptual sRGB space) and converted to the
+// linear RGBA stored in `PheromoneLayerParam.color`, so the composite pass
+// keeps working in linear space. Named palettes spread a gradient across all
+// layers at once.
+
+use bevy::math::{Vec3, Vec4};
+
+/// Convert HSV to sRGB. `h` is in degrees (wrapped to [0, 360)); `s` and `v`
+/// are clamped to [0, 1].
+pub fn hsv_to_rgb(h: f32, s: f32, v: f32) -> Vec3 {
+    let h = h.rem_euclid(360.0) / 60.0;
+    let s = s.clamp(0.0, 1.0);
+    let v = v.clamp(0.0, 1.0);
+    let c = v * s;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = v - c;
+    Vec3::new(r + m, g + m, b + m)
+}
+
+/// sRGB transfer function inverse (sRGB-encoded channel -> linear).
+pub fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Layer color from HSV, as the linear RGBA (alpha 1) used by the composite.
+pub fn layer_color_hsv(h: f32, s: f32, v: f32) -> Vec4 {
+    let rgb = hsv_to_rgb(h, s, v);
+    Vec4::new(
+        srgb_to_linear(rgb.x),
+        srgb_to_linear(rgb.y),
+        srgb_to_linear(rgb.z),
+        1.0,
+    )
+}
+
+/// Named layer palettes: sRGB stops the layers are spread evenly across.
+pub const PALETTES: &[(&str, &[[f32; 3]])] = &[
+    (
+        "fire",
+        &[[0.35, 0.02, 0.0], [0.8, 0.1, 0.0], [1.0, 0.45, 0.0], [1.0, 0.8, 0.1], [1.0, 1.0, 0.8]],
+    ),
+    (
+        "ocean",
+        &[[0.0, 0.15, 0.35], [0.0, 0.35, 0.6], [0.0, 0.6, 0.75], [0.3, 0.85, 0.85], [0.8, 1.0, 0.95]],
+    ),
+    (
+        "viridis",
+        &[
+            [0.267, 0.005, 0.329],
+            [0.231, 0.322, 0.545],
+            [0.129, 0.569, 0.549],
+            [0.369, 0.788, 0.384],
+            [0.993, 0.906, 0.144],
+        ],
+    ),
+];
+
+/// `count` linear layer colors sampled evenly from palette `name` (first
+/// layer at the dark end). `None` for unknown names.
+pub fn palette_colors(name: &str, count: usize) -> Option<Vec<Vec4>> {
+    let (_, stops) = PALETTES.iter().find(|(n, _)| *n == name)?;
+    let last = stops.len() - 1;
+    Some(
+        (0..count)
+            .map(|i| {
+                // A lone layer takes the bright end so it stays visible
+                let t = if count > 1 { i as f32 / (count - 1) as f32 } else { 1.0 };
+                let x = t * last as f32;
+                let k = (x.floor() as usize).min(last.saturating_sub(1));
+                let a = Vec3::from(stops[k]);
+                let b = Vec3::from(stops[(k + 1).min(last)]);
+                let rgb = a.lerp(b, x - k as f32);
+                Vec4::new(srgb_to_linear(rgb.x), srgb_to_linear(rgb.y), srgb_to_linear(rgb.z), 1.0)
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Vec3, b: Vec3) -> bool {
+        (a - b).abs().max_element() < 1e-5
+    }
+
+    #[test]
+    fn hsv_to_rgb_known_values() {
+        assert!(close(hsv_to_rgb(0.0, 1.0, 1.0), Vec3::new(1.0, 0.0, 0.0)));
+        assert!(close(hsv_to_rgb(120.0, 1.0, 1.0), Vec3::new(0.0, 1.0, 0.0)));
+        assert!(close(hsv_to_rgb(240.0, 1.0, 1.0), Vec3::new(0.0, 0.0, 1.0)));
+        assert!(close(hsv_to_rgb(60.0, 1.0, 1.0), Vec3::new(1.0, 1.0, 0.0)));
+        assert!(close(hsv_to_rgb(300.0, 1.0, 0.5), Vec3::new(0.5, 0.0, 0.5)));
+        // zero saturation is gray at value v
+        assert!(close(hsv_to_rgb(200.0, 0.0, 0.25), Vec3::splat(0.25)));
+        // hue wraps
+        assert!(close(hsv_to_rgb(360.0, 1.0, 1.0), hsv_to_rgb(0.0, 1.0, 1.0)));
+        assert!(close(hsv_to_rgb(-120.0, 1.0, 1.0), hsv_to_rgb(240.0, 1.0, 1.0)));
+    }
+
+    #[test]
+    fn layer_color_hsv_is_linear() {
+        // primaries and black/white are unchanged by the transfer function
+        assert_eq!(layer_color_hsv(0.0, 1.0, 1.0), Vec4::new(1.0, 0.0, 0.0, 1.0));
+        assert_eq!(layer_color_hsv(0.0, 0.0, 0.0), Vec4::new(0.0, 0.0, 0.0, 1.0));
+        // sRGB mid gray (~0.5) is ~0.214 linear
+        let gray = layer_color_hsv(0.0, 0.0, 0.5);
+        assert!((gray.x - 0.214).abs() < 1e-3);
+        assert_eq!(gray.w, 1.0);
+    }
+
+    #[test]
+    fn palette_colors_span_the_stops() {
+        assert!(palette_colors("nope", 3).is_none());
+        let fire = palette_colors("fire", 9).unwrap();
+        assert_eq!(fire.len(), 9);
+        // ends land exactly on the first and last stops
+        assert_eq!(fire[0].x, srgb_to_linear(0.35));
+        assert!((fire[8] - Vec4::new(1.0, 1.0, srgb_to_linear(0.8), 1.0)).abs().max_element() < 1e-5);
+        // brightness climbs along the palette
+        assert!(fire.windows(2).all(|w| w[0].y <= w[1].y));
+        assert_eq!(palette_colors("ocean", 1).unwrap()[0].w, 1.0);
+        assert!(palette_colors("viridis", 0).unwrap().is_empty());
+    }
+}
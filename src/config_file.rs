@@ -0,0 +1,330 @@
+// Save/load of the full simulation tuning to a RON file.
+//
+// `F5` writes the current `PheromoneConfig`, per-layer base params
+// (`PheromoneLayerParamsCpu`), and authored species to `CONFIG_FILE_PATH`.
+// `F9` reads it back: species entities are respawned and re-uploaded via
+// `upload_species_to_gpu`, the layer params buffer is recreated, and the
+// pheromone array images are reallocated if the layer count changed.
+
+use bevy::prelude::*;
+use bevy::render::renderer::RenderDevice;
+use serde::{Deserialize, Serialize};
+
+use crate::pheromones::make_pheromone_array_images;
+use crate::resources::{PheromoneConfig, PheromoneLayerParam, SimulationSize};
+use crate::setup::{PheromoneLayerParamsCpu, default_layer_params, insert_layer_params};
+use crate::species::{
+    AgentColor, AgentSpecies, Avoidance, DepositFatigue, DepositRadius, DepositThreshold, EmitsPheromone,
+    ExtraEmits, LayerWeights, MaxAge, MaxTurn, MoveSpeed, Sensor, SensorResponse, SharedEmit, SpeciesDef,
+    SpeciesHeading, SpeedJitter, Steering, TurnBias, TurnSpeed, Wander,
+    species_def_from_components, spawn_species_def, upload_species_to_gpu,
+};
+
+pub struct ConfigFilePlugin;
+impl Plugin for ConfigFilePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (save_config, load_config));
+    }
+}
+
+/// On-disk snapshot of the tunable simulation state.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SimConfigFile {
+    pub pheromones: PheromoneConfig,
+    pub layers: Vec<LayerParamDef>,
+    pub species: Vec<SpeciesDef>,
+}
+
+/// File-side `PheromoneLayerParam` (base rates, not per-frame factors).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LayerParamDef {
+    pub diffusion: f32,
+    pub decay: f32,
+    #[serde(default)]
+    pub kernel: u32,
+    #[serde(default)]
+    pub frozen: bool,
+    pub color: [f32; 4],
+    /// Concentration cap; 0 (the default) = unbounded.
+    #[serde(default)]
+    pub max_value: f32,
+    /// Diagonal blur weight; 0 (the default) = the kernel's own.
+    #[serde(default)]
+    pub diagonal_weight: f32,
+    /// `false` turns off spatial spread (decay still runs).
+    #[serde(default = "enabled")]
+    pub diffuse_enabled: bool,
+    /// `false` turns off decay (spread still runs).
+    #[serde(default = "enabled")]
+    pub decay_enabled: bool,
+    /// Drift direction; any length (only the direction is used).
+    #[serde(default)]
+    pub flow_dir: [f32; 2],
+    /// Drift speed in texels per second; 0 (the default) = no drift.
+    #[serde(default)]
+    pub flow_strength: f32,
+    /// Value trails decay toward instead of 0; 0 (the default) = plain decay.
+    #[serde(default)]
+    pub decay_floor: f32,
+    /// Brush strength multiplier for this layer; 1 (the default) = plain.
+    #[serde(default = "unit")]
+    pub brush_amount: f32,
+    /// `true` makes diffusion conserve the layer's total (no decay or flow).
+    #[serde(default)]
+    pub conserve_mass: bool,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+fn unit() -> f32 {
+    1.0
+}
+
+impl From<&PheromoneLayerParam> for LayerParamDef {
+    fn from(p: &PheromoneLayerParam) -> Self {
+        Self {
+            diffusion: p.diffusion,
+            decay: p.decay,
+            kernel: p.kernel,
+            frozen: p.frozen != 0,
+            color: p.color.to_array(),
+            max_value: p.max_value,
+            diagonal_weight: p.diagonal_weight,
+            diffuse_enabled: p.diffuse_enabled != 0,
+            decay_enabled: p.decay_enabled != 0,
+            flow_dir: p.flow_dir.to_array(),
+            flow_strength: p.flow_strength,
+            decay_floor: p.decay_floor,
+            brush_amount: p.brush_amount,
+            conserve_mass: p.conserve_mass != 0,
+        }
+    }
+}
+
+/// Build `layer_count` params from file definitions, padding missing layers
+/// with the startup defaults and dropping extras.
+pub fn layer_params_from_defs(defs: &[LayerParamDef], layer_count: u32) -> Vec<PheromoneLayerParam> {
+    let mut params = default_layer_params(layer_count);
+    for (param, def) in params.iter_mut().zip(defs.iter()) {
+        param.diffusion = def.diffusion;
+        param.decay = def.decay;
+        param.kernel = def.kernel;
+        param.frozen = def.frozen as u32;
+        param.color = Vec4::from_array(def.color);
+        param.max_value = def.max_value;
+        param.diagonal_weight = def.diagonal_weight;
+        param.diffuse_enabled = def.diffuse_enabled as u32;
+        param.decay_enabled = def.decay_enabled as u32;
+        param.flow_dir = Vec2::from_array(def.flow_dir);
+        param.flow_strength = def.flow_strength;
+        param.decay_floor = def.decay_floor.max(0.0);
+        param.brush_amount = if def.brush_amount.is_finite() { def.brush_amount.max(0.0) } else { 1.0 };
+        param.conserve_mass = def.conserve_mass as u32;
+    }
+    params
+}
+
+pub fn config_to_ron(config: &SimConfigFile) -> Result<String, String> {
+    ron::ser::to_string_pretty(config, ron::ser::PrettyConfig::default()).map_err(|err| err.to_string())
+}
+
+pub fn config_from_ron(text: &str) -> Result<SimConfigFile, String> {
+    ron::from_str(text).map_err(|err| err.to_string())
+}
+
+#[allow(clippy::type_complexity)]
+fn save_config(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    phero_cfg: Res<PheromoneConfig>,
+    layer_params: Res<PheromoneLayerParamsCpu>,
+    query: Query<
+        (
+            &AgentColor,
+            &MoveSpeed,
+            &TurnSpeed,
+            &Sensor,
+            Option<&EmitsPheromone>,
+            Option<&ExtraEmits>,
+            Option<&LayerWeights>,
+            Option<&Wander>,
+            Option<&MaxTurn>,
+            Option<&Steering>,
+            Option<&SpeedJitter>,
+            Option<&MaxAge>,
+            Option<&TurnBias>,
+            Option<&DepositRadius>,
+            // Nested: a query tuple holds at most 15 elements
+            (
+                Option<&SensorResponse>,
+                Option<&SpeciesHeading>,
+                Option<&Avoidance>,
+                Option<&DepositThreshold>,
+                Option<&DepositFatigue>,
+                Option<&SharedEmit>,
+            ),
+        ),
+        With<AgentSpecies>,
+    >,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F5) {
+        return;
+    }
+    let config = SimConfigFile {
+        pheromones: phero_cfg.clone(),
+        layers: layer_params.params.iter().map(LayerParamDef::from).collect(),
+        species: query
+            .iter()
+            .map(
+                |(
+                    color,
+                    move_speed,
+                    turn_speed,
+                    sensor,
+                    emit,
+                    extra,
+                    weights,
+                    wander,
+                    max_turn,
+                    steering,
+                    jitter,
+                    max_age,
+                    turn_bias,
+                    deposit_radius,
+                    (response, heading, avoidance, threshold, fatigue, shared),
+                )| {
+                    species_def_from_components(
+                        color, move_speed, turn_speed, sensor, emit, extra, weights, wander, max_turn,
+                        steering, jitter, max_age, turn_bias, deposit_radius, response, heading, avoidance,
+                        threshold, fatigue, shared,
+                    )
+                },
+            )
+            .collect(),
+    };
+    let path = crate::CONFIG_FILE_PATH;
+    match config_to_ron(&config).and_then(|text| std::fs::write(path, text).map_err(|err| err.to_string())) {
+        Ok(()) => info!("Config: saved to {path}"),
+        Err(err) => error!("Config: failed to save {path}: {err}"),
+    }
+}
+
+fn load_config(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut phero_cfg: ResMut<PheromoneConfig>,
+    mut images: ResMut<Assets<Image>>,
+    render_device: Res<RenderDevice>,
+    sim_size: Res<SimulationSize>,
+    species: Query<Entity, With<AgentSpecies>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F9) {
+        return;
+    }
+    let path = crate::CONFIG_FILE_PATH;
+    let config = match std::fs::read_to_string(path)
+        .map_err(|err| err.to_string())
+        .and_then(|text| config_from_ron(&text))
+    {
+        Ok(config) => config,
+        Err(err) => {
+            warn!("Config: failed to load {path}: {err}");
+            return;
+        }
+    };
+
+    // Layer count drives the array depth, params length, and weights stride
+    let old_layer_count = phero_cfg.layer_count.max(1);
+    *phero_cfg = config.pheromones.clone();
+    for fix in phero_cfg.validate() {
+        warn!("Config: {fix}");
+    }
+    let layer_count = phero_cfg.layer_count.max(1);
+    if layer_count != old_layer_count {
+        info!("Pheromones: layers = {layer_count} (was {old_layer_count})");
+        commands.insert_resource(make_pheromone_array_images(&mut images, layer_count, sim_size.0));
+    }
+    insert_layer_params(
+        &mut commands,
+        &render_device,
+        layer_params_from_defs(&config.layers, layer_count),
+    );
+
+    // Respawn species, then rebuild their GPU buffers once the spawns apply
+    for entity in species.iter() {
+        commands.entity(entity).despawn();
+    }
+    for def in config.species.iter() {
+        spawn_species_def(&mut commands, def);
+    }
+    commands.run_system_cached(upload_species_to_gpu);
+    info!("Config: loaded {path}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::species::default_species_defs;
+
+    #[test]
+    fn config_ron_roundtrip() {
+        let config = SimConfigFile {
+            pheromones: PheromoneConfig {
+                layer_count: 5,
+                brush_target_layer: 1,
+                universal_love_layers: vec![1],
+                universal_hate_layers: vec![0],
+                wrap_diffusion: true,
+                brush_bindings: [crate::resources::BRUSH_LAYER, 0, 3],
+                shared_layer: Some(2),
+            },
+            layers: default_layer_params(5).iter().map(LayerParamDef::from).collect(),
+            species: default_species_defs(),
+        };
+        let text = config_to_ron(&config).expect("serializes");
+        let back = config_from_ron(&text).expect("parses");
+        assert_eq!(back, config);
+    }
+
+    #[test]
+    fn layer_params_from_defs_pads_and_truncates() {
+        let defs = vec![LayerParamDef {
+            diffusion: 0.1,
+            decay: 0.2,
+            kernel: 1,
+            frozen: true,
+            color: [0.5, 0.5, 0.5, 1.0],
+            max_value: 2.0,
+            diagonal_weight: 0.7,
+            diffuse_enabled: false,
+            decay_enabled: true,
+            flow_dir: [0.0, 1.0],
+            flow_strength: 12.0,
+            decay_floor: 0.05,
+            brush_amount: 3.0,
+            conserve_mass: true,
+        }];
+        let params = layer_params_from_defs(&defs, 3);
+        assert_eq!(params.len(), 3);
+        assert_eq!(params[0].diffusion, 0.1);
+        assert_eq!(params[0].kernel, 1);
+        assert_eq!(params[0].frozen, 1);
+        assert_eq!(params[1].frozen, 0);
+        assert_eq!(params[0].max_value, 2.0);
+        assert_eq!(params[1].max_value, 0.0);
+        assert_eq!(params[0].diagonal_weight, 0.7);
+        assert_eq!((params[0].diffuse_enabled, params[0].decay_enabled), (0, 1));
+        assert_eq!(params[1].diffuse_enabled, 1);
+        assert_eq!((params[0].flow_dir, params[0].flow_strength), (Vec2::Y, 12.0));
+        assert_eq!(params[1].flow_strength, 0.0);
+        assert_eq!((params[0].decay_floor, params[1].decay_floor), (0.05, 0.0));
+        assert_eq!((params[0].brush_amount, params[1].brush_amount), (3.0, 1.0));
+        assert_eq!((params[0].conserve_mass, params[1].conserve_mass), (1, 0));
+        // remaining layers keep startup defaults
+        assert_eq!(params[1].diffusion, default_layer_params(3)[1].diffusion);
+
+        let params = layer_params_from_defs(&defs, 0);
+        assert!(params.is_empty());
+    }
+}
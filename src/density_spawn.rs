@@ -0,0 +1,142 @@
+// Respawn agents from a painted density map.
+//
+// `N` reads back the pheromone array once and, when it lands, replaces the
+// agent population with agents sampled proportionally to the brush layer's
+// values (`agents::agents_from_density`), so agents start where you painted.
+// Either array of the ping-pong pair works: they differ by one frame. A layer
+// with nothing painted falls back to the disc spawn. Pheromones are left
+// alone, so the painted layer keeps attracting (or repelling) the new agents.
+
+use bevy::prelude::*;
+use bevy::render::gpu_readback::{Readback, ReadbackComplete};
+
+use crate::agents::{
+    AgentPopulation, AgentSpeciesMix, AgentsCpu, SimSeed, SpawnPattern, agents_from_density, generate_agents,
+    place_agents_in_regions,
+};
+use crate::pheromones::PheromoneArrayImages;
+use crate::resources::{PheromoneConfig, SimRegions, SimulationSize};
+use crate::setup::OverlayNotice;
+use crate::species::{AgentSpecies, SpeciesHeading, authored_species_count, species_heading_modes};
+
+// wgpu requires buffer copy rows to be aligned to this many bytes
+const COPY_BYTES_PER_ROW_ALIGNMENT: usize = 256;
+// R32Float
+const BYTES_PER_TEXEL: usize = 4;
+
+pub struct DensitySpawnPlugin;
+impl Plugin for DensitySpawnPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, handle_density_spawn_hotkey);
+    }
+}
+
+fn handle_density_spawn_hotkey(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    arrays: Res<PheromoneArrayImages>,
+    phero_cfg: Res<PheromoneConfig>,
+    sim_size: Res<SimulationSize>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyN) {
+        return;
+    }
+    let layer = phero_cfg.brush_target_layer;
+    let size = sim_size.0;
+    commands
+        .spawn(Readback::texture(arrays.prev.clone()))
+        .observe(move |event: On<ReadbackComplete>, mut commands: Commands| {
+            // Readback repeats every frame while the entity lives; we only want one
+            commands.entity(event.entity).despawn();
+            let density = layer_from_readback(&event.data, size, layer);
+            commands.run_system_cached_with(respawn_from_density, (density, size, layer));
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn respawn_from_density(
+    In((density, size, layer)): In<(Option<Vec<f32>>, UVec2, u32)>,
+    mut agents: ResMut<AgentsCpu>,
+    mut notice: ResMut<OverlayNotice>,
+    population: Res<AgentPopulation>,
+    species_mix: Res<AgentSpeciesMix>,
+    seed: Res<SimSeed>,
+    sim_size: Res<SimulationSize>,
+    regions: Res<SimRegions>,
+    species: Query<Option<&SpeciesHeading>, With<AgentSpecies>>,
+) {
+    if sim_size.0 != size {
+        warn!("Density spawn: simulation resized during readback, ignoring");
+        return;
+    }
+    let species_count = authored_species_count(&species);
+    // Same population, so the existing GPU buffer is reused by `sync_agents_to_gpu`
+    let sampled = density.and_then(|density| {
+        agents_from_density(&density, size, population.0, species_count, &species_mix.0, seed.0)
+    });
+    agents.list = match sampled {
+        Some(list) => {
+            notice.flash(format!("Spawned {} agents from layer {layer}", list.len()));
+            list
+        }
+        None => {
+            notice.flash(format!("Layer {layer} is empty: disc spawn"));
+            let mut list = generate_agents(
+                size,
+                population.0,
+                species_count,
+                &species_mix.0,
+                &species_heading_modes(&species),
+                SpawnPattern::DiscInward,
+                seed.0,
+            );
+            place_agents_in_regions(&mut list, size, &regions.0);
+            list
+        }
+    };
+    agents.upload_from = 0;
+}
+
+/// Pull one layer out of a raw readback of the `R32Float` array: layers are
+/// stacked, each `size.y` rows padded to the copy alignment. `None` if the
+/// data is too short to hold that layer.
+pub fn layer_from_readback(data: &[u8], size: UVec2, layer: u32) -> Option<Vec<f32>> {
+    let row_bytes = size.x as usize * BYTES_PER_TEXEL;
+    let padded_row_bytes = row_bytes.div_ceil(COPY_BYTES_PER_ROW_ALIGNMENT) * COPY_BYTES_PER_ROW_ALIGNMENT;
+    let layer_bytes = padded_row_bytes * size.y as usize;
+    let start = layer as usize * layer_bytes;
+    let layer_data = data.get(start..start + layer_bytes)?;
+    Some(
+        layer_data
+            .chunks_exact(padded_row_bytes)
+            .flat_map(|row| bytemuck::pod_collect_to_vec::<u8, f32>(&row[..row_bytes]))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layer_from_readback_skips_padding_and_other_layers() {
+        // 3 texels * 4 B = 12 B per row, padded to 256; 2 rows, 2 layers
+        let size = UVec2::new(3, 2);
+        let mut data = Vec::new();
+        for layer in 0..2 {
+            for row in 0..2 {
+                for x in 0..3 {
+                    let v = (layer * 100 + row * 10 + x) as f32;
+                    data.extend_from_slice(&v.to_le_bytes());
+                }
+                data.extend(std::iter::repeat_n(0xAA, COPY_BYTES_PER_ROW_ALIGNMENT - 12));
+            }
+        }
+        assert_eq!(layer_from_readback(&data, size, 0), Some(vec![0.0, 1.0, 2.0, 10.0, 11.0, 12.0]));
+        assert_eq!(
+            layer_from_readback(&data, size, 1),
+            Some(vec![100.0, 101.0, 102.0, 110.0, 111.0, 112.0])
+        );
+        assert_eq!(layer_from_readback(&data, size, 2), None);
+    }
+}
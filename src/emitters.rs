@@ -0,0 +1,92 @@
+// Persistent point emitters ("food sources").
+//
+// Shift + left click drops an `Emitter` on the brush layer under the cursor;
+// Shift + C removes them all. The list lives in `Emitters`, is extracted each
+// frame, and the render node stamps every emitter into the pheromone array
+// with `stamp_emitters` right after the brush in the input pass (so it is
+// skipped while the input stage is off, and costs nothing with no emitters).
+
+use bevy::prelude::*;
+
+use crate::input::SHIFT_KEYS;
+use crate::resources::{Emitter, Emitters, GlobalUniforms, PheromoneConfig};
+use crate::setup::OverlayNotice;
+
+// Value per second added at an emitter's centre
+const EMITTER_AMOUNT: f32 = 20.0;
+
+pub struct EmittersPlugin;
+impl Plugin for EmittersPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Emitters>()
+            .add_systems(Update, (place_emitter_on_shift_click, handle_clear_emitters_hotkey));
+    }
+}
+
+fn place_emitter_on_shift_click(
+    buttons: Res<ButtonInput<MouseButton>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    cfg: Res<PheromoneConfig>,
+    globals: Res<GlobalUniforms>,
+    mut emitters: ResMut<Emitters>,
+    mut notice: ResMut<OverlayNotice>,
+) {
+    if !buttons.just_pressed(MouseButton::Left) || !keyboard_input.any_pressed(SHIFT_KEYS) {
+        return;
+    }
+    let Some(pos) = emitter_position(globals.mouse_position, globals.screen_size) else {
+        return;
+    };
+    let layer = cfg.brush_target_layer;
+    emitters.push(Emitter { pos, layer, amount: EMITTER_AMOUNT });
+    info!("Emitters: placed on layer {layer} at {pos} ({} total)", emitters.0.len());
+    notice.flash(format!("Emitter on layer {layer} ({} placed)", emitters.0.len()));
+}
+
+fn handle_clear_emitters_hotkey(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut emitters: ResMut<Emitters>,
+    mut notice: ResMut<OverlayNotice>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyC) || !keyboard_input.any_pressed(SHIFT_KEYS) {
+        return;
+    }
+    if emitters.0.is_empty() {
+        return;
+    }
+    info!("Emitters: cleared {}", emitters.0.len());
+    emitters.0.clear();
+    notice.flash("Emitters cleared");
+}
+
+/// Texel position for an emitter under the cursor, kept inside the field, or
+/// `None` before the cursor has entered the window (parked at -10000).
+pub fn emitter_position(cursor: Vec2, size: Vec2) -> Option<Vec2> {
+    (cursor.x > -9000.0).then(|| cursor.clamp(Vec2::ZERO, (size - Vec2::ONE).max(Vec2::ZERO)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::MAX_EMITTERS;
+
+    #[test]
+    fn emitters_cap_drops_the_oldest() {
+        let mut emitters = Emitters::default();
+        for i in 0..MAX_EMITTERS + 2 {
+            emitters.push(Emitter { pos: Vec2::splat(i as f32), layer: 0, amount: 1.0 });
+        }
+        assert_eq!(emitters.0.len(), MAX_EMITTERS);
+        assert_eq!(emitters.0[0].pos, Vec2::splat(2.0));
+        assert_eq!(emitters.0.last().unwrap().pos, Vec2::splat((MAX_EMITTERS + 1) as f32));
+        assert_eq!(std::mem::size_of::<Emitter>(), 16);
+    }
+
+    #[test]
+    fn emitter_position_clamps_and_ignores_parked_cursor() {
+        let size = Vec2::new(100.0, 50.0);
+        assert_eq!(emitter_position(Vec2::new(10.0, 20.0), size), Some(Vec2::new(10.0, 20.0)));
+        assert_eq!(emitter_position(Vec2::new(-10000.0, -10000.0), size), None);
+        assert_eq!(emitter_position(Vec2::new(100.0, 50.0), size), Some(Vec2::new(99.0, 49.0)));
+    }
+}
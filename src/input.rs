@@ -3,10 +3,28 @@
 // - `MouseWorldPos` stores the mouse position in world (texture) coordinates
 //   so shaders can read it via the `GlobalUniforms` uniform buffer.
 // - `MouseButtonState` tracks left/right button pressed state for the brush.
+// - `KEYBINDINGS` lists every binding (including those handled by other
+//   plugins) and feeds the `H` help overlay; add new bindings there.
 
 use bevy::{input::keyboard, prelude::*};
-use crate::resources::PheromoneConfig;
+use crate::resources::{
+    ActiveSpecies, AgentRenderConfig, AgentSimRunConfig, AgentView, BeaconSettings, BoundaryMode, BrushFalloff,
+    BrushMode, BrushTarget, ClearPheromonesRequest, CompositeSettings, DisplayFilter, GlobalUniforms,
+    MAX_DIFFUSE_ITERATIONS, PheromoneConfig, PheromoneImages, SimulationSize, SrgbDisplay, step_time_scale,
+};
+use crate::agents::{
+    AgentPopulation, AgentSpeciesMix, AgentsCpu, SimSeed, SpawnPattern, append_agents, brush_agents,
+    brush_species_index, generate_agents, max_agent_capacity, place_agents_in_regions,
+};
+use crate::setup::{OverlayNotice, PheromoneLayerParamsCpu, apply_palette, palette_names, srgb_display_image};
+use crate::species::{
+    AgentSpecies, EmitsPheromone, LayerWeights, SpeciesEnabled, SpeciesHeading, effective_layer_weight,
+    nudge_layer_weight, authored_species_count, species_heading_modes, toggle_species_enabled,
+    universal_layer_weight, upload_species_to_gpu,
+};
 use bevy::input::mouse::MouseWheel;
+use bevy::render::extract_resource::ExtractResource;
+use bevy::render::renderer::RenderDevice;
 
 pub struct InputPlugin;
 impl Plugin for InputPlugin {
@@ -19,19 +37,167 @@ impl Plugin for InputPlugin {
                 handle_keyboard_input,
                 handle_mouse_wheel_layer,
                 handle_brush_hotkeys,
+                handle_clear_hotkey,
+                handle_reset_hotkey,
+                handle_boundary_hotkey,
+                // Nested groups keep this under Bevy's 20-system tuple limit
+                (handle_blend_mode_hotkey, handle_solo_layer_hotkey, handle_exposure_hotkeys),
+                (handle_layer_rate_hotkeys, handle_brush_amount_hotkeys),
+                handle_layer_flow_hotkeys,
+                handle_freeze_hotkey,
+                (handle_brush_mode_hotkey, handle_brush_target_hotkey, handle_brush_falloff_hotkey),
+                (
+                    handle_agent_dots_hotkey,
+                    handle_agent_render_hotkeys,
+                    handle_sensor_markers_hotkey,
+                    handle_display_filter_hotkey,
+                ),
+                (handle_stage_hotkeys, handle_diffuse_iteration_hotkeys, handle_time_scale_hotkeys),
+                (handle_palette_hotkey, handle_species_enable_hotkey, handle_agent_view_hotkey),
+                spawn_agents_with_brush,
+                (
+                    handle_species_weight_hotkeys,
+                    follow_active_species_layer,
+                    track_active_species_weight,
+                )
+                    .chain(),
+                toggle_help_overlay,
             ),
         )
+        .add_systems(Startup, spawn_help_overlay)
+        .add_systems(First, reset_clear_request)
+        .init_resource::<BrushMode>()
+        .init_resource::<BrushTarget>()
+        .init_resource::<BrushFalloff>()
+        .init_resource::<BeaconSettings>()
+        .init_resource::<ActiveSpecies>()
+        .init_resource::<AgentView>()
+        .init_resource::<AgentRenderConfig>()
         .insert_resource(MouseWorldPos(Vec2::ZERO))
         .insert_resource(MouseButtonState {
             left_pressed: false,
             right_pressed: false,
+            middle_pressed: false,
         });
     }
 }
 
+/// Every active binding as (input, action), shown by the `H` help overlay.
+pub const KEYBINDINGS: &[(&str, &str)] = &[
+    ("H", "Toggle this help"),
+    ("Left mouse", "Paint brush layer (or spawn agents / drag beacon)"),
+    ("Right mouse", "Erase brush layer (or paint its bound layer)"),
+    ("Middle mouse", "Paint its bound layer (brush layer by default)"),
+    ("Mouse wheel", "Cycle brush layer"),
+    ("0-9", "Select brush layer 0-9"),
+    ("Shift + 0-9", "Select brush layer 10-19 (20+: mouse wheel only)"),
+    ("PageUp / PageDown", "Move brush layer up / down the stack"),
+    ("Up / Down", "Brush layer diffusion +/-"),
+    ("Right / Left", "Brush layer decay +/-"),
+    ("Shift + arrows", "Push brush layer flow (drift) that way"),
+    ("F", "Freeze / unfreeze brush layer"),
+    ("A", "Toggle brush: pheromone / spawn agents"),
+    ("E", "Toggle brush: pheromone / beacon (drag an attractor)"),
+    ("D", "Toggle agent dot overlay"),
+    ("Shift + D", "Cycle agent dot size (1-5 px)"),
+    ("W", "Toggle agent dots: overwrite / additive glow"),
+    ("J", "Toggle sensor markers for a few agents (left green, forward white, right magenta)"),
+    ("Y", "Toggle view: pheromone field / agents with fading trails"),
+    ("I", "Toggle display filtering: nearest / linear"),
+    ("L", "Cycle layer color palette"),
+    ("G", "Toggle brush layer: manual / follow active species"),
+    ("K", "Cycle brush falloff: quadratic / constant / linear / gaussian"),
+    ("Tab", "Cycle active species"),
+    ("U", "Pause / resume active species (agents freeze, no deposits)"),
+    ("= / -", "Active species weight for brush layer +/-"),
+    ("C", "Clear all pheromones"),
+    ("Shift + left mouse", "Place an emitter on the brush layer"),
+    ("Shift + C", "Clear all emitters"),
+    ("R", "Reset: re-seed agents and clear pheromones"),
+    ("N", "Respawn agents where the brush layer is painted"),
+    ("B", "Toggle bounce / wrap edges"),
+    ("M", "Cycle composite blend mode"),
+    ("O", "Solo brush layer as grayscale"),
+    ("[ / ]", "Exposure down / up"),
+    ("Shift + [ / ]", "Brush layer brush strength down / up"),
+    ("T", "Toggle tone mapping"),
+    ("S", "Toggle layer min/mean/max stats"),
+    ("P", "Save PNG capture"),
+    ("V", "Start / stop PNG sequence recording"),
+    ("F5", "Save config"),
+    ("F6", "Reload species file"),
+    ("F9", "Load config"),
+    ("F1", "Toggle brush/input stage"),
+    ("F2", "Toggle diffusion/decay stage"),
+    (", / .", "Diffusion passes per frame -/+"),
+    ("Z / X", "Simulation speed slower / faster (slowest = frozen)"),
+    ("F3", "Toggle agent stage"),
+    ("Q", "Toggle auto quality (fewer diffusion passes when FPS drops)"),
+    ("Escape", "Quit"),
+];
+
+#[derive(Component)]
+pub struct HelpOverlay;
+
+/// Render `KEYBINDINGS` as aligned lines for the overlay.
+pub fn help_text() -> String {
+    let width = KEYBINDINGS.iter().map(|(k, _)| k.len()).max().unwrap_or(0);
+    KEYBINDINGS
+        .iter()
+        .map(|(key, action)| format!("{key:<width$}  {action}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn spawn_help_overlay(mut commands: Commands) {
+    commands.spawn((
+        Text::new(help_text()),
+        TextFont { font_size: 16.0, ..default() },
+        TextColor(Color::WHITE),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(32.0),
+            left: Val::Px(8.0),
+            ..default()
+        },
+        Visibility::Hidden,
+        HelpOverlay,
+    ));
+}
+
+// H shows/hides the keybinding overlay
+fn toggle_help_overlay(
+    keyboard_input: Res<ButtonInput<keyboard::KeyCode>>,
+    mut overlay: Query<&mut Visibility, With<HelpOverlay>>,
+) {
+    if !keyboard_input.just_pressed(keyboard::KeyCode::KeyH) {
+        return;
+    }
+    for mut vis in &mut overlay {
+        *vis = match *vis {
+            Visibility::Hidden => Visibility::Inherited,
+            _ => Visibility::Hidden,
+        };
+    }
+}
+
 #[derive(Resource)]
 pub struct MouseWorldPos(pub Vec2);
 
+/// Map a world-space cursor position onto simulation texels. The display
+/// sprite (`display_size` on screen, i.e. its sim-sized `custom_size` times
+/// the `Transform` display scale) is centred on the origin and stretched over
+/// a `sim_size` texture whose rows run top-down, so Y is flipped against the
+/// sprite's own height rather than the texture's. The result is clamped to
+/// the texture after the flip so off-screen cursors land on a valid texel.
+pub fn cursor_to_texel(world: Vec2, display_size: Vec2, sim_size: Vec2) -> Vec2 {
+    let display_size = display_size.max(Vec2::ONE);
+    let local = world + display_size / 2.0;
+    let flipped = Vec2::new(local.x, display_size.y - local.y);
+    let tex = flipped * sim_size / display_size;
+    tex.clamp(Vec2::ZERO, (sim_size - Vec2::ONE).max(Vec2::ZERO))
+}
+
 fn update_mouse_position(
     mut cursor_moved_events: MessageReader<CursorMoved>,
     mut mouse_pos: ResMut<MouseWorldPos>,
@@ -48,17 +214,32 @@ fn update_mouse_position(
     }
 }
 
-#[derive(Resource)]
+#[derive(Resource, Clone, ExtractResource)]
 pub struct MouseButtonState {
     pub left_pressed: bool,
     pub right_pressed: bool,
+    pub middle_pressed: bool,
+}
+
+impl MouseButtonState {
+    /// Held buttons as `[left, right, middle]` (see `PheromoneConfig::brush_stroke`).
+    pub fn held(&self) -> [bool; 3] {
+        [self.left_pressed, self.right_pressed, self.middle_pressed]
+    }
 }
 
 fn handle_button_input(
     buttons: Res<ButtonInput<MouseButton>>,
+    keyboard_input: Res<ButtonInput<keyboard::KeyCode>>,
+    brush_mode: Res<BrushMode>,
     mut mouse_button_state: ResMut<MouseButtonState>,
 ) {
-    if buttons.just_pressed(MouseButton::Left) {
+    // In spawn mode the left button adds agents instead of painting, and
+    // Shift + left click places an emitter (see emitters.rs)
+    if buttons.just_pressed(MouseButton::Left)
+        && *brush_mode != BrushMode::SpawnAgents
+        && !keyboard_input.any_pressed(SHIFT_KEYS)
+    {
         mouse_button_state.left_pressed = true;
     }
     if buttons.just_released(MouseButton::Left) {
@@ -70,12 +251,20 @@ fn handle_button_input(
     if buttons.just_released(MouseButton::Right) {
         mouse_button_state.right_pressed = false;
     }
+    if buttons.just_pressed(MouseButton::Middle) {
+        mouse_button_state.middle_pressed = true;
+    }
+    if buttons.just_released(MouseButton::Middle) {
+        mouse_button_state.middle_pressed = false;
+    }
 }
 
-fn handle_keyboard_input(keyboard_input: Res<ButtonInput<keyboard::KeyCode>>) {
+// Escape requests a normal shutdown (rather than `process::exit`) so Bevy
+// runs its cleanup and in-flight screenshot/recording writes can finish
+fn handle_keyboard_input(keyboard_input: Res<ButtonInput<keyboard::KeyCode>>, mut exit: MessageWriter<AppExit>) {
     if keyboard_input.just_pressed(keyboard::KeyCode::Escape) {
-        println!("Escape key pressed. Exiting...");
-        std::process::exit(0);
+        info!("Escape key pressed. Exiting...");
+        exit.write(AppExit::Success);
     }
 }
 
@@ -102,7 +291,8 @@ fn handle_mouse_wheel_layer(
     }
 }
 
-// Number keys 0-9 set brush target layer directly (clamped to available layers)
+// Number keys 0-9 set brush target layer directly, Shift+0-9 layers 10-19
+// (see `digit_layer`); layers from `DIGIT_LAYER_LIMIT` up need the wheel
 fn handle_brush_hotkeys(
     keyboard_input: Res<ButtonInput<keyboard::KeyCode>>,
     mut cfg: ResMut<PheromoneConfig>,
@@ -120,16 +310,658 @@ fn handle_brush_hotkeys(
         keyboard::KeyCode::Digit8,
         keyboard::KeyCode::Digit9,
     ];
-    let mut set: Option<u32> = None;
-    for (idx, key) in keys.iter().enumerate() {
-        if keyboard_input.just_pressed(*key) {
-            set = Some(idx as u32);
-            break;
+    let Some(digit) = keys.iter().position(|key| keyboard_input.just_pressed(*key)) else { return; };
+    let shifted = keyboard_input.any_pressed(SHIFT_KEYS);
+    cfg.brush_target_layer = digit_layer(digit as u32, shifted, cfg.layer_count);
+}
+
+// Layers selectable by number key: 0-9 plain, 10-19 with Shift
+pub const DIGIT_LAYER_LIMIT: u32 = 20;
+
+/// Layer picked by number key `digit` (0-9), offset by 10 with Shift and
+/// clamped to the last layer, so keys past the layer count select the top one.
+pub fn digit_layer(digit: u32, shifted: bool, layer_count: u32) -> u32 {
+    let layer = digit.min(9) + if shifted { 10 } else { 0 };
+    layer.min(layer_count.saturating_sub(1))
+}
+
+// C requests a one-frame clear of every pheromone layer
+fn handle_clear_hotkey(
+    keyboard_input: Res<ButtonInput<keyboard::KeyCode>>,
+    mut clear: ResMut<ClearPheromonesRequest>,
+) {
+    // Shift + C clears emitters instead
+    if keyboard_input.just_pressed(keyboard::KeyCode::KeyC) && !keyboard_input.any_pressed(SHIFT_KEYS) {
+        clear.pending = true;
+    }
+}
+
+// R soft-restarts the dynamic state: fresh agents from a new seed plus a
+// pheromone clear. Species, layer params, and config are left untouched.
+#[allow(clippy::too_many_arguments)]
+fn handle_reset_hotkey(
+    keyboard_input: Res<ButtonInput<keyboard::KeyCode>>,
+    mut agents: ResMut<AgentsCpu>,
+    mut seed: ResMut<SimSeed>,
+    mut clear: ResMut<ClearPheromonesRequest>,
+    population: Res<AgentPopulation>,
+    species_mix: Res<AgentSpeciesMix>,
+    spawn_pattern: Res<SpawnPattern>,
+    sim_size: Res<crate::resources::SimulationSize>,
+    regions: Res<crate::resources::SimRegions>,
+    species: Query<Option<&SpeciesHeading>, With<AgentSpecies>>,
+) {
+    if !keyboard_input.just_pressed(keyboard::KeyCode::KeyR) {
+        return;
+    }
+    *seed = SimSeed::default();
+    info!("Reset: seed = {}", seed.0);
+    // Same population, so the existing GPU buffer is reused by `sync_agents_to_gpu`
+    agents.list = generate_agents(
+        sim_size.0,
+        population.0,
+        authored_species_count(&species),
+        &species_mix.0,
+        &species_heading_modes(&species),
+        *spawn_pattern,
+        seed.0,
+    );
+    place_agents_in_regions(&mut agents.list, sim_size.0, &regions.0);
+    agents.upload_from = 0;
+    clear.pending = true;
+}
+
+// The request was extracted last frame, so the clear has been dispatched
+fn reset_clear_request(mut clear: ResMut<ClearPheromonesRequest>) {
+    if clear.pending {
+        clear.pending = false;
+    }
+}
+
+// B toggles between bouncing and wrapping world edges
+fn handle_boundary_hotkey(
+    keyboard_input: Res<ButtonInput<keyboard::KeyCode>>,
+    mut boundary: ResMut<BoundaryMode>,
+) {
+    if keyboard_input.just_pressed(keyboard::KeyCode::KeyB) {
+        *boundary = match *boundary {
+            BoundaryMode::Bounce => BoundaryMode::Wrap,
+            BoundaryMode::Wrap => BoundaryMode::Bounce,
+        };
+        info!("Boundary mode: {:?}", *boundary);
+    }
+}
+
+// M cycles the composite blend mode (weighted average -> additive -> max)
+fn handle_blend_mode_hotkey(
+    keyboard_input: Res<ButtonInput<keyboard::KeyCode>>,
+    mut composite: ResMut<CompositeSettings>,
+) {
+    if keyboard_input.just_pressed(keyboard::KeyCode::KeyM) {
+        composite.blend_mode = composite.blend_mode.next();
+        info!("Composite blend mode: {:?}", composite.blend_mode);
+    }
+}
+
+// O swaps the composite for a grayscale view of the brush layer alone
+fn handle_solo_layer_hotkey(
+    keyboard_input: Res<ButtonInput<keyboard::KeyCode>>,
+    mut composite: ResMut<CompositeSettings>,
+) {
+    if keyboard_input.just_pressed(keyboard::KeyCode::KeyO) {
+        composite.solo_layer = !composite.solo_layer;
+        info!("Solo layer view: {}", if composite.solo_layer { "on" } else { "off" });
+    }
+}
+
+// [ / ] scale composite exposure down/up; T toggles Reinhard tone mapping
+fn handle_exposure_hotkeys(
+    keyboard_input: Res<ButtonInput<keyboard::KeyCode>>,
+    mut composite: ResMut<CompositeSettings>,
+) {
+    // Shift + brackets scale the brush strength instead
+    let shifted = keyboard_input.any_pressed(SHIFT_KEYS);
+    let step = 1.25;
+    if keyboard_input.just_pressed(keyboard::KeyCode::BracketLeft) && !shifted {
+        composite.exposure = (composite.exposure / step).max(1.0 / 64.0);
+        info!("Composite exposure: {:.3}", composite.exposure);
+    }
+    if keyboard_input.just_pressed(keyboard::KeyCode::BracketRight) && !shifted {
+        composite.exposure = (composite.exposure * step).min(64.0);
+        info!("Composite exposure: {:.3}", composite.exposure);
+    }
+    if keyboard_input.just_pressed(keyboard::KeyCode::KeyT) {
+        composite.tonemap = !composite.tonemap;
+        info!("Composite tonemap: {}", composite.tonemap);
+    }
+}
+
+pub(crate) const SHIFT_KEYS: [keyboard::KeyCode; 2] = [keyboard::KeyCode::ShiftLeft, keyboard::KeyCode::ShiftRight];
+
+// Up/Down adjust the brush layer's diffusion, Right/Left its decay. The params
+// buffer is re-uploaded from `PheromoneLayerParamsCpu` every frame.
+fn handle_layer_rate_hotkeys(
+    keyboard_input: Res<ButtonInput<keyboard::KeyCode>>,
+    cfg: Res<PheromoneConfig>,
+    mut layers: ResMut<PheromoneLayerParamsCpu>,
+) {
+    // Shift + arrows steer the layer's flow instead
+    if keyboard_input.any_pressed(SHIFT_KEYS) { return; }
+    let step = 0.05;
+    let mut d_diffusion = 0.0;
+    let mut d_decay = 0.0;
+    if keyboard_input.just_pressed(keyboard::KeyCode::ArrowUp) { d_diffusion += step; }
+    if keyboard_input.just_pressed(keyboard::KeyCode::ArrowDown) { d_diffusion -= step; }
+    if keyboard_input.just_pressed(keyboard::KeyCode::ArrowRight) { d_decay += step; }
+    if keyboard_input.just_pressed(keyboard::KeyCode::ArrowLeft) { d_decay -= step; }
+    if d_diffusion == 0.0 && d_decay == 0.0 { return; }
+    let layer = cfg.brush_target_layer as usize;
+    let Some(p) = layers.params.get_mut(layer) else { return; };
+    p.diffusion = (p.diffusion + d_diffusion).clamp(0.0, 1.0);
+    p.decay = (p.decay + d_decay).clamp(0.0, 1.0);
+    info!("Layer {layer}: diffusion {:.2}, decay {:.2}", p.diffusion, p.decay);
+}
+
+// Brush strength range and factor per Shift + bracket press
+const MIN_BRUSH_AMOUNT: f32 = 1.0 / 16.0;
+const MAX_BRUSH_AMOUNT: f32 = 16.0;
+const BRUSH_AMOUNT_STEP: f32 = 1.25;
+
+// Scale a layer's brush amount one step up or down, within the range above
+fn step_brush_amount(amount: f32, up: bool) -> f32 {
+    let amount = if amount.is_finite() && amount > 0.0 { amount } else { 1.0 };
+    let next = if up { amount * BRUSH_AMOUNT_STEP } else { amount / BRUSH_AMOUNT_STEP };
+    next.clamp(MIN_BRUSH_AMOUNT, MAX_BRUSH_AMOUNT)
+}
+
+// Shift + [ / ] scale the brush layer's brush strength (`brush_amount`)
+fn handle_brush_amount_hotkeys(
+    keyboard_input: Res<ButtonInput<keyboard::KeyCode>>,
+    cfg: Res<PheromoneConfig>,
+    mut layers: ResMut<PheromoneLayerParamsCpu>,
+    mut notice: ResMut<OverlayNotice>,
+) {
+    if !keyboard_input.any_pressed(SHIFT_KEYS) { return; }
+    let up = if keyboard_input.just_pressed(keyboard::KeyCode::BracketRight) {
+        true
+    } else if keyboard_input.just_pressed(keyboard::KeyCode::BracketLeft) {
+        false
+    } else {
+        return;
+    };
+    let layer = cfg.brush_target_layer as usize;
+    let Some(p) = layers.params.get_mut(layer) else { return; };
+    p.brush_amount = step_brush_amount(p.brush_amount, up);
+    info!("Layer {layer}: brush amount {:.2}", p.brush_amount);
+    notice.flash(format!("Layer {layer} brush x{:.2}", p.brush_amount));
+}
+
+// Flow velocity change per Shift + arrow press (texels/second), and its cap
+const FLOW_STEP: f32 = 5.0;
+const MAX_FLOW: f32 = 60.0;
+
+// Add `delta` to the flow velocity `dir * strength`, returning the new
+// (direction, strength). Tiny results snap to exactly zero (isotropic).
+fn nudge_flow(dir: Vec2, strength: f32, delta: Vec2) -> (Vec2, f32) {
+    let v = (dir.normalize_or_zero() * strength + delta).clamp_length_max(MAX_FLOW);
+    if v.length() < 1e-3 { (Vec2::ZERO, 0.0) } else { (v.normalize(), v.length()) }
+}
+
+// Shift + arrows push the brush layer's flow (drift) velocity in that direction
+fn handle_layer_flow_hotkeys(
+    keyboard_input: Res<ButtonInput<keyboard::KeyCode>>,
+    cfg: Res<PheromoneConfig>,
+    mut layers: ResMut<PheromoneLayerParamsCpu>,
+) {
+    if !keyboard_input.any_pressed(SHIFT_KEYS) { return; }
+    let mut delta = Vec2::ZERO;
+    if keyboard_input.just_pressed(keyboard::KeyCode::ArrowUp) { delta.y += FLOW_STEP; }
+    if keyboard_input.just_pressed(keyboard::KeyCode::ArrowDown) { delta.y -= FLOW_STEP; }
+    if keyboard_input.just_pressed(keyboard::KeyCode::ArrowRight) { delta.x += FLOW_STEP; }
+    if keyboard_input.just_pressed(keyboard::KeyCode::ArrowLeft) { delta.x -= FLOW_STEP; }
+    if delta == Vec2::ZERO { return; }
+    let layer = cfg.brush_target_layer as usize;
+    let Some(p) = layers.params.get_mut(layer) else { return; };
+    (p.flow_dir, p.flow_strength) = nudge_flow(p.flow_dir, p.flow_strength, delta);
+    info!("Layer {layer}: flow {:.1} texels/s toward {:?}", p.flow_strength, p.flow_dir);
+}
+
+// F toggles whether the brush layer diffuses and decays; a frozen layer keeps
+// whatever is painted into it as a static attractor/repellor map
+fn handle_freeze_hotkey(
+    keyboard_input: Res<ButtonInput<keyboard::KeyCode>>,
+    cfg: Res<PheromoneConfig>,
+    mut layers: ResMut<PheromoneLayerParamsCpu>,
+) {
+    if !keyboard_input.just_pressed(keyboard::KeyCode::KeyF) { return; }
+    let layer = cfg.brush_target_layer as usize;
+    let Some(p) = layers.params.get_mut(layer) else { return; };
+    p.frozen = (p.frozen == 0) as u32;
+    info!("Layer {layer}: {}", if p.frozen != 0 { "frozen" } else { "unfrozen" });
+}
+
+// A toggles the left-button brush between painting pheromone and adding
+// agents; E between painting and dragging the beacon (`BeaconSettings`)
+fn handle_brush_mode_hotkey(
+    keyboard_input: Res<ButtonInput<keyboard::KeyCode>>,
+    mut brush_mode: ResMut<BrushMode>,
+    mut mouse_button_state: ResMut<MouseButtonState>,
+) {
+    let toggled = if keyboard_input.just_pressed(keyboard::KeyCode::KeyA) {
+        BrushMode::SpawnAgents
+    } else if keyboard_input.just_pressed(keyboard::KeyCode::KeyE) {
+        BrushMode::Beacon
+    } else {
+        return;
+    };
+    *brush_mode = brush_mode.toggle(toggled);
+    // Don't keep painting if the toggle happens mid-stroke
+    mouse_button_state.left_pressed = false;
+    info!("Brush mode: {:?}", *brush_mode);
+}
+
+// D toggles the agent dot overlay drawn over the composite
+fn handle_agent_dots_hotkey(
+    keyboard_input: Res<ButtonInput<keyboard::KeyCode>>,
+    mut run_config: ResMut<AgentSimRunConfig>,
+) {
+    // Shift + D cycles the dot size instead
+    if !keyboard_input.just_pressed(keyboard::KeyCode::KeyD) || keyboard_input.any_pressed(SHIFT_KEYS) {
+        return;
+    }
+    run_config.draw_agent_dots = !run_config.draw_agent_dots;
+    info!("Agent dots: {}", run_config.draw_agent_dots);
+}
+
+// Shift + D cycles the agent dot size, W toggles additive dots
+fn handle_agent_render_hotkeys(
+    keyboard_input: Res<ButtonInput<keyboard::KeyCode>>,
+    mut render_cfg: ResMut<AgentRenderConfig>,
+    mut notice: ResMut<OverlayNotice>,
+) {
+    if keyboard_input.just_pressed(keyboard::KeyCode::KeyD) && keyboard_input.any_pressed(SHIFT_KEYS) {
+        render_cfg.point_size = render_cfg.next_point_size();
+        info!("Agent dot size: {} px", render_cfg.point_size);
+        notice.flash(format!("Agent dots: {} px", render_cfg.point_size));
+    }
+    if keyboard_input.just_pressed(keyboard::KeyCode::KeyW) {
+        render_cfg.additive = !render_cfg.additive;
+        let mode = if render_cfg.additive { "additive" } else { "overwrite" };
+        info!("Agent dots: {mode}");
+        notice.flash(format!("Agent dots: {mode}"));
+    }
+}
+
+// J toggles the sensor markers (where a few agents sample) over the composite
+fn handle_sensor_markers_hotkey(
+    keyboard_input: Res<ButtonInput<keyboard::KeyCode>>,
+    mut run_config: ResMut<AgentSimRunConfig>,
+) {
+    if !keyboard_input.just_pressed(keyboard::KeyCode::KeyJ) { return; }
+    run_config.draw_sensors = !run_config.draw_sensors;
+    info!("Sensor markers: {}", run_config.draw_sensors);
+}
+
+fn handle_agent_view_hotkey(
+    keyboard_input: Res<ButtonInput<keyboard::KeyCode>>,
+    mut view: ResMut<AgentView>,
+    mut notice: ResMut<OverlayNotice>,
+) {
+    if !keyboard_input.just_pressed(keyboard::KeyCode::KeyY) { return; }
+    view.enabled = !view.enabled;
+    let mode = if view.enabled { "agents" } else { "pheromone field" };
+    info!("View: {mode}");
+    notice.flash(format!("View: {mode}"));
+}
+
+// I swaps the display sampler. Samplers are fixed per GPU image, so the sRGB
+// presentation target is reallocated with the new one (`switch_textures`
+// points the sprite at it next frame); it is rewritten every frame anyway.
+fn handle_display_filter_hotkey(
+    keyboard_input: Res<ButtonInput<keyboard::KeyCode>>,
+    mut filter: ResMut<DisplayFilter>,
+    mut display_images: ResMut<PheromoneImages>,
+    mut images: ResMut<Assets<Image>>,
+    sim_size: Res<SimulationSize>,
+    srgb_display: Res<SrgbDisplay>,
+    mut notice: ResMut<OverlayNotice>,
+) {
+    if !keyboard_input.just_pressed(keyboard::KeyCode::KeyI) { return; }
+    *filter = filter.toggle();
+    display_images.display_srgb = images.add(srgb_display_image(sim_size.0, *filter));
+    info!("Display filter: {:?}", *filter);
+    if srgb_display.0 {
+        notice.flash(format!("Display filter: {:?}", *filter));
+    } else {
+        notice.flash(format!("Display filter: {:?} (applies to the sRGB display only)", *filter));
+    }
+}
+
+// F1 / F2 / F3 pause or resume the input, diffuse and agent stages
+fn handle_stage_hotkeys(
+    keyboard_input: Res<ButtonInput<keyboard::KeyCode>>,
+    mut run_config: ResMut<AgentSimRunConfig>,
+) {
+    use keyboard::KeyCode::{F1, F2, F3};
+    if !keyboard_input.any_just_pressed([F1, F2, F3]) { return; }
+    // Only borrow mutably on a press so the overlay's change check stays quiet
+    let run = &mut *run_config;
+    let stages = [
+        (F1, "Input", &mut run.run_copy_and_input),
+        (F2, "Diffuse", &mut run.run_diffuse),
+        (F3, "Agents", &mut run.run_agents),
+    ];
+    for (key, name, flag) in stages {
+        if keyboard_input.just_pressed(key) {
+            *flag = !*flag;
+            info!("{name} stage: {}", if *flag { "on" } else { "off" });
         }
     }
-    if let Some(mut v) = set {
-        let max_layer = cfg.layer_count.saturating_sub(1);
-        if v > max_layer { v = max_layer; }
-        cfg.brush_target_layer = v;
+}
+
+// Comma / Period remove / add a diffusion pass per frame
+fn handle_diffuse_iteration_hotkeys(
+    keyboard_input: Res<ButtonInput<keyboard::KeyCode>>,
+    mut run_config: ResMut<AgentSimRunConfig>,
+) {
+    let steps = run_config.diffuse_steps();
+    let new_steps = if keyboard_input.just_pressed(keyboard::KeyCode::Period) {
+        (steps + 1).min(MAX_DIFFUSE_ITERATIONS)
+    } else if keyboard_input.just_pressed(keyboard::KeyCode::Comma) {
+        steps.saturating_sub(1).max(1)
+    } else {
+        return;
+    };
+    if new_steps != steps {
+        run_config.diffuse_iterations = new_steps;
+        info!("Diffusion iterations per frame: {new_steps}");
+    }
+}
+
+// Z / X slow down / speed up the simulation through `TIME_SCALE_STEPS`; the
+// slowest step (0) freezes it while the passes keep running
+fn handle_time_scale_hotkeys(
+    keyboard_input: Res<ButtonInput<keyboard::KeyCode>>,
+    mut run_config: ResMut<AgentSimRunConfig>,
+) {
+    let faster = if keyboard_input.just_pressed(keyboard::KeyCode::KeyX) {
+        true
+    } else if keyboard_input.just_pressed(keyboard::KeyCode::KeyZ) {
+        false
+    } else {
+        return;
+    };
+    let scale = step_time_scale(run_config.time_scale, faster);
+    if scale != run_config.time_scale {
+        run_config.time_scale = scale;
+        info!("Time scale: x{scale}");
+    }
+}
+
+// L steps through `palette_names()`, recoloring every layer
+fn handle_palette_hotkey(
+    keyboard_input: Res<ButtonInput<keyboard::KeyCode>>,
+    mut layers: ResMut<PheromoneLayerParamsCpu>,
+    mut palette: Local<usize>,
+) {
+    if !keyboard_input.just_pressed(keyboard::KeyCode::KeyL) { return; }
+    *palette = (*palette + 1) % palette_names().count();
+    let Some(name) = palette_names().nth(*palette) else { return; };
+    apply_palette(&mut layers.params, name);
+    info!("Layer palette: {name}");
+}
+
+// G switches the brush layer between manual selection and the active
+// species' emit layer
+fn handle_brush_target_hotkey(
+    keyboard_input: Res<ButtonInput<keyboard::KeyCode>>,
+    mut target: ResMut<BrushTarget>,
+) {
+    if !keyboard_input.just_pressed(keyboard::KeyCode::KeyG) { return; }
+    *target = match *target {
+        BrushTarget::Manual => BrushTarget::FollowSpecies,
+        BrushTarget::FollowSpecies => BrushTarget::Manual,
+    };
+    info!("Brush target: {:?}", *target);
+}
+
+// K cycles the pheromone brush's edge profile
+fn handle_brush_falloff_hotkey(
+    keyboard_input: Res<ButtonInput<keyboard::KeyCode>>,
+    mut falloff: ResMut<BrushFalloff>,
+) {
+    if keyboard_input.just_pressed(keyboard::KeyCode::KeyK) {
+        *falloff = falloff.next();
+        info!("Brush falloff: {:?}", *falloff);
+    }
+}
+
+// Brush layer in follow mode: the species' emit layer, if it has one in range
+fn followed_layer(emit: Option<&EmitsPheromone>, layer_count: u32) -> Option<u32> {
+    emit.map(|e| e.channel).filter(|&layer| layer < layer_count)
+}
+
+// In follow mode, keep the brush layer on the active species' emit layer
+// (overriding manual picks). Species without one leave the layer as is.
+fn follow_active_species_layer(
+    target: Res<BrushTarget>,
+    active: Res<ActiveSpecies>,
+    mut cfg: ResMut<PheromoneConfig>,
+    species: Query<Option<&EmitsPheromone>, With<AgentSpecies>>,
+) {
+    if *target != BrushTarget::FollowSpecies { return; }
+    let emit = species.iter().nth(active.index).flatten();
+    if let Some(layer) = followed_layer(emit, cfg.layer_count)
+        && cfg.brush_target_layer != layer
+    {
+        cfg.brush_target_layer = layer;
+    }
+}
+
+// Weight change per `=` / `-` press
+const WEIGHT_EDIT_STEP: f32 = 0.1;
+
+// Tab selects the species to tune; = / - nudge its weight for the brush layer
+// and re-upload the species buffers. Species are indexed in the same query
+// order `upload_species_to_gpu` packs them in.
+fn handle_species_weight_hotkeys(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<keyboard::KeyCode>>,
+    cfg: Res<PheromoneConfig>,
+    mut active: ResMut<ActiveSpecies>,
+    mut species: Query<&mut LayerWeights, With<AgentSpecies>>,
+) {
+    let count = species.iter().count();
+    if count == 0 { return; }
+    if keyboard_input.just_pressed(keyboard::KeyCode::Tab) {
+        active.index = (active.index + 1) % count;
+        info!("Active species: {}", active.index);
+    }
+    let delta = if keyboard_input.just_pressed(keyboard::KeyCode::Equal) {
+        WEIGHT_EDIT_STEP
+    } else if keyboard_input.just_pressed(keyboard::KeyCode::Minus) {
+        -WEIGHT_EDIT_STEP
+    } else {
+        return;
+    };
+    let layer = cfg.brush_target_layer;
+    if universal_layer_weight(layer, &cfg).is_some() {
+        info!("Layer {layer} is a universal love/hate layer; its weight is fixed");
+        return;
+    }
+    let index = active.index.min(count - 1);
+    let Some(mut weights) = species.iter_mut().nth(index) else { return; };
+    let w = nudge_layer_weight(&mut weights.0, layer, delta);
+    info!("Species {index}: layer {layer} weight {w:.2}");
+    commands.run_system_cached(upload_species_to_gpu);
+}
+
+// U pauses / resumes the active species: its agents stop moving and
+// depositing while the rest of the field keeps running.
+fn handle_species_enable_hotkey(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<keyboard::KeyCode>>,
+    active: Res<ActiveSpecies>,
+    mut notice: ResMut<OverlayNotice>,
+    mut species: Query<Option<&mut SpeciesEnabled>, With<AgentSpecies>>,
+) {
+    if !keyboard_input.just_pressed(keyboard::KeyCode::KeyU) {
+        return;
+    }
+    let index = active.index;
+    let Some(enabled) = toggle_species_enabled(&mut species, index) else { return; };
+    let state = if enabled { "enabled" } else { "disabled" };
+    info!("Species {index}: {state}");
+    notice.flash(format!("Species {index} {state}"));
+    commands.run_system_cached(upload_species_to_gpu);
+}
+
+// Keep `ActiveSpecies.weight` in sync with the selection, brush layer and edits
+fn track_active_species_weight(
+    cfg: Res<PheromoneConfig>,
+    mut active: ResMut<ActiveSpecies>,
+    species: Query<&LayerWeights, With<AgentSpecies>>,
+) {
+    let index = active.index;
+    let weight = species
+        .iter()
+        .nth(index)
+        .map(|w| effective_layer_weight(&w.0, cfg.brush_target_layer, &cfg));
+    // Species can disappear on config load; fall back to the first one
+    if weight.is_none() && index != 0 {
+        active.index = 0;
+        return;
+    }
+    active.set_if_neq(ActiveSpecies { index, weight });
+}
+
+// Agents added per frame while the spawn brush is held, and their scatter radius
+// (texels; matches the pheromone brush in `pheromones.wgsl`)
+const SPAWN_BRUSH_RATE: u32 = 200;
+const SPAWN_BRUSH_RADIUS: f32 = 80.0;
+
+// Hold left click in spawn mode to append agents at the cursor. The species is
+// the brush layer mapped onto the species list; `sync_agents_to_gpu` grows the
+// agent buffer as needed, up to what the device allows.
+fn spawn_agents_with_brush(
+    buttons: Res<ButtonInput<MouseButton>>,
+    brush_mode: Res<BrushMode>,
+    cfg: Res<PheromoneConfig>,
+    globals: Res<GlobalUniforms>,
+    render_device: Res<RenderDevice>,
+    keyboard_input: Res<ButtonInput<keyboard::KeyCode>>,
+    mut agents: ResMut<AgentsCpu>,
+) {
+    if *brush_mode != BrushMode::SpawnAgents
+        || !buttons.pressed(MouseButton::Left)
+        || keyboard_input.any_pressed(SHIFT_KEYS)
+    {
+        return;
+    }
+    let limit = max_agent_capacity(&render_device);
+    if agents.list.len() >= limit as usize {
+        return;
+    }
+    let species = brush_species_index(cfg.brush_target_layer, globals.species_count, globals.species_offset);
+    let max = (globals.screen_size - Vec2::ONE).max(Vec2::ZERO);
+    let mut new = brush_agents(globals.mouse_position, SPAWN_BRUSH_RADIUS, SPAWN_BRUSH_RATE, species);
+    for agent in &mut new {
+        agent.position = agent.position.clamp(Vec2::ZERO, max);
+    }
+    if append_agents(&mut agents, &new, limit) < new.len() {
+        info!("Spawn brush: agent limit reached ({limit} agents)");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digit_layer_shift_reaches_teens_and_clamps() {
+        assert_eq!(digit_layer(3, false, 24), 3);
+        assert_eq!(digit_layer(3, true, 24), 13);
+        assert_eq!(digit_layer(9, true, 24), DIGIT_LAYER_LIMIT - 1);
+        // Past the layer count: the top layer
+        assert_eq!(digit_layer(7, false, 5), 4);
+        assert_eq!(digit_layer(0, true, 12), 10);
+        assert_eq!(digit_layer(5, true, 12), 11);
+        assert_eq!(digit_layer(4, true, 0), 0);
+    }
+
+    #[test]
+    fn cursor_to_texel_scales_display_to_sim() {
+        let display = Vec2::new(1920.0, 1080.0);
+        // Same size: the old direct mapping (y flipped)
+        assert_eq!(cursor_to_texel(Vec2::ZERO, display, display), Vec2::new(960.0, 540.0));
+        // Half-resolution sim: the display centre and corners land proportionally
+        let sim = display / 2.0;
+        assert_eq!(cursor_to_texel(Vec2::ZERO, display, sim), Vec2::new(480.0, 270.0));
+        let top_right = cursor_to_texel(Vec2::new(480.0, 270.0), display, sim);
+        assert_eq!(top_right, Vec2::new(720.0, 135.0));
+        // Off-screen cursors are clamped into the texture
+        let far = cursor_to_texel(Vec2::splat(-1.0e5), display, sim);
+        assert_eq!(far, Vec2::new(0.0, sim.y - 1.0));
+    }
+
+    #[test]
+    fn cursor_to_texel_follows_a_windowed_display() {
+        // An 800x600 window over the default-sized sim texture
+        let display = Vec2::new(800.0, 600.0);
+        let sim = Vec2::new(1920.0, 1080.0);
+        assert_eq!(cursor_to_texel(Vec2::ZERO, display, sim), sim / 2.0);
+        // Top-left corner of the window is texel row 0
+        let top_left = cursor_to_texel(Vec2::new(-400.0, 300.0), display, sim);
+        assert_eq!(top_left, Vec2::ZERO);
+        // A quarter down from the top maps a quarter down the texture
+        let quarter = cursor_to_texel(Vec2::new(0.0, 150.0), display, sim);
+        assert_eq!(quarter, Vec2::new(960.0, 270.0));
+    }
+
+    #[test]
+    fn followed_layer_needs_an_emit_layer_in_range() {
+        let emit = |channel| EmitsPheromone {
+            channel,
+            amount: 1.0,
+            mode: Default::default(),
+            decay_bias: 1.0,
+        };
+        assert_eq!(followed_layer(Some(&emit(3)), 5), Some(3));
+        assert_eq!(followed_layer(Some(&emit(5)), 5), None);
+        assert_eq!(followed_layer(None, 5), None);
+    }
+
+    #[test]
+    fn nudge_flow_adds_velocities_and_snaps_to_zero() {
+        let (dir, strength) = nudge_flow(Vec2::ZERO, 0.0, Vec2::new(FLOW_STEP, 0.0));
+        assert_eq!((dir, strength), (Vec2::X, FLOW_STEP));
+        let (dir, strength) = nudge_flow(dir, strength, Vec2::new(0.0, FLOW_STEP));
+        assert!((dir - Vec2::ONE.normalize()).length() < 1e-6);
+        assert!((strength - FLOW_STEP * 2f32.sqrt()).abs() < 1e-4);
+        // pushing back the other way returns to exactly no flow
+        assert_eq!(nudge_flow(Vec2::X, FLOW_STEP, Vec2::new(-FLOW_STEP, 0.0)), (Vec2::ZERO, 0.0));
+        assert_eq!(nudge_flow(Vec2::X, MAX_FLOW, Vec2::new(FLOW_STEP, 0.0)).1, MAX_FLOW);
+    }
+
+    #[test]
+    fn brush_amount_steps_within_range() {
+        assert_eq!(step_brush_amount(1.0, true), BRUSH_AMOUNT_STEP);
+        assert_eq!(step_brush_amount(BRUSH_AMOUNT_STEP, false), 1.0);
+        assert_eq!(step_brush_amount(MAX_BRUSH_AMOUNT, true), MAX_BRUSH_AMOUNT);
+        assert_eq!(step_brush_amount(MIN_BRUSH_AMOUNT, false), MIN_BRUSH_AMOUNT);
+        // A zeroed or broken amount restarts from 1
+        assert_eq!(step_brush_amount(0.0, true), BRUSH_AMOUNT_STEP);
+        assert_eq!(step_brush_amount(f32::NAN, false), 1.0 / BRUSH_AMOUNT_STEP);
+    }
+
+    #[test]
+    fn keybindings_are_unique_and_rendered() {
+        let mut keys: Vec<&str> = KEYBINDINGS.iter().map(|(k, _)| *k).collect();
+        keys.sort();
+        keys.dedup();
+        assert_eq!(keys.len(), KEYBINDINGS.len());
+        let text = help_text();
+        assert_eq!(text.lines().count(), KEYBINDINGS.len());
+        assert!(text.lines().all(|l| KEYBINDINGS.iter().any(|(k, a)| l.starts_with(k) && l.ends_with(a))));
     }
 }
@@ -0,0 +1,175 @@
+// Runtime reordering of pheromone layers.
+//
+// `PageUp` / `PageDown` move the brush layer one slot up / down the stack by
+// swapping it with its neighbor. Everything indexed by layer is permuted
+// together: the layer params (rates, colors, flags), each species' sensing
+// weights and emit channels (re-uploaded through `upload_species_to_gpu`,
+// which rebuilds the dense weight buffer and decay bias), the universal
+// love/hate lists, the beacon and emitter layers, and the brush layer itself,
+// which moves with its contents. The texture layers are exchanged on the GPU by
+// the render node via a one-shot `LayerSwapRequest`
+// (`pheromones::swap_array_layers`).
+
+use bevy::prelude::*;
+
+use crate::resources::{BRUSH_LAYER, BeaconSettings, Emitters, LayerSwapRequest, PheromoneConfig};
+use crate::setup::{OverlayNotice, PheromoneLayerParamsCpu, update_layer_params_buffer};
+use crate::species::{AgentSpecies, EmitsPheromone, ExtraEmits, LayerWeights, upload_species_to_gpu};
+
+pub struct LayerOrderPlugin;
+impl Plugin for LayerOrderPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LayerSwapRequest>()
+            // Same frame as the GPU swap, so colors and rates never lag a frame
+            .add_systems(Update, handle_layer_move_hotkeys.before(update_layer_params_buffer))
+            .add_systems(First, reset_layer_swap_request);
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn handle_layer_move_hotkeys(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut cfg: ResMut<PheromoneConfig>,
+    mut layers: ResMut<PheromoneLayerParamsCpu>,
+    mut beacon: ResMut<BeaconSettings>,
+    mut emitters: ResMut<Emitters>,
+    mut swap: ResMut<LayerSwapRequest>,
+    mut notice: ResMut<OverlayNotice>,
+    mut species: Query<
+        (Option<&mut LayerWeights>, Option<&mut EmitsPheromone>, Option<&mut ExtraEmits>),
+        With<AgentSpecies>,
+    >,
+) {
+    let from = cfg.brush_target_layer;
+    let to = if keyboard_input.just_pressed(KeyCode::PageUp) {
+        from.checked_add(1)
+    } else if keyboard_input.just_pressed(KeyCode::PageDown) {
+        from.checked_sub(1)
+    } else {
+        return;
+    };
+    let layer_count = cfg.layer_count.min(layers.params.len() as u32);
+    let Some(to) = to.filter(|&to| to < layer_count && from < layer_count) else {
+        return;
+    };
+    // The GPU swaps one pair per frame
+    if swap.pending.is_some() {
+        return;
+    }
+
+    layers.params.swap(from as usize, to as usize);
+    swap_config_layers(&mut cfg, from, to);
+    beacon.layer = swapped_layer(beacon.layer, from, to);
+    for emitter in emitters.0.iter_mut() {
+        emitter.layer = swapped_layer(emitter.layer, from, to);
+    }
+    for (weights, emit, extra) in species.iter_mut() {
+        if let Some(mut weights) = weights {
+            swap_weight_columns(&mut weights.0, from, to);
+        }
+        if let Some(mut emit) = emit {
+            emit.channel = swapped_layer(emit.channel, from, to);
+        }
+        if let Some(mut extra) = extra {
+            for e in extra.iter_mut() {
+                e.channel = swapped_layer(e.channel, from, to);
+            }
+        }
+    }
+    commands.run_system_cached(upload_species_to_gpu);
+    swap.pending = Some((from, to));
+    info!("Layers: moved {from} -> {to}");
+    notice.flash(format!("Layer {from} moved to {to}"));
+}
+
+// The request was extracted last frame, so the swap has been dispatched
+fn reset_layer_swap_request(mut swap: ResMut<LayerSwapRequest>) {
+    if swap.pending.is_some() {
+        swap.pending = None;
+    }
+}
+
+/// Where `layer` ends up after layers `a` and `b` trade places.
+pub fn swapped_layer(layer: u32, a: u32, b: u32) -> u32 {
+    if layer == a {
+        b
+    } else if layer == b {
+        a
+    } else {
+        layer
+    }
+}
+
+/// Swap two columns of one species' sparse weights. Missing entries count as
+/// 0, so the list grows when only one of the two layers was authored.
+pub fn swap_weight_columns(weights: &mut Vec<f32>, a: u32, b: u32) {
+    let (lo, hi) = (a.min(b) as usize, a.max(b) as usize);
+    if weights.len() <= lo {
+        return;
+    }
+    if weights.len() <= hi {
+        weights.resize(hi + 1, 0.0);
+    }
+    weights.swap(lo, hi);
+}
+
+/// Apply a swap of layers `a` and `b` to the brush layer, the mouse button
+/// bindings, the universal love/hate lists and the shared layer.
+pub fn swap_config_layers(cfg: &mut PheromoneConfig, a: u32, b: u32) {
+    cfg.brush_target_layer = swapped_layer(cfg.brush_target_layer, a, b);
+    for binding in cfg.brush_bindings.iter_mut().filter(|b| **b != BRUSH_LAYER) {
+        *binding = swapped_layer(*binding, a, b);
+    }
+    for layer in cfg.universal_love_layers.iter_mut().chain(cfg.universal_hate_layers.iter_mut()) {
+        *layer = swapped_layer(*layer, a, b);
+    }
+    cfg.shared_layer = cfg.shared_layer.map(|layer| swapped_layer(layer, a, b));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swapped_layer_exchanges_only_the_pair() {
+        assert_eq!(swapped_layer(2, 2, 3), 3);
+        assert_eq!(swapped_layer(3, 2, 3), 2);
+        assert_eq!(swapped_layer(0, 2, 3), 0);
+        assert_eq!(swapped_layer(2, 2, 2), 2);
+    }
+
+    #[test]
+    fn weight_columns_swap_and_pad() {
+        let mut w = vec![-1.0, 1.0, 0.5];
+        swap_weight_columns(&mut w, 1, 2);
+        assert_eq!(w, vec![-1.0, 0.5, 1.0]);
+        // Only the lower layer was authored: the upper one was implicitly 0
+        swap_weight_columns(&mut w, 4, 2);
+        assert_eq!(w, vec![-1.0, 0.5, 0.0, 0.0, 1.0]);
+        // Neither authored: unchanged
+        let mut short = vec![0.25];
+        swap_weight_columns(&mut short, 2, 3);
+        assert_eq!(short, vec![0.25]);
+    }
+
+    #[test]
+    fn config_layers_follow_a_swap() {
+        let mut cfg = PheromoneConfig {
+            layer_count: 5,
+            brush_target_layer: 0,
+            universal_love_layers: vec![1],
+            universal_hate_layers: vec![0],
+            brush_bindings: [BRUSH_LAYER, 0, 3],
+            shared_layer: Some(1),
+            ..Default::default()
+        };
+        swap_config_layers(&mut cfg, 0, 1);
+        assert_eq!(cfg.brush_target_layer, 1);
+        assert_eq!(cfg.brush_bindings, [BRUSH_LAYER, 1, 3]);
+        assert_eq!(cfg.universal_love_layers, vec![0]);
+        assert_eq!(cfg.universal_hate_layers, vec![1]);
+        assert_eq!(cfg.shared_layer, Some(0));
+        assert_eq!(cfg.paint_only_mask(), 0b11);
+    }
+}
@@ -0,0 +1,184 @@
+// Library entry: the whole simulation as one plugin, `SlimeSimPlugin`, so it
+// can run inside a larger Bevy app as well as from the standalone binary in
+// `main.rs`. The plugin registers every sub-plugin and the startup/update
+// systems; the host supplies `DefaultPlugins` (with or without a window) and
+// may insert any tuning resource beforehand to override its default.
+
+use bevy::prelude::*;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+// Display size in logical pixels (the window's until it reports its own)
+pub const SIZE: UVec2 = UVec2::new(1920, 1080);
+// The simulation runs at 1/DISPLAY_FACTOR of the display size per axis and the
+// sprite's `Transform` scales it back up to fill the display. A fixed
+// `SimResolution` overrides the divided size.
+pub const DISPLAY_FACTOR: u32 = 1;
+// Workgroup edge for the 2D pheromone passes; passed to the WGSL as a shader
+// def (see `render::workgroup_shader_defs`), so this is the only place to tune
+pub const WORKGROUP_SIZE: u32 = 16;
+
+// Agent simulation (workgroup size is also a shader def)
+pub const AGENT_WORKGROUP_SIZE: u32 = 256;
+// Startup agent count; see `agents::AgentPopulation` for runtime changes
+pub const NUM_AGENTS: u32 = 100000;
+// Spare agent buffer slots for the spawn brush before the buffer has to grow
+pub const AGENT_SPAWN_HEADROOM: u32 = 50000;
+
+// Shader asset paths
+pub const AGENTS_SHADER_PATH: &str = "shaders/agents.wgsl";
+pub const PHERO_SHADER_PATH: &str = "shaders/pheromones.wgsl";
+// Species definitions (read from disk at startup, relative to the working dir)
+pub const SPECIES_FILE_PATH: &str = "assets/species.ron";
+// Saved simulation tuning (F5 save / F9 load)
+pub const CONFIG_FILE_PATH: &str = "slime_config.ron";
+// Optional black/white obstacle map (bright = wall), resampled to the sim size
+pub const OBSTACLE_MAP_PATH: &str = "assets/obstacles.png";
+
+mod agents;
+mod auto_quality;
+mod bench;
+mod capture;
+mod color;
+mod config_file;
+mod density_spawn;
+mod emitters;
+mod input;
+mod layer_order;
+mod obstacles;
+mod pheromones;
+mod probe;
+#[cfg(feature = "gpu-profiling")]
+mod profiling;
+mod render;
+mod resources;
+mod setup;
+mod species;
+mod stats;
+
+use auto_quality::AutoQualityPlugin;
+use capture::CapturePlugin;
+use config_file::ConfigFilePlugin;
+use density_spawn::DensitySpawnPlugin;
+use emitters::EmittersPlugin;
+use input::InputPlugin;
+use layer_order::LayerOrderPlugin;
+use probe::ProbePlugin;
+use stats::StatsPlugin;
+
+// Tuning a host may insert before adding the plugin, and simulation state it
+// can read back while it runs
+pub use agents::{Agent, AgentPopulation, AgentSpeciesMix, AgentsCpu, SimSeed, SpawnPattern};
+pub use bench::{BenchPlugin, parse_bench_frames};
+pub use probe::PheromoneProbe;
+pub use render::AgentSimComputePlugin;
+pub use resources::{
+    AgentRenderConfig, AgentSimRunConfig, BRUSH_LAYER, Background, BoundaryMode, DisplayFilter, GlobalUniforms,
+    InitialField, MaxDeltaTime, PheromoneConfig, SensorSampling, SimRegions, SimResolution, SimTimestep,
+    SimulationSize, SrgbDisplay,
+};
+pub use setup::PheromoneLayerParamsCpu;
+pub use species::{SpeciesBuilder, SpeciesBundle};
+pub use stats::PheromoneStats;
+
+/// What `SlimeSimPlugin` sets up; everything else keeps its default or the
+/// value the host inserted.
+#[derive(Clone, Copy, Debug)]
+pub struct SlimeSimConfig {
+    /// Pheromone layers (`PheromoneConfig::layer_count`).
+    pub layer_count: u32,
+    /// Startup agent count (`AgentPopulation`).
+    pub agent_count: u32,
+    /// Simulation size in texels; `None` = window resolution / `DISPLAY_FACTOR`
+    /// (`SIZE` without a window).
+    pub size: Option<UVec2>,
+}
+
+impl Default for SlimeSimConfig {
+    fn default() -> Self {
+        Self { layer_count: 5, agent_count: NUM_AGENTS, size: None }
+    }
+}
+
+#[derive(Default)]
+pub struct SlimeSimPlugin {
+    pub config: SlimeSimConfig,
+}
+
+impl Plugin for SlimeSimPlugin {
+    fn build(&self, app: &mut App) {
+        let config = self.config;
+        // A host-inserted `PheromoneConfig` keeps its other settings
+        let mut phero_cfg = app.world_mut().remove_resource::<PheromoneConfig>().unwrap_or_default();
+        phero_cfg.layer_count = config.layer_count;
+        app.insert_resource(phero_cfg)
+            .insert_resource(AgentPopulation(config.agent_count))
+            .insert_resource(SimResolution(config.size))
+            // `init_resource` keeps whatever the host inserted first
+            .init_resource::<Background>()
+            .init_resource::<AgentSpeciesMix>()
+            .init_resource::<SpawnPattern>()
+            .init_resource::<SimSeed>()
+            .init_resource::<BoundaryMode>()
+            .init_resource::<SimRegions>()
+            .init_resource::<SensorSampling>()
+            .init_resource::<MaxDeltaTime>()
+            .init_resource::<SimTimestep>()
+            .init_resource::<SrgbDisplay>()
+            .init_resource::<DisplayFilter>()
+            .init_resource::<InitialField>();
+        #[cfg(feature = "gpu-profiling")]
+        app.add_plugins(profiling::GpuProfilingPlugin);
+        app.add_plugins((
+            AgentSimComputePlugin,
+            InputPlugin,
+            CapturePlugin,
+            ConfigFilePlugin,
+            ProbePlugin,
+            StatsPlugin,
+            DensitySpawnPlugin,
+            LayerOrderPlugin,
+            AutoQualityPlugin,
+            EmittersPlugin,
+        ))
+        // Startup systems: spawn species (from `SPECIES_FILE_PATH`, falling back
+        // to the built-in trio), upload species buffer, and create
+        // textures/agents. The chain ensures species are created before we
+        // attempt to upload them to the GPU.
+        .add_systems(
+            Startup,
+            (
+                setup::validate_pheromone_config,
+                species::spawn_species_from_file,
+                species::upload_species_to_gpu,
+                setup::setup,
+                agents::init_species_rotation_timer,
+            )
+                .chain(),
+        )
+        // Update systems: alternate display textures, push CPU agent changes
+        // to the GPU, and refresh global uniforms (mouse/frames/time).
+        .add_systems(
+            Update,
+            (
+                setup::handle_window_resize,
+                setup::switch_textures,
+                agents::rotate_agent_species,
+                (
+                    agents::rebuild_agents_on_population_change,
+                    agents::sync_agents_to_gpu,
+                )
+                    .chain(),
+                setup::update_globals_uniform,
+                setup::update_brush_layer_text,
+                setup::update_fps_counter,
+                setup::update_layer_params_buffer,
+                setup::sync_clear_color,
+                species::reload_species_hotkey,
+            ),
+        )
+        .add_systems(First, agents::reset_agent_buffer_grow_request);
+    }
+}
@@ -1,94 +1,87 @@
-// Application entry: assemble Bevy app, register plugins, and wire startup
-// systems. The order here matters: we ensure species and buffers are created
-// during `Startup` before the render sub-app extracts resources.
+// Standalone binary: a borderless fullscreen window (or the headless
+// `--bench` runner) around `SlimeSimPlugin`. The tuning below is this app's
+// choice; an embedding app inserts its own before adding the plugin.
 
+use bevy::app::ScheduleRunnerPlugin;
 use bevy::prelude::*;
-use bevy::window::{Window, WindowMode, WindowPlugin};
-
-// ============================================================================
-// CONSTANTS
-// ============================================================================
-
-// Display and simulation dimensions
-pub const DISPLAY_FACTOR: u32 = 1;
-pub const SIZE: UVec2 = UVec2::new(1920 / DISPLAY_FACTOR, 1080 / DISPLAY_FACTOR);
-pub const WORKGROUP_SIZE: u32 = 16;
-
-// Agent simulation
-pub const AGENT_WORKGROUP_SIZE: u32 = 256;
-pub const NUM_AGENTS: u32 = 100000;
-// Number of authored species/archetypes
-pub const NUM_SPECIES: u32 = 3;
-
-// Shader asset paths
-pub const AGENTS_SHADER_PATH: &str = "shaders/agents.wgsl";
-pub const PHERO_SHADER_PATH: &str = "shaders/pheromones.wgsl";
-
-mod agents;
-mod input;
-mod pheromones;
-mod render;
-mod resources;
-mod setup;
-mod species;
-
-use input::InputPlugin;
-use render::AgentSimComputePlugin;
-use resources::PheromoneConfig;
+use bevy::window::{ExitCondition, Window, WindowMode, WindowPlugin};
+use bevy_slime::{
+    AgentSpeciesMix, BRUSH_LAYER, Background, BenchPlugin, BoundaryMode, DisplayFilter, InitialField, MaxDeltaTime,
+    PheromoneConfig, SensorSampling, SimRegions, SimSeed, SimTimestep, SlimeSimConfig, SlimeSimPlugin, SpawnPattern,
+    SrgbDisplay, parse_bench_frames,
+};
+use std::time::Duration;
 
 fn main() {
-    App::new()
-        .insert_resource(ClearColor(Color::BLACK))
-        // Insert runtime pheromone config with safe defaults (RGB, legacy behavior)
-        // Configure 5 pheromone layers by default with universal hate/love paint-only channels
+    // `--bench <frames>` runs headless for a fixed frame count (see `bench`)
+    let bench_frames = parse_bench_frames(std::env::args());
+
+    let mut app = App::new();
+    app.insert_resource(ClearColor(Color::BLACK))
+        // Composite background; `transparent: true` exports empty pixels with alpha 0
+        .insert_resource(Background::default())
+        // Universal hate/love paint-only channels; the layer count comes from `SlimeSimConfig`
         .insert_resource(PheromoneConfig {
-            layer_count: 5,
             brush_target_layer: 1, // default to painting "love"
             universal_love_layers: vec![1],
             universal_hate_layers: vec![0],
+            // Diffusion wraps with wrapping agents (B) regardless; true also wraps it for bouncing ones
+            wrap_diffusion: false,
+            // Layer per [left, right, middle] button; e.g. `[BRUSH_LAYER, 0, 1]` paints hate/love directly
+            brush_bindings: [BRUSH_LAYER; 3],
+            ..Default::default()
         })
-        .add_plugins((
-            DefaultPlugins
+        // Relative agent count per species; empty splits evenly
+        .insert_resource(AgentSpeciesMix(vec![1.0, 1.0, 1.0]))
+        .insert_resource(SpawnPattern::DiscInward)
+        // Random per launch; use e.g. `SimSeed(1234)` to reproduce a logged run
+        .insert_resource(SimSeed::default())
+        .insert_resource(BoundaryMode::Bounce)
+        // Empty = one field; e.g. `SimRegions::quadrants(SIZE)` for four side-by-side runs
+        .insert_resource(SimRegions::default())
+        // Bilinear sensor taps; `SensorSampling { bilinear: false }` snaps to texels
+        .insert_resource(SensorSampling::default())
+        // Longest simulated step (s); longer frames run as this much time
+        .insert_resource(MaxDeltaTime::default())
+        // Real frame time drives the sim; `SimTimestep::Fixed(1.0 / 60.0)` makes runs reproducible
+        .insert_resource(SimTimestep::Variable)
+        // Show (and capture) an 8-bit sRGB copy of the display; `SrgbDisplay(false)` shows the float target
+        .insert_resource(SrgbDisplay::default())
+        // Upscaled display sampling; `DisplayFilter::Linear` smooths it (`I` toggles)
+        .insert_resource(DisplayFilter::Nearest)
+        // Pre-drawn starting field; e.g. `InitialField::Channels("assets/field.png".into())`
+        .insert_resource(InitialField::Empty);
+    // Nearest by default for every other image; the display sprite's sampler
+    // follows `DisplayFilter` instead
+    let default_plugins = DefaultPlugins.set(ImagePlugin::default_nearest());
+    if let Some(frames) = bench_frames {
+        // No window or event loop: the schedule runner drives frames back to
+        // back and the compute node still dispatches every frame
+        app.add_plugins((
+            default_plugins
                 .set(WindowPlugin {
-                    primary_window: Some(Window {
-                        title: "bevy-slime".into(),
-                        mode: WindowMode::BorderlessFullscreen(
-                            bevy::window::MonitorSelection::Primary,
-                        ),
-                        ..Default::default()
-                    }),
+                    primary_window: None,
+                    exit_condition: ExitCondition::DontExit,
                     ..Default::default()
                 })
-                .set(ImagePlugin::default_nearest()),
-            AgentSimComputePlugin,
-            InputPlugin,
+                .disable::<bevy::winit::WinitPlugin>()
+                // Only part of `DefaultPlugins` without `bevy_window`, so add
+                // it rather than `set` it
+                .add(ScheduleRunnerPlugin::run_loop(Duration::ZERO)),
+            BenchPlugin { frames },
         ))
-        // Startup systems: spawn species, upload species buffer, and create
-        // textures/agents. The chain ensures species are created before we
-        // attempt to upload them to the GPU.
-        .add_systems(
-            Startup,
-            (
-                species::spawn_default_species,
-                species::upload_species_to_gpu,
-                setup::setup,
-                agents::init_species_rotation_timer,
-            )
-                .chain(),
-        )
-        // Update systems: alternate display textures, push CPU agent changes
-        // to the GPU, and refresh global uniforms (mouse/frames/time).
-        .add_systems(
-            Update,
-            (
-                setup::switch_textures,
-                agents::rotate_agent_species,
-                agents::sync_agents_to_gpu,
-                setup::update_globals_uniform,
-                setup::update_brush_layer_text,
-                setup::update_fps_counter,
-                setup::update_layer_params_buffer,
-            ),
-        )
-        .run();
+        // Same simulated work per frame on any machine
+        .insert_resource(SimTimestep::Fixed(1.0 / 60.0));
+    } else {
+        app.add_plugins(default_plugins.set(WindowPlugin {
+            primary_window: Some(Window {
+                title: "bevy-slime".into(),
+                mode: WindowMode::BorderlessFullscreen(bevy::window::MonitorSelection::Primary),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }));
+    }
+    // Layers, agents and sim size; e.g. `size: Some(bevy_slime::SIZE / 2)` runs the sim at half size
+    app.add_plugins(SlimeSimPlugin { config: SlimeSimConfig::default() }).run();
 }
@@ -0,0 +1,125 @@
+// Obstacle mask: a single-layer `R32Float` texture bound into the agent
+// shader (group 0, binding 10). Texels with value 1.0 are walls that agents
+// cannot enter; sensors landing in a wall steer the agent away.
+//
+// The mask is loaded from `OBSTACLE_MAP_PATH` if present (bright pixels are
+// walls) and resampled to the simulation size. Without a file an empty mask
+// is bound so the shader layout stays fixed.
+
+use bevy::asset::RenderAssetUsages;
+use bevy::image::{CompressedImageFormats, ImageSampler, ImageType};
+use bevy::prelude::*;
+use bevy::render::extract_resource::ExtractResource;
+use bevy::render::render_resource::{TextureFormat, TextureUsages};
+
+// Luminance above this is treated as a wall
+const WALL_THRESHOLD: f32 = 0.5;
+
+#[derive(Resource, Clone, ExtractResource)]
+pub struct ObstacleMask {
+    pub image: Handle<Image>,
+}
+
+/// Build the obstacle mask image from a wall mask (1.0 = wall) sized `size`.
+pub fn create_obstacle_image(size: UVec2, walls: &[f32]) -> Image {
+    let mut img = Image::new_target_texture(size.x, size.y, TextureFormat::R32Float);
+    img.asset_usage = RenderAssetUsages::RENDER_WORLD;
+    img.texture_descriptor.usage =
+        TextureUsages::COPY_DST | TextureUsages::STORAGE_BINDING | TextureUsages::TEXTURE_BINDING;
+    img.data = Some(bytemuck::cast_slice(walls).to_vec());
+    img
+}
+
+/// Decode an image file into a wall mask resampled (nearest) to `size`.
+pub fn load_obstacle_walls(path: &str, size: UVec2) -> Result<Vec<f32>, String> {
+    let bytes = std::fs::read(path).map_err(|err| format!("{path}: {err}"))?;
+    let ext = std::path::Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("png");
+    let image = Image::from_buffer(
+        &bytes,
+        ImageType::Extension(ext),
+        CompressedImageFormats::NONE,
+        false,
+        ImageSampler::Default,
+        RenderAssetUsages::MAIN_WORLD,
+    )
+    .map_err(|err| format!("{path}: {err}"))?;
+    let luma = image
+        .try_into_dynamic()
+        .map_err(|err| format!("{path}: {err:?}"))?
+        .to_luma32f();
+    let (w, h) = luma.dimensions();
+    let walls: Vec<f32> = luma
+        .into_raw()
+        .into_iter()
+        .map(|v| if v > WALL_THRESHOLD { 1.0 } else { 0.0 })
+        .collect();
+    Ok(resample_nearest(&walls, UVec2::new(w, h), size))
+}
+
+/// Nearest-neighbor resample of a row-major scalar grid. Image rows are
+/// top-down while the simulation's y axis points up, so rows are flipped.
+pub fn resample_nearest(src: &[f32], src_size: UVec2, dst_size: UVec2) -> Vec<f32> {
+    let mut out = Vec::with_capacity((dst_size.x * dst_size.y) as usize);
+    if src_size.x == 0 || src_size.y == 0 {
+        out.resize((dst_size.x * dst_size.y) as usize, 0.0);
+        return out;
+    }
+    for y in 0..dst_size.y {
+        let sy = src_size.y - 1 - (y * src_size.y / dst_size.y);
+        for x in 0..dst_size.x {
+            let sx = x * src_size.x / dst_size.x;
+            out.push(src[(sy * src_size.x + sx) as usize]);
+        }
+    }
+    out
+}
+
+/// Load the obstacle mask (or an empty one) and insert `ObstacleMask`.
+pub fn init_obstacle_mask(commands: &mut Commands, images: &mut Assets<Image>, size: UVec2) {
+    let path = crate::OBSTACLE_MAP_PATH;
+    let walls = if std::path::Path::new(path).exists() {
+        match load_obstacle_walls(path, size) {
+            Ok(walls) => {
+                info!("Obstacles: loaded {path}");
+                walls
+            }
+            Err(err) => {
+                warn!("Obstacles: failed to load ({err}); running without walls");
+                vec![0.0; (size.x * size.y) as usize]
+            }
+        }
+    } else {
+        vec![0.0; (size.x * size.y) as usize]
+    };
+    let image = images.add(create_obstacle_image(size, &walls));
+    commands.insert_resource(ObstacleMask { image });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resample_nearest_scales_and_flips_rows() {
+        // 2x2 source: top row walls, bottom row free
+        let src = [1.0, 1.0, 0.0, 0.0];
+        let out = resample_nearest(&src, UVec2::new(2, 2), UVec2::new(4, 4));
+        assert_eq!(out.len(), 16);
+        // sim row 0 is the bottom of the image
+        assert!(out[..8].iter().all(|&v| v == 0.0));
+        assert!(out[8..].iter().all(|&v| v == 1.0));
+    }
+
+    #[test]
+    fn create_obstacle_image_descriptor() {
+        let size = UVec2::new(8, 4);
+        let img = create_obstacle_image(size, &vec![0.0; 32]);
+        assert_eq!(img.texture_descriptor.format, TextureFormat::R32Float);
+        assert_eq!(img.texture_descriptor.size.width, 8);
+        assert_eq!(img.texture_descriptor.size.height, 4);
+        assert_eq!(img.data.as_ref().map(|d| d.len()), Some(32 * 4));
+    }
+}
@@ -8,10 +8,21 @@
 //
 // Important notes:
 // - Allocation: `make_pheromone_array_images` creates a pair of array textures
-//   (prev/next) which are ping-ponged by the render node.
+//   (prev/next) which are ping-ponged by the render node;
+//   `make_pheromone_array_images_from` seeds them with a field read by
+//   `load_initial_field` (see `InitialField`).
 // - Pipelines: `init_pheromone_array_pipelines` registers compute pipelines for
 //   diffusion, input (brush), and composite steps. The WGSL shader implements
-//   `diffuse_phero_array`, `handle_input_phero_array`, and `composite_pheromones_array`.
+//   `diffuse_phero_array`, `handle_input_phero_array`, `stamp_emitters` (point
+//   sources from `Emitters`), and `composite_pheromones_array`.
+//   `init_pheromone_clear_pipeline` adds the on-demand `clear_phero_array` pass,
+//   and `init_pheromone_probe_pipeline` the cursor readout `probe_phero_array`.
+//   `init_display_fade_pipeline` adds `fade_display`, the agent view's
+//   stand-in for the composite (see `AgentView`).
+//   `init_display_encode_pipeline` adds `encode_display_srgb`, which copies the
+//   composited display into the 8-bit sRGB presentation target.
+// - `swap_array_layers` reorders layers in place with texture copies (used by
+//   `layer_order` when a layer is moved up or down the stack).
 // - Bind groups created by `create_phero_array_bind_groups` must match the
 //   layout expected by the WGSL entry points. Keep binding indices in sync.
 
@@ -23,7 +34,8 @@ use bevy::render::{
 };
 use std::borrow::Cow;
 
-use crate::{SIZE, PHERO_SHADER_PATH};
+use crate::PHERO_SHADER_PATH;
+use crate::render::workgroup_shader_defs;
 
 // Array-based pheromone images
 #[derive(Resource, Clone, ExtractResource)]
@@ -32,25 +44,149 @@ pub(crate) struct PheromoneArrayImages {
     pub next: Handle<Image>,
 }
 
+/// One side of the prev/next ping-pong pair.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PingArray {
+    Prev,
+    Next,
+}
+
+impl PingArray {
+    /// Array the env pass (diffuse/decay/brush) reads on ping `index`.
+    pub fn env_read(index: usize) -> Self {
+        if index % 2 == 0 { PingArray::Prev } else { PingArray::Next }
+    }
+
+    /// Array the env pass writes on ping `index`. Agents sense and deposit
+    /// into this one the same frame, and the composite/probe/stats read it,
+    /// so every per-ping binding must come from here.
+    pub fn env_write(index: usize) -> Self {
+        Self::env_read(index + 1)
+    }
+
+    pub fn view<'a>(self, prev: &'a TextureView, next: &'a TextureView) -> &'a TextureView {
+        match self {
+            PingArray::Prev => prev,
+            PingArray::Next => next,
+        }
+    }
+
+    pub fn image(self, arrays: &PheromoneArrayImages) -> &Handle<Image> {
+        match self {
+            PingArray::Prev => &arrays.prev,
+            PingArray::Next => &arrays.next,
+        }
+    }
+}
+
 /// Allocate array-based pheromone textures (prev/next), one layer per pheromone.
-pub fn make_pheromone_array_images(images: &mut Assets<Image>, layers: u32) -> PheromoneArrayImages {
-    let prev = images.add(create_pheromone_array_image(layers));
-    let next = images.add(create_pheromone_array_image(layers));
+pub fn make_pheromone_array_images(
+    images: &mut Assets<Image>,
+    layers: u32,
+    size: UVec2,
+) -> PheromoneArrayImages {
+    let prev = images.add(create_pheromone_array_image(layers, size));
+    let next = images.add(create_pheromone_array_image(layers, size));
     PheromoneArrayImages { prev, next }
 }
 
+/// Like `make_pheromone_array_images`, but both arrays start from `values`
+/// (`layers` planes of `size.x * size.y` texels, as `load_initial_field`
+/// returns them) instead of zeros.
+pub fn make_pheromone_array_images_from(
+    images: &mut Assets<Image>,
+    layers: u32,
+    size: UVec2,
+    values: &[f32],
+) -> PheromoneArrayImages {
+    let make = || {
+        let mut img = create_pheromone_array_image(layers, size);
+        img.data = Some(bytemuck::cast_slice(values).to_vec());
+        img
+    };
+    PheromoneArrayImages { prev: images.add(make()), next: images.add(make()) }
+}
+
+/// Read `field` into `layers` planes of `size` texels (layer-major, rows
+/// top-down like the array texture). `Ok(None)` for `InitialField::Empty`.
+/// Every image must be exactly `size`.
+pub fn load_initial_field(
+    field: &crate::resources::InitialField,
+    layers: u32,
+    size: UVec2,
+) -> Result<Option<Vec<f32>>, String> {
+    use crate::resources::InitialField;
+    let plane = (size.x * size.y) as usize;
+    match field {
+        InitialField::Empty => Ok(None),
+        InitialField::Channels(path) => {
+            let rgba = read_field_image(path, size, true)?;
+            Ok(Some(channels_to_layers(&rgba, plane, layers)))
+        }
+        InitialField::PerLayer(paths) => {
+            if paths.len() > layers as usize {
+                return Err(format!("{} images for {layers} layers", paths.len()));
+            }
+            let mut values = vec![0.0; plane * layers as usize];
+            for (layer, path) in paths.iter().enumerate() {
+                let luma = read_field_image(path, size, false)?;
+                values[layer * plane..(layer + 1) * plane].copy_from_slice(&luma);
+            }
+            Ok(Some(values))
+        }
+    }
+}
+
+/// Decode `path` as RGBA (`rgba`) or luminance floats in [0, 1], failing
+/// unless it is exactly `size`.
+fn read_field_image(path: &str, size: UVec2, rgba: bool) -> Result<Vec<f32>, String> {
+    use bevy::image::{CompressedImageFormats, ImageSampler, ImageType};
+    let bytes = std::fs::read(path).map_err(|err| format!("{path}: {err}"))?;
+    let ext = std::path::Path::new(path).extension().and_then(|e| e.to_str()).unwrap_or("png");
+    let image = Image::from_buffer(
+        &bytes,
+        ImageType::Extension(ext),
+        CompressedImageFormats::NONE,
+        false,
+        ImageSampler::Default,
+        RenderAssetUsages::MAIN_WORLD,
+    )
+    .map_err(|err| format!("{path}: {err}"))?;
+    let image_size = image.size();
+    if image_size != size {
+        return Err(format!("{path} is {image_size}, but the simulation is {size}"));
+    }
+    let dynamic = image.try_into_dynamic().map_err(|err| format!("{path}: {err:?}"))?;
+    Ok(if rgba { dynamic.to_rgba32f().into_raw() } else { dynamic.to_luma32f().into_raw() })
+}
+
+/// Split interleaved RGBA texels into planes: channel `c` seeds layer `c`
+/// for the first `min(4, layers)` layers; the rest stay 0.
+pub fn channels_to_layers(rgba: &[f32], plane: usize, layers: u32) -> Vec<f32> {
+    let mut values = vec![0.0; plane * layers as usize];
+    for channel in 0..(layers as usize).min(4) {
+        for (texel, px) in rgba.chunks_exact(4).take(plane).enumerate() {
+            values[channel * plane + texel] = px[channel];
+        }
+    }
+    values
+}
+
 /// Create a single pheromone array texture descriptor/image without allocating in Assets.
 /// This is a pure helper so we can unit-test texture allocation independently.
-pub fn create_pheromone_array_image(layers: u32) -> Image {
-    let mut img = Image::new_target_texture(SIZE.x, SIZE.y, TextureFormat::R32Float);
+pub fn create_pheromone_array_image(layers: u32, size: UVec2) -> Image {
+    let mut img = Image::new_target_texture(size.x, size.y, TextureFormat::R32Float);
     img.asset_usage = RenderAssetUsages::RENDER_WORLD;
-    img.texture_descriptor.usage =
-        TextureUsages::COPY_DST | TextureUsages::STORAGE_BINDING | TextureUsages::TEXTURE_BINDING;
+    // COPY_SRC allows readback for density spawning (see `density_spawn`)
+    img.texture_descriptor.usage = TextureUsages::COPY_DST
+        | TextureUsages::COPY_SRC
+        | TextureUsages::STORAGE_BINDING
+        | TextureUsages::TEXTURE_BINDING;
     // make it a 2D array with the requested number of layers
     img.texture_descriptor.size.depth_or_array_layers = layers;
     // ensure data buffer matches expected size to avoid upload panic
     let bytes_per_pixel: u32 = 4; // R32Float
-    let byte_len = SIZE.x * SIZE.y * layers * bytes_per_pixel;
+    let byte_len = size.x * size.y * layers * bytes_per_pixel;
     img.data = vec![0u8; byte_len as usize].into();
     img
 }
@@ -62,7 +198,7 @@ pub fn create_pheromone_array_image(layers: u32) -> Image {
 // used to convert the array back into an RGBA display texture.
 
 /// Initialize array-based pheromone pipelines and layouts (prev/next array processing).
-/// Returns (env_layout, diffuse_array_pipeline, input_array_pipeline, composite_array_layout, composite_array_pipeline)
+/// Returns (env_layout, diffuse_array_pipeline, input_array_pipeline, emitter_array_pipeline, composite_array_layout, composite_array_pipeline)
 pub fn init_pheromone_array_pipelines(
     render_device: &RenderDevice,
     asset_server: &AssetServer,
@@ -71,10 +207,11 @@ pub fn init_pheromone_array_pipelines(
     BindGroupLayout,
     CachedComputePipelineId,
     CachedComputePipelineId,
+    CachedComputePipelineId,
     BindGroupLayout,
     CachedComputePipelineId,
 ) {
-    // Env layout: prev_array (ro), next_array (rw), globals, per-layer params, brush control
+    // Env layout: prev_array (ro), next_array (rw), globals, per-layer params, brush control, regions, emitters
     let env_bind_group_layout = render_device.create_bind_group_layout(
         Some("PheroArrayEnvBindGroupLayout"),
         &[
@@ -133,6 +270,28 @@ pub fn init_pheromone_array_pipelines(
                 },
                 count: None,
             },
+            // 5: simulation regions (brush paints only the region under the cursor)
+            BindGroupLayoutEntry {
+                binding: 5,
+                visibility: ShaderStages::COMPUTE,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            // 6: emitters (array<Emitter>)
+            BindGroupLayoutEntry {
+                binding: 6,
+                visibility: ShaderStages::COMPUTE,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
         ],
     );
 
@@ -141,16 +300,25 @@ pub fn init_pheromone_array_pipelines(
         layout: vec![env_bind_group_layout.clone()],
         shader: shader.clone(),
         entry_point: Some(Cow::from("diffuse_phero_array")),
+        shader_defs: workgroup_shader_defs(),
         ..default()
     });
     let input_array_pipeline = pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
         layout: vec![env_bind_group_layout.clone()],
         shader: shader.clone(),
         entry_point: Some(Cow::from("handle_input_phero_array")),
+        shader_defs: workgroup_shader_defs(),
+        ..default()
+    });
+    let emitter_array_pipeline = pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+        layout: vec![env_bind_group_layout.clone()],
+        shader: shader.clone(),
+        entry_point: Some(Cow::from("stamp_emitters")),
+        shader_defs: workgroup_shader_defs(),
         ..default()
     });
 
-    // Composite layout: array (ro) + rgba (wo) + per-layer params
+    // Composite layout: array (ro) + rgba (wo) + per-layer params + composite control
     let composite_array_layout = render_device.create_bind_group_layout(
         Some("PheroArrayCompositeBindGroupLayout"),
         &[
@@ -184,6 +352,17 @@ pub fn init_pheromone_array_pipelines(
                 },
                 count: None,
             },
+            // 3: composite control uniform (blend mode, exposure, tonemap)
+            BindGroupLayoutEntry {
+                binding: 3,
+                visibility: ShaderStages::COMPUTE,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
         ],
     );
 
@@ -192,6 +371,7 @@ pub fn init_pheromone_array_pipelines(
             layout: vec![composite_array_layout.clone()],
             shader,
             entry_point: Some(Cow::from("composite_pheromones_array")),
+            shader_defs: workgroup_shader_defs(),
             ..default()
         });
 
@@ -199,6 +379,7 @@ pub fn init_pheromone_array_pipelines(
         env_bind_group_layout,
         diffuse_array_pipeline,
         input_array_pipeline,
+        emitter_array_pipeline,
         composite_array_layout,
         composite_array_pipeline,
     )
@@ -217,17 +398,22 @@ pub fn create_phero_array_bind_groups(
     global_uniform_buffer: &bevy::render::render_resource::UniformBuffer<&crate::resources::GlobalUniforms>,
     layer_params_buffer: &bevy::render::render_resource::Buffer,
     brush_control_uniform: &bevy::render::render_resource::UniformBuffer<&crate::resources::BrushControlUniform>,
+    composite_control_uniform: &bevy::render::render_resource::UniformBuffer<&crate::resources::CompositeControlUniform>,
+    regions_uniform: &bevy::render::render_resource::UniformBuffer<&crate::resources::RegionsUniform>,
+    emitters_buffer: &bevy::render::render_resource::Buffer,
 ) -> Option<([BindGroup; 2], [BindGroup; 2])> {
     let prev_view = &gpu_images.get(&phero_arrays.prev)?.texture_view;
     let next_view = &gpu_images.get(&phero_arrays.next)?.texture_view;
+    let read = |index| PingArray::env_read(index).view(prev_view, next_view);
+    let write = |index| PingArray::env_write(index).view(prev_view, next_view);
 
     // Ping 0: prev=prev, next=next
     let env_bg0 = render_device.create_bind_group(
         None,
         env_layout,
         &BindGroupEntries::sequential((
-            prev_view,
-            next_view,
+            read(0),
+            write(0),
             global_uniform_buffer,
             BufferBinding {
                 buffer: layer_params_buffer,
@@ -235,19 +421,22 @@ pub fn create_phero_array_bind_groups(
                 size: None,
             },
             brush_control_uniform,
+            regions_uniform,
+            emitters_buffer.as_entire_buffer_binding(),
         )),
     );
     let comp_bg0 = render_device.create_bind_group(
         None,
         composite_layout,
         &BindGroupEntries::sequential((
-            next_view,
+            write(0),
             view_out_b,
             BufferBinding {
                 buffer: layer_params_buffer,
                 offset: 0,
                 size: None,
             },
+            composite_control_uniform,
         )),
     );
 
@@ -256,8 +445,8 @@ pub fn create_phero_array_bind_groups(
         None,
         env_layout,
         &BindGroupEntries::sequential((
-            next_view,
-            prev_view,
+            read(1),
+            write(1),
             global_uniform_buffer,
             BufferBinding {
                 buffer: layer_params_buffer,
@@ -265,37 +454,440 @@ pub fn create_phero_array_bind_groups(
                 size: None,
             },
             brush_control_uniform,
+            regions_uniform,
+            emitters_buffer.as_entire_buffer_binding(),
         )),
     );
     let comp_bg1 = render_device.create_bind_group(
         None,
         composite_layout,
         &BindGroupEntries::sequential((
-            prev_view,
+            write(1),
             view_out_a,
             BufferBinding {
                 buffer: layer_params_buffer,
                 offset: 0,
                 size: None,
             },
+            composite_control_uniform,
         )),
     );
 
     Some(([env_bg0, env_bg1], [comp_bg0, comp_bg1]))
 }
 
+/// Initialize the clear pipeline: a single write-only array binding zeroed by
+/// `clear_phero_array`. Returns (clear_layout, clear_array_pipeline).
+pub fn init_pheromone_clear_pipeline(
+    render_device: &RenderDevice,
+    asset_server: &AssetServer,
+    pipeline_cache: &PipelineCache,
+) -> (BindGroupLayout, CachedComputePipelineId) {
+    let clear_layout = render_device.create_bind_group_layout(
+        Some("PheroArrayClearBindGroupLayout"),
+        &[
+            // 0: array to clear (write-only)
+            BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::COMPUTE,
+                ty: BindingType::StorageTexture {
+                    access: StorageTextureAccess::WriteOnly,
+                    format: TextureFormat::R32Float,
+                    view_dimension: TextureViewDimension::D2Array,
+                },
+                count: None,
+            },
+        ],
+    );
+
+    let clear_array_pipeline = pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+        layout: vec![clear_layout.clone()],
+        shader: asset_server.load(PHERO_SHADER_PATH),
+        entry_point: Some(Cow::from("clear_phero_array")),
+        shader_defs: workgroup_shader_defs(),
+        ..default()
+    });
+
+    (clear_layout, clear_array_pipeline)
+}
+
+/// Create clear bind groups for both arrays ([prev, next]) so a clear wipes
+/// the whole ping-pong pair regardless of the current ping.
+pub fn create_phero_clear_bind_groups(
+    render_device: &RenderDevice,
+    gpu_images: &RenderAssets<GpuImage>,
+    phero_arrays: &PheromoneArrayImages,
+    clear_layout: &BindGroupLayout,
+) -> Option<[BindGroup; 2]> {
+    let prev_view = &gpu_images.get(&phero_arrays.prev)?.texture_view;
+    let next_view = &gpu_images.get(&phero_arrays.next)?.texture_view;
+
+    let clear_prev = render_device.create_bind_group(
+        None,
+        clear_layout,
+        &BindGroupEntries::single(prev_view),
+    );
+    let clear_next = render_device.create_bind_group(
+        None,
+        clear_layout,
+        &BindGroupEntries::single(next_view),
+    );
+
+    Some([clear_prev, clear_next])
+}
+
+/// Initialize the cursor probe pipeline: array (ro), probe output buffer (rw),
+/// globals (mouse position). Returns (probe_layout, probe_array_pipeline).
+pub fn init_pheromone_probe_pipeline(
+    render_device: &RenderDevice,
+    asset_server: &AssetServer,
+    pipeline_cache: &PipelineCache,
+) -> (BindGroupLayout, CachedComputePipelineId, CachedComputePipelineId) {
+    let probe_layout = render_device.create_bind_group_layout(
+        Some("PheroArrayProbeBindGroupLayout"),
+        &[
+            // 0: array to sample
+            BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::COMPUTE,
+                ty: BindingType::StorageTexture {
+                    access: StorageTextureAccess::ReadOnly,
+                    format: TextureFormat::R32Float,
+                    view_dimension: TextureViewDimension::D2Array,
+                },
+                count: None,
+            },
+            // 1: probe output (one f32 per layer)
+            BindGroupLayoutEntry {
+                binding: 1,
+                visibility: ShaderStages::COMPUTE,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Storage { read_only: false },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            // 2: globals (mouse position)
+            BindGroupLayoutEntry {
+                binding: 2,
+                visibility: ShaderStages::COMPUTE,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+        ],
+    );
+
+    let probe_array_pipeline = pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+        layout: vec![probe_layout.clone()],
+        shader: asset_server.load(PHERO_SHADER_PATH),
+        entry_point: Some(Cow::from("probe_phero_array")),
+        shader_defs: workgroup_shader_defs(),
+        ..default()
+    });
+    // Layer stats reduce the same array into another buffer with this layout
+    let stats_array_pipeline = pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+        layout: vec![probe_layout.clone()],
+        shader: asset_server.load(PHERO_SHADER_PATH),
+        entry_point: Some(Cow::from("reduce_phero_stats")),
+        shader_defs: workgroup_shader_defs(),
+        ..default()
+    });
+
+    (probe_layout, probe_array_pipeline, stats_array_pipeline)
+}
+
+/// Create probe bind groups per ping, sampling the same array the composite
+/// reads that frame (`PingArray::env_write`).
+pub fn create_phero_probe_bind_groups(
+    render_device: &RenderDevice,
+    gpu_images: &RenderAssets<GpuImage>,
+    phero_arrays: &PheromoneArrayImages,
+    probe_layout: &BindGroupLayout,
+    probe_buffer: &Buffer,
+    global_uniform_buffer: &UniformBuffer<&crate::resources::GlobalUniforms>,
+) -> Option<[BindGroup; 2]> {
+    let prev_view = &gpu_images.get(&phero_arrays.prev)?.texture_view;
+    let next_view = &gpu_images.get(&phero_arrays.next)?.texture_view;
+    let probe_binding = || BufferBinding {
+        buffer: probe_buffer,
+        offset: 0,
+        size: None,
+    };
+
+    Some([0, 1].map(|index| {
+        render_device.create_bind_group(
+            None,
+            probe_layout,
+            &BindGroupEntries::sequential((
+                PingArray::env_write(index).view(prev_view, next_view),
+                probe_binding(),
+                global_uniform_buffer,
+            )),
+        )
+    }))
+}
+
+/// Initialize the display encode pipeline: float display (ro) -> 8-bit
+/// target (wo). Returns (encode_layout, encode_display_pipeline).
+pub fn init_display_encode_pipeline(
+    render_device: &RenderDevice,
+    asset_server: &AssetServer,
+    pipeline_cache: &PipelineCache,
+) -> (BindGroupLayout, CachedComputePipelineId) {
+    let encode_layout = render_device.create_bind_group_layout(
+        Some("DisplayEncodeBindGroupLayout"),
+        &[
+            // 0: composited display (Rgba32Float, linear)
+            BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::COMPUTE,
+                ty: BindingType::StorageTexture {
+                    access: StorageTextureAccess::ReadOnly,
+                    format: TextureFormat::Rgba32Float,
+                    view_dimension: TextureViewDimension::D2,
+                },
+                count: None,
+            },
+            // 1: 8-bit target, written through its Rgba8Unorm view
+            BindGroupLayoutEntry {
+                binding: 1,
+                visibility: ShaderStages::COMPUTE,
+                ty: BindingType::StorageTexture {
+                    access: StorageTextureAccess::WriteOnly,
+                    format: TextureFormat::Rgba8Unorm,
+                    view_dimension: TextureViewDimension::D2,
+                },
+                count: None,
+            },
+        ],
+    );
+
+    let encode_display_pipeline = pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+        layout: vec![encode_layout.clone()],
+        shader: asset_server.load(PHERO_SHADER_PATH),
+        entry_point: Some(Cow::from("encode_display_srgb")),
+        shader_defs: workgroup_shader_defs(),
+        ..default()
+    });
+
+    (encode_layout, encode_display_pipeline)
+}
+
+/// Agent view trail fade: previous display (ro) -> this ping's display (wo),
+/// plus the fade uniform. Returns (fade_layout, fade_pipeline).
+pub fn init_display_fade_pipeline(
+    render_device: &RenderDevice,
+    asset_server: &AssetServer,
+    pipeline_cache: &PipelineCache,
+) -> (BindGroupLayout, CachedComputePipelineId) {
+    let display_texture = |binding, access| BindGroupLayoutEntry {
+        binding,
+        visibility: ShaderStages::COMPUTE,
+        ty: BindingType::StorageTexture {
+            access,
+            format: TextureFormat::Rgba32Float,
+            view_dimension: TextureViewDimension::D2,
+        },
+        count: None,
+    };
+    let fade_layout = render_device.create_bind_group_layout(
+        Some("DisplayFadeBindGroupLayout"),
+        &[
+            // 0: last frame's display
+            display_texture(0, StorageTextureAccess::ReadOnly),
+            // 1: this frame's display
+            display_texture(1, StorageTextureAccess::WriteOnly),
+            // 2: fade control (background, keep)
+            BindGroupLayoutEntry {
+                binding: 2,
+                visibility: ShaderStages::COMPUTE,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+        ],
+    );
+
+    let fade_pipeline = pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+        layout: vec![fade_layout.clone()],
+        shader: asset_server.load(PHERO_SHADER_PATH),
+        entry_point: Some(Cow::from("fade_display")),
+        shader_defs: workgroup_shader_defs(),
+        ..default()
+    });
+
+    (fade_layout, fade_pipeline)
+}
+
+/// Create agent view fade bind groups per ping. Ping 0 writes `view_b` (as
+/// the composite does), so it fades from `view_a`, shown last frame; ping 1
+/// the other way round.
+pub fn create_display_fade_bind_groups(
+    render_device: &RenderDevice,
+    fade_layout: &BindGroupLayout,
+    view_a: &TextureView,
+    view_b: &TextureView,
+    fade_uniform: &UniformBuffer<&crate::resources::DisplayFadeUniform>,
+) -> [BindGroup; 2] {
+    [(view_a, view_b), (view_b, view_a)].map(|(previous, current)| {
+        render_device.create_bind_group(
+            None,
+            fade_layout,
+            &BindGroupEntries::sequential((previous, current, fade_uniform)),
+        )
+    })
+}
+
+/// Create display encode bind groups per ping, reading the float display the
+/// composite wrote that ping (0 -> `view_b`, 1 -> `view_a`).
+pub fn create_display_encode_bind_groups(
+    render_device: &RenderDevice,
+    gpu_images: &RenderAssets<GpuImage>,
+    display_srgb: &Handle<Image>,
+    encode_layout: &BindGroupLayout,
+    view_a: &TextureView,
+    view_b: &TextureView,
+) -> Option<[BindGroup; 2]> {
+    // The image's default view is Rgba8UnormSrgb (for sampling); storage needs
+    // the plain Unorm view
+    let srgb_image = gpu_images.get(display_srgb)?;
+    let storage_view = TextureView::from(srgb_image.texture.create_view(&TextureViewDescriptor {
+        format: Some(TextureFormat::Rgba8Unorm),
+        ..default()
+    }));
+
+    Some([view_b, view_a].map(|display_view| {
+        render_device.create_bind_group(
+            None,
+            encode_layout,
+            &BindGroupEntries::sequential((display_view, &storage_view)),
+        )
+    }))
+}
+
 // Removed legacy per-channel bind group creation
 
+/// Exchange layers `a` and `b` of the array `texture`, staging them in the
+/// same layers of `scratch` (crossed over) and copying them back. `scratch`
+/// must be the same size and format; its two layers are left holding the
+/// swapped values, so the ping-pong partner works when it's rewritten anyway.
+pub fn swap_array_layers(encoder: &mut CommandEncoder, texture: &Texture, scratch: &Texture, a: u32, b: u32) {
+    fn layer(texture: &Texture, z: u32) -> TexelCopyTextureInfo<'_> {
+        let mut copy = texture.as_image_copy();
+        copy.origin.z = z;
+        copy
+    }
+    let size = Extent3d { depth_or_array_layers: 1, ..texture.size() };
+    encoder.copy_texture_to_texture(layer(texture, a), layer(scratch, b), size);
+    encoder.copy_texture_to_texture(layer(texture, b), layer(scratch, a), size);
+    encoder.copy_texture_to_texture(layer(scratch, a), layer(texture, a), size);
+    encoder.copy_texture_to_texture(layer(scratch, b), layer(texture, b), size);
+}
+
+/// Exchange layers `a` and `b` of the array `texture` through a temporary
+/// two-layer texture, leaving every other texture untouched. For when both
+/// ping-pong arrays hold live data (diffusion off), so neither can stage.
+pub fn swap_array_layers_staged(
+    render_device: &RenderDevice,
+    encoder: &mut CommandEncoder,
+    texture: &Texture,
+    a: u32,
+    b: u32,
+) {
+    fn layer(texture: &Texture, z: u32) -> TexelCopyTextureInfo<'_> {
+        let mut copy = texture.as_image_copy();
+        copy.origin.z = z;
+        copy
+    }
+    let size = Extent3d { depth_or_array_layers: 1, ..texture.size() };
+    // Kept alive by the encoder until the copies have run
+    let scratch = render_device.create_texture(&TextureDescriptor {
+        label: Some("Pheromone layer swap scratch"),
+        size: Extent3d { depth_or_array_layers: 2, ..size },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: texture.format(),
+        usage: TextureUsages::COPY_SRC | TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+    encoder.copy_texture_to_texture(layer(texture, a), layer(&scratch, 0), size);
+    encoder.copy_texture_to_texture(layer(texture, b), layer(&scratch, 1), size);
+    encoder.copy_texture_to_texture(layer(&scratch, 1), layer(texture, a), size);
+    encoder.copy_texture_to_texture(layer(&scratch, 0), layer(texture, b), size);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::SIZE;
     use bevy::prelude::Assets;
 
+    // CPU copy of the shader's `neighbor_coord`: texel `offset` away from
+    // `coord`, each axis wrapping modulo `size` when `wrap` is set (see
+    // `PheromoneConfig::diffusion_wraps`) and clamping to the edge otherwise
+    fn diffusion_neighbor(coord: IVec2, offset: IVec2, size: UVec2, wrap: bool) -> IVec2 {
+        let size = size.as_ivec2().max(IVec2::ONE);
+        let n = coord + offset;
+        if wrap {
+            IVec2::new(n.x.rem_euclid(size.x), n.y.rem_euclid(size.y))
+        } else {
+            n.clamp(IVec2::ZERO, size - 1)
+        }
+    }
+
+    // CPU copy of one mass-conserving diffusion step over a `size` field
+    // (row-major), as `diffuse_phero_array` runs it for `conserve_mass`
+    // layers: each texel exchanges `rate / 4` (per-frame factor) of its value
+    // with each edge neighbor, so the field's sum is unchanged
+    fn conserving_diffusion_step(field: &[f32], size: UVec2, rate: f32, wrap: bool) -> Vec<f32> {
+        let share = rate.clamp(0.0, 1.0) * 0.25;
+        let width = size.x.max(1) as i32;
+        let at = |p: IVec2| field[(p.y * width + p.x) as usize];
+        (0..field.len())
+            .map(|i| {
+                let coord = IVec2::new(i as i32 % width, i as i32 / width);
+                let c = field[i];
+                let neighbors: f32 = [IVec2::NEG_X, IVec2::X, IVec2::NEG_Y, IVec2::Y]
+                    .into_iter()
+                    .map(|offset| at(diffusion_neighbor(coord, offset, size, wrap)))
+                    .sum();
+                c + share * (neighbors - 4.0 * c)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn initial_field_channels_split_into_layers() {
+        // Two texels: (1, 2, 3, 4) and (5, 6, 7, 8)
+        let rgba = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0];
+        assert_eq!(channels_to_layers(&rgba, 2, 2), vec![1.0, 5.0, 2.0, 6.0]);
+        // Layers past the four channels start empty
+        let five = channels_to_layers(&rgba, 2, 5);
+        assert_eq!(&five[6..8], &[4.0, 8.0]);
+        assert_eq!(&five[8..], &[0.0, 0.0]);
+
+        use crate::resources::InitialField;
+        assert_eq!(load_initial_field(&InitialField::Empty, 3, UVec2::ONE), Ok(None));
+        let too_many = InitialField::PerLayer(vec!["a.png".into(), "b.png".into()]);
+        assert!(load_initial_field(&too_many, 1, UVec2::ONE).is_err());
+        let missing = InitialField::Channels("no/such/field.png".into());
+        assert!(load_initial_field(&missing, 3, UVec2::ONE).unwrap_err().contains("no/such/field.png"));
+    }
+
     #[test]
     fn make_pheromone_array_images_layers_and_size() {
         let mut images: Assets<Image> = Assets::default();
         let test_layers = 3u32; // Test with legacy RGB layer count
-        let phero_imgs = make_pheromone_array_images(&mut images, test_layers);
+        let phero_imgs = make_pheromone_array_images(&mut images, test_layers, SIZE);
 
         let prev = images.get(&phero_imgs.prev).expect("prev image exists");
         let next = images.get(&phero_imgs.next).expect("next image exists");
@@ -325,10 +917,67 @@ mod tests {
         );
     }
 
+    #[test]
+    fn diffusion_neighbors_wrap_at_corners() {
+        let size = UVec2::new(4, 3);
+        let corner = IVec2::new(3, 2);
+        // Clamped: the corner is its own right/up/diagonal neighbor
+        assert_eq!(diffusion_neighbor(corner, IVec2::new(1, 1), size, false), corner);
+        assert_eq!(diffusion_neighbor(IVec2::ZERO, IVec2::new(-1, 0), size, false), IVec2::ZERO);
+        // Wrapped: each axis goes to the opposite edge, so diagonals reach the opposite corner
+        assert_eq!(diffusion_neighbor(corner, IVec2::new(1, 1), size, true), IVec2::ZERO);
+        assert_eq!(diffusion_neighbor(IVec2::ZERO, IVec2::new(-1, -1), size, true), corner);
+        assert_eq!(diffusion_neighbor(IVec2::new(0, 2), IVec2::new(-1, 1), size, true), IVec2::new(3, 0));
+        assert_eq!(diffusion_neighbor(corner, IVec2::new(1, 0), size, true), IVec2::new(0, 2));
+        // Interior texels are the same either way
+        let inner = IVec2::new(1, 1);
+        for offset in [IVec2::new(-1, -1), IVec2::new(1, 1), IVec2::new(1, -1)] {
+            assert_eq!(
+                diffusion_neighbor(inner, offset, size, true),
+                diffusion_neighbor(inner, offset, size, false)
+            );
+        }
+    }
+
+    #[test]
+    fn conserving_diffusion_keeps_total_mass() {
+        let size = UVec2::new(7, 5);
+        // A bright spot plus an uneven background
+        let mut start: Vec<f32> = (0..35).map(|i| ((i * 7) % 11) as f32 * 0.1).collect();
+        start[17] = 50.0;
+        let total: f32 = start.iter().sum();
+        for wrap in [false, true] {
+            let mut field = start.clone();
+            for _ in 0..200 {
+                field = conserving_diffusion_step(&field, size, 0.8, wrap);
+            }
+            let sum: f32 = field.iter().sum();
+            assert!((sum - total).abs() < total * 1e-4, "wrap {wrap}: {sum} vs {total}");
+            // It still spreads: the spot flattens toward the mean, never negative
+            let max = field.iter().copied().fold(f32::MIN, f32::max);
+            assert!(max < 50.0 * 0.1, "wrap {wrap}: peak {max}");
+            assert!(field.iter().all(|&v| v >= 0.0));
+        }
+        // Rate 0 leaves the field alone
+        assert_eq!(conserving_diffusion_step(&start, size, 0.0, false), start);
+    }
+
+    #[test]
+    fn agents_use_the_array_the_env_pass_wrote() {
+        for index in 0..2 {
+            // Env pass flips between the two arrays
+            assert_ne!(PingArray::env_read(index), PingArray::env_write(index));
+            // What this ping wrote is what the next ping reads
+            assert_eq!(PingArray::env_write(index), PingArray::env_read(1 - index));
+        }
+        assert_eq!(PingArray::env_write(0), PingArray::Next);
+        assert_eq!(PingArray::env_write(1), PingArray::Prev);
+    }
+
     #[test]
     fn create_pheromone_array_image_descriptor() {
         let test_layers = 3u32; // Test with legacy RGB layer count
-        let img = create_pheromone_array_image(test_layers);
+        let img = create_pheromone_array_image(test_layers, SIZE);
         // check dimensions and layer count
         assert_eq!(img.texture_descriptor.size.width, SIZE.x);
         assert_eq!(img.texture_descriptor.size.height, SIZE.y);
@@ -354,5 +1003,10 @@ mod tests {
                 .usage
                 .contains(TextureUsages::TEXTURE_BINDING)
         );
+        assert!(
+            img.texture_descriptor
+                .usage
+                .contains(TextureUsages::COPY_SRC)
+        );
     }
 }
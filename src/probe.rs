@@ -0,0 +1,69 @@
+// Pheromone values under the cursor, for debugging the field numerically.
+//
+// Each frame the render node runs `probe_phero_array`, which copies every
+// layer's texel at `GlobalUniforms.mouse_position` into a small storage
+// buffer. A persistent `Readback` of that buffer lands in `PheromoneProbe`
+// one frame later, and the overlay label prints the values.
+
+use bevy::prelude::*;
+use bevy::render::extract_resource::ExtractResource;
+use bevy::render::gpu_readback::{Readback, ReadbackComplete};
+use bevy::render::render_resource::BufferUsages;
+use bevy::render::storage::ShaderStorageBuffer;
+
+use crate::resources::PheromoneConfig;
+
+// Probe buffer capacity; layers past this are not reported
+pub const MAX_PROBE_LAYERS: usize = 32;
+
+/// GPU buffer the probe pass writes (one f32 per layer).
+#[derive(Resource, Clone, ExtractResource)]
+pub struct PheromoneProbeBuffer {
+    pub buffer: Handle<ShaderStorageBuffer>,
+}
+
+/// Latest values read back from the probe buffer, one per active layer.
+#[derive(Resource, Default, Debug)]
+pub struct PheromoneProbe {
+    pub values: Vec<f32>,
+}
+
+pub struct ProbePlugin;
+impl Plugin for ProbePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PheromoneProbe>()
+            .add_systems(Startup, spawn_probe_readback);
+    }
+}
+
+fn spawn_probe_readback(mut commands: Commands, mut buffers: ResMut<Assets<ShaderStorageBuffer>>) {
+    let mut buffer = ShaderStorageBuffer::from(vec![0.0f32; MAX_PROBE_LAYERS]);
+    buffer.buffer_description.usage |= BufferUsages::COPY_SRC;
+    let buffer = buffers.add(buffer);
+    commands.insert_resource(PheromoneProbeBuffer { buffer: buffer.clone() });
+    // Readback repeats every frame while the entity lives, which is what we want here
+    commands.spawn(Readback::buffer(buffer)).observe(
+        |event: On<ReadbackComplete>, cfg: Res<PheromoneConfig>, mut probe: ResMut<PheromoneProbe>| {
+            probe.values = probe_values(&event.data, cfg.layer_count);
+        },
+    );
+}
+
+/// Decode the raw probe buffer, keeping only the first `layer_count` layers.
+pub fn probe_values(data: &[u8], layer_count: u32) -> Vec<f32> {
+    let mut values: Vec<f32> = bytemuck::pod_collect_to_vec(data);
+    values.truncate(layer_count as usize);
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn probe_values_decodes_and_truncates() {
+        let data: Vec<u8> = [0.5f32, 1.0, 2.0, 0.0].iter().flat_map(|v| v.to_le_bytes()).collect();
+        assert_eq!(probe_values(&data, 3), vec![0.5, 1.0, 2.0]);
+        assert_eq!(probe_values(&data, 8).len(), 4);
+    }
+}
@@ -0,0 +1,59 @@
+// Optional GPU timing of the simulation's compute passes (feature
+// `gpu-profiling`).
+//
+// `AgentSimNode` wraps each compute pass in a render diagnostics span. With
+// Bevy's `RenderDiagnosticsPlugin` installed those spans are measured with
+// timestamp queries (when the adapter supports them) and land in the
+// `DiagnosticsStore`; without it they are no-ops. This plugin installs the
+// recorder and logs each pass's GPU time averaged over the stored history.
+
+use bevy::diagnostic::{DiagnosticPath, DiagnosticsStore};
+use bevy::prelude::*;
+use bevy::render::diagnostic::RenderDiagnosticsPlugin;
+
+use crate::render::PROFILED_PASSES;
+
+// How often the averaged timings are printed
+const REPORT_INTERVAL_SECS: f32 = 2.0;
+
+pub struct GpuProfilingPlugin;
+impl Plugin for GpuProfilingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RenderDiagnosticsPlugin)
+            .insert_resource(ProfilingReportTimer(Timer::from_seconds(
+                REPORT_INTERVAL_SECS,
+                TimerMode::Repeating,
+            )))
+            .add_systems(Update, report_pass_timings);
+    }
+}
+
+#[derive(Resource)]
+struct ProfilingReportTimer(Timer);
+
+/// Diagnostic path Bevy records a span's GPU time under.
+pub fn gpu_time_path(pass: &str) -> DiagnosticPath {
+    DiagnosticPath::from_components(["render", pass, "elapsed_gpu"])
+}
+
+fn report_pass_timings(
+    time: Res<Time>,
+    mut timer: ResMut<ProfilingReportTimer>,
+    diagnostics: Res<DiagnosticsStore>,
+) {
+    if !timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+    let parts: Vec<String> = PROFILED_PASSES
+        .iter()
+        .filter_map(|pass| {
+            let avg = diagnostics.get(&gpu_time_path(pass))?.average()?;
+            Some(format!("{pass} {avg:.3} ms"))
+        })
+        .collect();
+    if parts.is_empty() {
+        info!("GPU passes: no timings yet (timestamp queries unsupported?)");
+    } else {
+        info!("GPU passes: {}", parts.join(" | "));
+    }
+}
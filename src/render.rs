@@ -20,6 +20,7 @@
 // the shader accordingly.
 
 use bevy::prelude::*;
+use bevy::render::diagnostic::RecordDiagnostics;
 use bevy::render::extract_resource::ExtractResourcePlugin;
 use bevy::render::render_resource::*;
 use bevy::render::renderer::{RenderContext, RenderDevice, RenderQueue};
@@ -29,17 +30,47 @@ use bevy::render::{
 };
 
 use bevy::render::render_asset::RenderAssets;
+use bevy::render::storage::GpuShaderStorageBuffer;
 use bevy::render::texture::GpuImage;
 use bevy::shader::PipelineCacheError;
 use std::borrow::Cow;
 
 // legacy per-pheromone pipelines removed
-use crate::pheromones::{create_phero_array_bind_groups, init_pheromone_array_pipelines};
+use crate::pheromones::{
+    create_display_encode_bind_groups, create_display_fade_bind_groups, create_phero_array_bind_groups,
+    create_phero_clear_bind_groups, create_phero_probe_bind_groups, init_display_encode_pipeline,
+    init_display_fade_pipeline, init_pheromone_array_pipelines,
+    init_pheromone_clear_pipeline, init_pheromone_probe_pipeline, swap_array_layers,
+    swap_array_layers_staged, PingArray,
+};
 use crate::resources::*;
-use crate::{AGENTS_SHADER_PATH, SIZE, WORKGROUP_SIZE, AGENT_WORKGROUP_SIZE, NUM_AGENTS};
+use crate::{AGENTS_SHADER_PATH, WORKGROUP_SIZE, AGENT_WORKGROUP_SIZE};
 
 pub struct AgentSimComputePlugin;
 
+// Diagnostic span names for each compute pass (timed by `profiling` when the
+// `gpu-profiling` feature installs the render diagnostics recorder)
+pub const PASS_CLEAR: &str = "slime_clear";
+pub const PASS_DIFFUSE: &str = "slime_diffuse";
+pub const PASS_INPUT: &str = "slime_input";
+pub const PASS_AGENT_GRID: &str = "slime_agent_grid";
+pub const PASS_AGENTS: &str = "slime_agents";
+pub const PASS_COMPOSITE: &str = "slime_composite";
+// Agent dots and sensor markers share this pass
+pub const PASS_AGENT_DOTS: &str = "slime_agent_dots";
+pub const PASS_DISPLAY_ENCODE: &str = "slime_display_encode";
+#[cfg_attr(not(feature = "gpu-profiling"), allow(dead_code))]
+pub const PROFILED_PASSES: [&str; 8] = [
+    PASS_CLEAR,
+    PASS_DIFFUSE,
+    PASS_INPUT,
+    PASS_AGENT_GRID,
+    PASS_AGENTS,
+    PASS_COMPOSITE,
+    PASS_AGENT_DOTS,
+    PASS_DISPLAY_ENCODE,
+];
+
 #[derive(Debug, Hash, PartialEq, Eq, Clone, bevy::render::render_graph::RenderLabel)]
 struct AgentSimLabel;
 
@@ -47,15 +78,40 @@ impl Plugin for AgentSimComputePlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins((
             ExtractResourcePlugin::<crate::agents::AgentGpuBuffer>::default(),
+            ExtractResourcePlugin::<crate::agents::AgentPopulation>::default(),
             ExtractResourcePlugin::<PheromoneImages>::default(),
             ExtractResourcePlugin::<SpeciesGpuBuffer>::default(),
             ExtractResourcePlugin::<SpeciesLayerWeights>::default(),
             ExtractResourcePlugin::<GlobalUniforms>::default(),
+            ExtractResourcePlugin::<SimulationSize>::default(),
             ExtractResourcePlugin::<PheromoneConfig>::default(),
-            ExtractResourcePlugin::<AgentSimRunConfig>::default(),
             ExtractResourcePlugin::<crate::pheromones::PheromoneArrayImages>::default(),
             ExtractResourcePlugin::<crate::resources::PheromoneLayerParamsBuffer>::default(),
-        ));
+            ExtractResourcePlugin::<crate::obstacles::ObstacleMask>::default(),
+            ExtractResourcePlugin::<crate::probe::PheromoneProbeBuffer>::default(),
+            ExtractResourcePlugin::<crate::stats::PheromoneStatsBuffer>::default(),
+            ExtractResourcePlugin::<SimRegions>::default(),
+            ExtractResourcePlugin::<AgentView>::default(),
+        ))
+        // Settings; a plugin tuple holds at most 15 entries
+        .add_plugins((
+            ExtractResourcePlugin::<AgentSimRunConfig>::default(),
+            ExtractResourcePlugin::<ClearPheromonesRequest>::default(),
+            ExtractResourcePlugin::<LayerSwapRequest>::default(),
+            ExtractResourcePlugin::<CompositeSettings>::default(),
+            ExtractResourcePlugin::<Background>::default(),
+            ExtractResourcePlugin::<BrushFalloff>::default(),
+            ExtractResourcePlugin::<BrushMode>::default(),
+            ExtractResourcePlugin::<BeaconSettings>::default(),
+            ExtractResourcePlugin::<SrgbDisplay>::default(),
+            ExtractResourcePlugin::<crate::stats::PheromoneStatsEnabled>::default(),
+            ExtractResourcePlugin::<SensorSampling>::default(),
+            ExtractResourcePlugin::<crate::auto_quality::QualityLevel>::default(),
+            ExtractResourcePlugin::<Emitters>::default(),
+            ExtractResourcePlugin::<crate::input::MouseButtonState>::default(),
+            ExtractResourcePlugin::<AgentRenderConfig>::default(),
+        ))
+        .add_plugins(ExtractResourcePlugin::<crate::agents::AgentBufferGrowRequest>::default());
 
         let render_app = app.sub_app_mut(RenderApp);
         render_app
@@ -71,6 +127,22 @@ impl Plugin for AgentSimComputePlugin {
     }
 }
 
+/// Shader defs carrying the workgroup sizes into every compute shader, so
+/// `WORKGROUP_SIZE` / `AGENT_WORKGROUP_SIZE` in `lib.rs` are the only place
+/// to tune them, plus the emitter stamp radius (`EMITTER_RADIUS`).
+pub fn workgroup_shader_defs() -> Vec<ShaderDefVal> {
+    vec![
+        ShaderDefVal::UInt("WORKGROUP_SIZE".into(), WORKGROUP_SIZE),
+        ShaderDefVal::UInt("AGENT_WORKGROUP_SIZE".into(), AGENT_WORKGROUP_SIZE),
+        ShaderDefVal::Int("EMITTER_RADIUS".into(), EMITTER_RADIUS as i32),
+    ]
+}
+
+/// Workgroups needed to cover `extent` invocations with `workgroup_size` each.
+pub fn dispatch_groups(extent: u32, workgroup_size: u32) -> u32 {
+    extent.div_ceil(workgroup_size.max(1))
+}
+
 /// Initialize the compute pipelines and layouts used by the simulation.
 ///
 /// This creates:
@@ -85,12 +157,34 @@ impl Plugin for AgentSimComputePlugin {
 pub struct AgentSimPipeline {
     pub texture_bind_group_layout: BindGroupLayout,
     pub agent_sim_pipeline: CachedComputePipelineId,
+    // Same-species avoidance grid build (`bin_agents`, agent layout)
+    pub agent_grid_pipeline: CachedComputePipelineId,
     // Array-based pipelines
     pub phero_array_env_layout: BindGroupLayout,
     pub diffuse_array_pipeline: CachedComputePipelineId,
     pub input_array_pipeline: CachedComputePipelineId,
+    // Point sources (`stamp_emitters`, env layout), run after the brush
+    pub emitter_array_pipeline: CachedComputePipelineId,
     pub phero_array_comp_layout: BindGroupLayout,
     pub composite_array_pipeline: CachedComputePipelineId,
+    // On-demand clear of both arrays
+    pub phero_clear_layout: BindGroupLayout,
+    pub clear_array_pipeline: CachedComputePipelineId,
+    // Cursor probe (array values under the mouse -> readback buffer)
+    pub phero_probe_layout: BindGroupLayout,
+    pub probe_array_pipeline: CachedComputePipelineId,
+    // Optional per-layer min/max/mean reduction (probe layout)
+    pub stats_array_pipeline: CachedComputePipelineId,
+    // Optional agent dot overlay (agents -> display texture)
+    pub agent_dots_layout: BindGroupLayout,
+    pub agent_dots_pipeline: CachedComputePipelineId,
+    // Sensor sample markers (`draw_sensor_markers`, agent dots layout)
+    pub sensor_markers_pipeline: CachedComputePipelineId,
+    // Float display -> 8-bit sRGB presentation target (`SrgbDisplay`)
+    pub display_encode_layout: BindGroupLayout,
+    pub display_encode_pipeline: CachedComputePipelineId,
+    pub display_fade_layout: BindGroupLayout,
+    pub display_fade_pipeline: CachedComputePipelineId,
 }
 
 // No separate agents pheromone bind group resource needed when using fixed bindings
@@ -161,7 +255,7 @@ fn init_agent_sim_pipeline(
             },
             count: None,
         },
-        // 9: PheroControl uniform (layer_count only)
+        // 9: PheroControl uniform (layer count, sampling, paint-only mask)
         BindGroupLayoutEntry {
             binding: 9,
             visibility: ShaderStages::COMPUTE,
@@ -172,6 +266,50 @@ fn init_agent_sim_pipeline(
             },
             count: None,
         },
+        // 10: obstacle mask (read-only, 1.0 = wall)
+        BindGroupLayoutEntry {
+            binding: 10,
+            visibility: ShaderStages::COMPUTE,
+            ty: BindingType::StorageTexture {
+                access: StorageTextureAccess::ReadOnly,
+                format: TextureFormat::R32Float,
+                view_dimension: TextureViewDimension::D2,
+            },
+            count: None,
+        },
+        // 11: simulation regions uniform (agents stay inside their region)
+        BindGroupLayoutEntry {
+            binding: 11,
+            visibility: ShaderStages::COMPUTE,
+            ty: BindingType::Buffer {
+                ty: BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        },
+        // 12: pheromone layer params (deposit clamp to max_value)
+        BindGroupLayoutEntry {
+            binding: 12,
+            visibility: ShaderStages::COMPUTE,
+            ty: BindingType::Buffer {
+                ty: BufferBindingType::Storage { read_only: true },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        },
+        // 14: avoidance grid counts (atomics; written by bin_agents, read by update_agents)
+        BindGroupLayoutEntry {
+            binding: 14,
+            visibility: ShaderStages::COMPUTE,
+            ty: BindingType::Buffer {
+                ty: BufferBindingType::Storage { read_only: false },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        },
     ];
     let texture_bind_group_layout = render_device.create_bind_group_layout(
         Some("AgentSimBindGroupLayout"),
@@ -186,6 +324,50 @@ fn init_agent_sim_pipeline(
         layout: vec![texture_bind_group_layout.clone()],
         shader: agents_shader.clone(),
         entry_point: Some(Cow::from("update_agents")),
+        shader_defs: workgroup_shader_defs(),
+        ..default()
+    });
+    let agent_grid_pipeline = pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+        layout: vec![texture_bind_group_layout.clone()],
+        shader: agents_shader.clone(),
+        entry_point: Some(Cow::from("bin_agents")),
+        shader_defs: workgroup_shader_defs(),
+        ..default()
+    });
+
+    // Agent dots: agents (0), globals (4), species (5), PheroControl (9),
+    // display texture (13, read-write so additive dots can accumulate)
+    let agent_dots_layout = render_device.create_bind_group_layout(
+        Some("AgentDotsBindGroupLayout"),
+        &[
+            entries[0].clone(),
+            entries[1].clone(),
+            entries[2].clone(),
+            entries[5].clone(),
+            BindGroupLayoutEntry {
+                binding: 13,
+                visibility: ShaderStages::COMPUTE,
+                ty: BindingType::StorageTexture {
+                    access: StorageTextureAccess::ReadWrite,
+                    format: TextureFormat::Rgba32Float,
+                    view_dimension: TextureViewDimension::D2,
+                },
+                count: None,
+            },
+        ],
+    );
+    let agent_dots_pipeline = pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+        layout: vec![agent_dots_layout.clone()],
+        shader: agents_shader.clone(),
+        entry_point: Some(Cow::from("draw_agent_dots")),
+        shader_defs: workgroup_shader_defs(),
+        ..default()
+    });
+    let sensor_markers_pipeline = pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+        layout: vec![agent_dots_layout.clone()],
+        shader: agents_shader.clone(),
+        entry_point: Some(Cow::from("draw_sensor_markers")),
+        shader_defs: workgroup_shader_defs(),
         ..default()
     });
 
@@ -194,18 +376,41 @@ fn init_agent_sim_pipeline(
         phero_array_env_layout,
         diffuse_array_pipeline,
         input_array_pipeline,
+        emitter_array_pipeline,
         phero_array_comp_layout,
         composite_array_pipeline,
     ) = init_pheromone_array_pipelines(&render_device, &asset_server, &pipeline_cache);
+    let (phero_clear_layout, clear_array_pipeline) =
+        init_pheromone_clear_pipeline(&render_device, &asset_server, &pipeline_cache);
+    let (phero_probe_layout, probe_array_pipeline, stats_array_pipeline) =
+        init_pheromone_probe_pipeline(&render_device, &asset_server, &pipeline_cache);
+    let (display_encode_layout, display_encode_pipeline) =
+        init_display_encode_pipeline(&render_device, &asset_server, &pipeline_cache);
+    let (display_fade_layout, display_fade_pipeline) =
+        init_display_fade_pipeline(&render_device, &asset_server, &pipeline_cache);
 
     commands.insert_resource(AgentSimPipeline {
         texture_bind_group_layout,
         agent_sim_pipeline,
+        agent_grid_pipeline,
         phero_array_env_layout,
         diffuse_array_pipeline,
         input_array_pipeline,
+        emitter_array_pipeline,
         phero_array_comp_layout,
         composite_array_pipeline,
+        phero_clear_layout,
+        clear_array_pipeline,
+        phero_probe_layout,
+        probe_array_pipeline,
+        stats_array_pipeline,
+        agent_dots_layout,
+        agent_dots_pipeline,
+        sensor_markers_pipeline,
+        display_encode_layout,
+        display_encode_pipeline,
+        display_fade_layout,
+        display_fade_pipeline,
     });
 }
 
@@ -229,6 +434,7 @@ fn prepare_bind_group(
     let globals = world.resource::<GlobalUniforms>();
     let phero_cfg = world.resource::<PheromoneConfig>();
     let layer_params = world.resource::<crate::resources::PheromoneLayerParamsBuffer>();
+    let obstacle_mask = world.resource::<crate::obstacles::ObstacleMask>();
 
     // Resolve GPU image handles
     let Some(image_a) = gpu_images.get(&pheromone_images.texture_a) else {
@@ -249,7 +455,8 @@ fn prepare_bind_group(
         // let mut pheromone_uniform_buffer = UniformBuffer::from(pheros);
         // pheromone_uniform_buffer.write_buffer(&render_device, &queue);
 
-    // Get pheromone array views for agents: choose "next" per ping
+    // Get pheromone array views for agents: each ping uses whichever array the
+    // env pass wrote that frame (`PingArray::env_write`)
     let Some(phero_prev_view) = gpu_images.get(&phero_arrays.prev).map(|g| &g.texture_view) else {
         return;
     };
@@ -257,16 +464,57 @@ fn prepare_bind_group(
         return;
     };
 
+    let Some(obstacle_view) = gpu_images.get(&obstacle_mask.image).map(|g| &g.texture_view) else {
+        return;
+    };
+
     // Extended pheromone dummy buffers and control uniform (use_extended=0 by default)
     let Some(species_layer_weights) = species_weights_res else { return; };
 
     let layer_count = phero_cfg.layer_count.max(1);
     let weights_buf_ref = &species_layer_weights.weights;
 
-    let phero_ctrl_uniform = crate::resources::PheroControlUniform { layer_count, _pad: UVec3::ZERO };
+    let bilinear_sensing = world.get_resource::<SensorSampling>().copied().unwrap_or_default().bilinear;
+    let agent_render = world.get_resource::<AgentRenderConfig>().copied().unwrap_or_default();
+    let phero_ctrl_uniform = crate::resources::PheroControlUniform {
+        layer_count,
+        bilinear_sensing: bilinear_sensing as u32,
+        paint_only_mask: phero_cfg.paint_only_mask(),
+        // From the bound buffer itself rather than `GlobalUniforms`, which can lag a species reload
+        species_count: SpeciesSettings::count_in(species_settings.buffer.size()),
+        shared_layer: phero_cfg.shared_layer.unwrap_or(NO_SHARED_LAYER),
+        dot_size: agent_render.point_size.clamp(1, MAX_AGENT_POINT_SIZE),
+        dot_additive: agent_render.additive as u32,
+        dot_strength: agent_render.additive_strength,
+    };
     let mut phero_ctrl_buffer = UniformBuffer::from(&phero_ctrl_uniform);
     phero_ctrl_buffer.write_buffer(&render_device, &queue);
 
+    // Region rects shared by the agent and brush passes
+    let regions = world.get_resource::<SimRegions>().map(|r| r.0.as_slice()).unwrap_or(&[]);
+    let regions_uniform = RegionsUniform::from_regions(regions);
+    let mut regions_buffer = UniformBuffer::from(&regions_uniform);
+    regions_buffer.write_buffer(&render_device, &queue);
+
+    // Avoidance grid: one count per cell per species slot, sized like the
+    // `species_count` the shader strides by. Zeroed on creation; the node
+    // clears it each frame before binning.
+    let grid_len =
+        crate::agents::avoid_grid_len(world.resource::<SimulationSize>().0, phero_ctrl_uniform.species_count);
+    let grid_buffer = match world.get_resource::<AgentGridBuffer>() {
+        Some(grid) if grid.len == grid_len => grid.buffer.clone(),
+        _ => {
+            let buffer = render_device.create_buffer(&BufferDescriptor {
+                label: Some("Agent avoidance grid"),
+                size: grid_len as u64 * std::mem::size_of::<u32>() as u64,
+                usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            commands.insert_resource(AgentGridBuffer { buffer: buffer.clone(), len: grid_len });
+            buffer
+        }
+    };
+
     // Build bind group entries for group(0)
     let entries0 = vec![
         BindGroupEntry {
@@ -289,10 +537,9 @@ fn prepare_bind_group(
                 size: None,
             }),
         },
-        // For ping index 0, env writes to next, so agents should read/write next
         BindGroupEntry {
             binding: 6,
-            resource: BindingResource::TextureView(phero_next_view),
+            resource: BindingResource::TextureView(PingArray::env_write(0).view(phero_prev_view, phero_next_view)),
         },
         BindGroupEntry {
             binding: 7,
@@ -303,6 +550,13 @@ fn prepare_bind_group(
             }),
         },
         BindGroupEntry { binding: 9, resource: phero_ctrl_buffer.binding().unwrap() },
+        BindGroupEntry {
+            binding: 10,
+            resource: BindingResource::TextureView(obstacle_view),
+        },
+        BindGroupEntry { binding: 11, resource: regions_buffer.binding().unwrap() },
+        BindGroupEntry { binding: 12, resource: layer_params.buffer.as_entire_binding() },
+        BindGroupEntry { binding: 14, resource: grid_buffer.as_entire_binding() },
     ];
 
     let bind_group_0 =
@@ -329,10 +583,9 @@ fn prepare_bind_group(
                 size: None,
             }),
         },
-        // For ping index 1, env writes to prev, so agents should read/write prev
         BindGroupEntry {
             binding: 6,
-            resource: BindingResource::TextureView(phero_prev_view),
+            resource: BindingResource::TextureView(PingArray::env_write(1).view(phero_prev_view, phero_next_view)),
         },
         BindGroupEntry {
             binding: 7,
@@ -343,6 +596,13 @@ fn prepare_bind_group(
             }),
         },
         BindGroupEntry { binding: 9, resource: phero_ctrl_buffer.binding().unwrap() },
+        BindGroupEntry {
+            binding: 10,
+            resource: BindingResource::TextureView(obstacle_view),
+        },
+        BindGroupEntry { binding: 11, resource: regions_buffer.binding().unwrap() },
+        BindGroupEntry { binding: 12, resource: layer_params.buffer.as_entire_binding() },
+        BindGroupEntry { binding: 14, resource: grid_buffer.as_entire_binding() },
     ];
 
     let bind_group_1 =
@@ -350,15 +610,103 @@ fn prepare_bind_group(
 
     commands.insert_resource(AgentSimImageBindGroups([bind_group_0, bind_group_1]));
 
-    // Brush control uniform for input pass
+    // Dots go onto the display texture the composite writes for each ping
+    // (ping 0 -> texture_b, ping 1 -> texture_a; see `create_phero_array_bind_groups`)
+    let agent_dots_groups = [view_b, view_a].map(|display_view| {
+        render_device.create_bind_group(
+            None,
+            &pipeline.agent_dots_layout,
+            &[
+                BindGroupEntry { binding: 0, resource: agent_gpu_buffer.buffer.as_entire_binding() },
+                BindGroupEntry { binding: 4, resource: global_uniform_buffer.binding().unwrap() },
+                BindGroupEntry { binding: 5, resource: species_settings.buffer.as_entire_binding() },
+                BindGroupEntry { binding: 9, resource: phero_ctrl_buffer.binding().unwrap() },
+                BindGroupEntry { binding: 13, resource: BindingResource::TextureView(display_view) },
+            ],
+        )
+    });
+    commands.insert_resource(AgentDotsBindGroups(agent_dots_groups));
+
+    if let Some(encode_groups) = create_display_encode_bind_groups(
+        &render_device,
+        gpu_images,
+        &pheromone_images.display_srgb,
+        &pipeline.display_encode_layout,
+        view_a,
+        view_b,
+    ) {
+        commands.insert_resource(DisplayEncodeBindGroups(encode_groups));
+    }
+
+    // Brush control uniform for input pass: the held button's bound layer;
+    // beacon mode stamps (left) or erases (right) its own layer instead
+    let brush_mode = world.get_resource::<BrushMode>().copied().unwrap_or_default();
+    let beacon = world.get_resource::<BeaconSettings>().copied().unwrap_or_default();
+    let held = world.get_resource::<crate::input::MouseButtonState>().map_or([false; 3], |b| b.held());
+    let stroke = if brush_mode == BrushMode::Beacon {
+        let [left, right, _] = held;
+        (left || right).then(|| (beacon.layer.min(phero_cfg.layer_count.max(1) - 1), !left))
+    } else {
+        phero_cfg.brush_stroke(held)
+    };
+    let (target_layer, erase) = stroke.unwrap_or((phero_cfg.brush_target_layer, false));
     let brush_uniform = crate::resources::BrushControlUniform {
-        target_layer: phero_cfg.brush_target_layer,
-        _mode: 0,
-        _pad: UVec2::ZERO,
+        target_layer,
+        falloff: world.get_resource::<BrushFalloff>().copied().unwrap_or_default() as u32,
+        beacon: (brush_mode == BrushMode::Beacon) as u32,
+        beacon_radius: beacon.radius,
+        beacon_amplitude: beacon.amplitude,
+        border_layer: phero_cfg.universal_hate_layers.first().copied().unwrap_or(0),
+        active: stroke.is_some() as u32,
+        erase: erase as u32,
     };
     let mut brush_uniform_buffer = UniformBuffer::from(&brush_uniform);
     brush_uniform_buffer.write_buffer(&render_device, &queue);
 
+    // Composite control uniform for the display pass
+    let composite_settings = world.get_resource::<CompositeSettings>().cloned().unwrap_or_default();
+    let background = world.get_resource::<Background>().copied().unwrap_or_default();
+    let composite_uniform = CompositeControlUniform {
+        blend_mode: composite_settings.blend_mode as u32,
+        exposure: composite_settings.exposure,
+        tonemap: composite_settings.tonemap as u32,
+        transparent_background: background.transparent as u32,
+        background: background.linear(),
+        solo: composite_settings.solo_layer as u32,
+        solo_layer: phero_cfg.brush_target_layer,
+        _pad: UVec2::ZERO,
+    };
+    let mut composite_uniform_buffer = UniformBuffer::from(&composite_uniform);
+    composite_uniform_buffer.write_buffer(&render_device, &queue);
+
+    // Agent view fade: keep what a `fade_rate` per second decay leaves after
+    // this frame's delta
+    let agent_view = world.get_resource::<AgentView>().copied().unwrap_or_default();
+    let fade_uniform = DisplayFadeUniform {
+        background: background.linear(),
+        keep: 1.0 - crate::setup::per_frame_factor(agent_view.fade_rate, globals.delta_time),
+    };
+    let mut fade_uniform_buffer = UniformBuffer::from(&fade_uniform);
+    fade_uniform_buffer.write_buffer(&render_device, &queue);
+    commands.insert_resource(DisplayFadeBindGroups(create_display_fade_bind_groups(
+        &render_device,
+        &pipeline.display_fade_layout,
+        view_a,
+        view_b,
+        &fade_uniform_buffer,
+    )));
+
+    // Emitter list, rebuilt each frame (it is small and edits are rare). A
+    // zeroed placeholder keeps the binding valid with none; the node skips
+    // the dispatch then.
+    let emitters = world.get_resource::<Emitters>().map(|e| e.0.as_slice()).unwrap_or(&[]);
+    let placeholder = [Emitter { pos: Vec2::ZERO, layer: 0, amount: 0.0 }];
+    let emitters_buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+        label: Some("Emitters"),
+        contents: bytemuck::cast_slice(if emitters.is_empty() { &placeholder } else { emitters }),
+        usage: BufferUsages::STORAGE,
+    });
+
     // Create array-based pheromone bind groups targeting the current ping outputs
     if let Some((env_ping, comp_ping)) = create_phero_array_bind_groups(
         &render_device,
@@ -371,10 +719,50 @@ fn prepare_bind_group(
         &global_uniform_buffer,
         &layer_params.buffer,
         &brush_uniform_buffer,
+        &composite_uniform_buffer,
+        &regions_buffer,
+        &emitters_buffer,
     ) {
         commands.insert_resource(crate::resources::PheroArrayEnvBindGroups(env_ping));
         commands.insert_resource(crate::resources::PheroArrayCompositeBindGroups(comp_ping));
     }
+
+    if let Some(clear_groups) = create_phero_clear_bind_groups(
+        &render_device,
+        gpu_images,
+        phero_arrays,
+        &pipeline.phero_clear_layout,
+    ) {
+        commands.insert_resource(PheroArrayClearBindGroups(clear_groups));
+    }
+
+    if let Some(probe) = world.get_resource::<crate::probe::PheromoneProbeBuffer>()
+        && let Some(probe_buffer) = world.resource::<RenderAssets<GpuShaderStorageBuffer>>().get(&probe.buffer)
+        && let Some(probe_groups) = create_phero_probe_bind_groups(
+            &render_device,
+            gpu_images,
+            phero_arrays,
+            &pipeline.phero_probe_layout,
+            &probe_buffer.buffer,
+            &global_uniform_buffer,
+        )
+    {
+        commands.insert_resource(PheroArrayProbeBindGroups(probe_groups));
+    }
+
+    if let Some(stats) = world.get_resource::<crate::stats::PheromoneStatsBuffer>()
+        && let Some(stats_buffer) = world.resource::<RenderAssets<GpuShaderStorageBuffer>>().get(&stats.buffer)
+        && let Some(stats_groups) = create_phero_probe_bind_groups(
+            &render_device,
+            gpu_images,
+            phero_arrays,
+            &pipeline.phero_probe_layout,
+            &stats_buffer.buffer,
+            &global_uniform_buffer,
+        )
+    {
+        commands.insert_resource(PheroArrayStatsBindGroups(stats_groups));
+    }
 }
 
 enum AgentSimState {
@@ -423,16 +811,61 @@ impl render_graph::Node for AgentSimNode {
                     pipeline_cache.get_compute_pipeline_state(pipeline.input_array_pipeline),
                     CachedPipelineState::Ok(_)
                 );
+                let array_emitter_ok = matches!(
+                    pipeline_cache.get_compute_pipeline_state(pipeline.emitter_array_pipeline),
+                    CachedPipelineState::Ok(_)
+                );
                 let array_comp_ok = matches!(
                     pipeline_cache.get_compute_pipeline_state(pipeline.composite_array_pipeline),
                     CachedPipelineState::Ok(_)
                 );
+                let array_clear_ok = matches!(
+                    pipeline_cache.get_compute_pipeline_state(pipeline.clear_array_pipeline),
+                    CachedPipelineState::Ok(_)
+                );
+                let array_probe_ok = matches!(
+                    pipeline_cache.get_compute_pipeline_state(pipeline.probe_array_pipeline),
+                    CachedPipelineState::Ok(_)
+                );
+                let array_stats_ok = matches!(
+                    pipeline_cache.get_compute_pipeline_state(pipeline.stats_array_pipeline),
+                    CachedPipelineState::Ok(_)
+                );
+                let agent_grid_ok = matches!(
+                    pipeline_cache.get_compute_pipeline_state(pipeline.agent_grid_pipeline),
+                    CachedPipelineState::Ok(_)
+                );
+                let agent_dots_ok = matches!(
+                    pipeline_cache.get_compute_pipeline_state(pipeline.agent_dots_pipeline),
+                    CachedPipelineState::Ok(_)
+                );
+                let sensor_markers_ok = matches!(
+                    pipeline_cache.get_compute_pipeline_state(pipeline.sensor_markers_pipeline),
+                    CachedPipelineState::Ok(_)
+                );
+                let display_encode_ok = matches!(
+                    pipeline_cache.get_compute_pipeline_state(pipeline.display_encode_pipeline),
+                    CachedPipelineState::Ok(_)
+                );
+                let display_fade_ok = matches!(
+                    pipeline_cache.get_compute_pipeline_state(pipeline.display_fade_pipeline),
+                    CachedPipelineState::Ok(_)
+                );
                 if diffuse_ok
                     && copy_ok
                     && input_ok
                     && array_diff_ok
                     && array_input_ok
+                    && array_emitter_ok
                     && array_comp_ok
+                    && array_clear_ok
+                    && array_probe_ok
+                    && array_stats_ok
+                    && agent_grid_ok
+                    && agent_dots_ok
+                    && sensor_markers_ok
+                    && display_encode_ok
+                    && display_fade_ok
                 {
                     self.state = AgentSimState::Update(0);
                 }
@@ -462,8 +895,20 @@ impl render_graph::Node for AgentSimNode {
             world.get_resource::<crate::resources::PheroArrayCompositeBindGroups>();
         let pipeline_cache = world.resource::<PipelineCache>();
         let pipeline = world.resource::<AgentSimPipeline>();
+        // Per-pass spans; no-ops unless render diagnostics are enabled
+        let diagnostics = render_context.diagnostic_recorder();
         // Using fixed pheromone bindings in group(0); no separate group(1)
 
+        // One-shot carry-over from an outgrown agent buffer; queued after the
+        // frames that used it and before anything reads the new one
+        if let Some((old, bytes)) = world
+            .get_resource::<crate::agents::AgentBufferGrowRequest>()
+            .and_then(|g| g.pending.as_ref())
+        {
+            let agents = &world.resource::<crate::agents::AgentGpuBuffer>().buffer;
+            render_context.command_encoder().copy_buffer_to_buffer(old, 0, agents, 0, *bytes);
+        }
+
         // The render node alternates between update indices 0 and 1 each
         // frame; those indices are used to select which ping is "prev" and
         // which is "next" for the array-based pheromone passes.
@@ -476,8 +921,9 @@ impl render_graph::Node for AgentSimNode {
                     return Ok(());
                 };
 
-                let groups_x = SIZE.x.div_ceil(WORKGROUP_SIZE);
-                let groups_y = SIZE.y.div_ceil(WORKGROUP_SIZE);
+                let sim_size = world.resource::<SimulationSize>().0;
+                let groups_x = dispatch_groups(sim_size.x, WORKGROUP_SIZE);
+                let groups_y = dispatch_groups(sim_size.y, WORKGROUP_SIZE);
                 let layer_count = world
                     .get_resource::<PheromoneConfig>()
                     .map(|c| c.layer_count)
@@ -486,6 +932,55 @@ impl render_graph::Node for AgentSimNode {
 
                 let run_config = world.resource::<AgentSimRunConfig>(); // toggles for agents/array passes
 
+                // One-shot clear of both arrays before any pass reads them this frame
+                let clear_requested = world
+                    .get_resource::<ClearPheromonesRequest>()
+                    .is_some_and(|c| c.pending);
+                if clear_requested
+                    && let Some(clear_groups) = world.get_resource::<PheroArrayClearBindGroups>()
+                    && let Some(clear_array) =
+                        pipeline_cache.get_compute_pipeline(pipeline.clear_array_pipeline)
+                {
+                    let mut pass_clear = render_context
+                        .command_encoder()
+                        .begin_compute_pass(&ComputePassDescriptor::default());
+                    let span = diagnostics.pass_span(&mut pass_clear, PASS_CLEAR);
+                    pass_clear.set_pipeline(clear_array);
+                    for group in clear_groups.0.iter() {
+                        pass_clear.set_bind_group(0, group, &[]);
+                        pass_clear.dispatch_workgroups(groups_x, groups_y, layer_count);
+                    }
+                    span.end(&mut pass_clear);
+                }
+
+                // Layer moved up/down the stack: reorder the array diffusion reads.
+                // With diffusion on, the write array is rewritten from it below,
+                // so it stages the pair. With diffusion off (F2) nothing rewrites
+                // it, and agents, composite and probe read it this frame, so
+                // both arrays are swapped through a temporary texture instead.
+                if let Some((a, b)) = world.get_resource::<LayerSwapRequest>().and_then(|r| r.pending)
+                    && a.max(b) < layer_count
+                    && let Some(arrays) = world.get_resource::<crate::pheromones::PheromoneArrayImages>()
+                {
+                    let gpu_images = world.resource::<RenderAssets<GpuImage>>();
+                    if let (Some(read), Some(write)) = (
+                        gpu_images.get(PingArray::env_read(index).image(arrays)),
+                        gpu_images.get(PingArray::env_write(index).image(arrays)),
+                    ) {
+                        // Same condition the diffuse pass below runs under
+                        if run_config.run_diffuse && phero_array_env.is_some() {
+                            let encoder = render_context.command_encoder();
+                            swap_array_layers(encoder, &read.texture, &write.texture, a, b);
+                        } else {
+                            let render_device = render_context.render_device().clone();
+                            let encoder = render_context.command_encoder();
+                            for texture in [&read.texture, &write.texture] {
+                                swap_array_layers_staged(&render_device, encoder, texture, a, b);
+                            }
+                        }
+                    }
+                }
+
                 // Array-based pheromone env passes (diffuse then input) with z-dispatch
                 if let Some(arr_env) = phero_array_env {
                     let Some(diffuse_array) =
@@ -498,39 +993,119 @@ impl render_graph::Node for AgentSimNode {
                     else {
                         return Ok(());
                     };
-                    // Only begin a compute pass if at least one of the array passes is enabled
-                    if run_config.run_diffuse || run_config.run_copy_and_input {
+                    let Some(emitter_array) =
+                        pipeline_cache.get_compute_pipeline(pipeline.emitter_array_pipeline)
+                    else {
+                        return Ok(());
+                    };
+                    // Diffuse and input run as separate passes so each can be timed
+                    if run_config.run_diffuse {
+                        // Extra iterations alternate the env groups (B -> A, A -> B, ...),
+                        // so an odd count ends in this ping's write array. Auto
+                        // quality may cap the count below the requested one.
+                        let quality = world.get_resource::<crate::auto_quality::QualityLevel>().copied();
+                        let steps = quality.unwrap_or_default().diffuse_steps(run_config.diffuse_steps()) as usize;
                         let mut pass_arr = render_context
                             .command_encoder()
                             .begin_compute_pass(&ComputePassDescriptor::default());
-                        pass_arr.set_bind_group(0, &arr_env.0[index], &[]);
-                        if run_config.run_diffuse {
-                            pass_arr.set_pipeline(diffuse_array);
+                        let span = diagnostics.pass_span(&mut pass_arr, PASS_DIFFUSE);
+                        pass_arr.set_pipeline(diffuse_array);
+                        for step in 0..steps {
+                            pass_arr.set_bind_group(0, &arr_env.0[(index + step) % 2], &[]);
                             pass_arr.dispatch_workgroups(groups_x, groups_y, layer_count);
                         }
-                        if run_config.run_copy_and_input {
-                            pass_arr.set_pipeline(input_array);
-                            pass_arr.dispatch_workgroups(groups_x, groups_y, layer_count);
+                        span.end(&mut pass_arr);
+                        drop(pass_arr);
+                        // An even count ends in the read array; copy it over so
+                        // input, agents and composite see the result
+                        if steps % 2 == 0
+                            && let Some(arrays) = world.get_resource::<crate::pheromones::PheromoneArrayImages>()
+                        {
+                            let gpu_images = world.resource::<RenderAssets<GpuImage>>();
+                            if let (Some(src), Some(dst)) = (
+                                gpu_images.get(PingArray::env_read(index).image(arrays)),
+                                gpu_images.get(PingArray::env_write(index).image(arrays)),
+                            ) {
+                                render_context.command_encoder().copy_texture_to_texture(
+                                    src.texture.as_image_copy(),
+                                    dst.texture.as_image_copy(),
+                                    src.texture.size(),
+                                );
+                            }
                         }
                     }
+                    if run_config.run_copy_and_input {
+                        let mut pass_arr = render_context
+                            .command_encoder()
+                            .begin_compute_pass(&ComputePassDescriptor::default());
+                        let span = diagnostics.pass_span(&mut pass_arr, PASS_INPUT);
+                        pass_arr.set_bind_group(0, &arr_env.0[index], &[]);
+                        pass_arr.set_pipeline(input_array);
+                        pass_arr.dispatch_workgroups(groups_x, groups_y, layer_count);
+                        // Emitters stamp on top of the brush, one invocation each
+                        let emitter_count = world.get_resource::<Emitters>().map_or(0, |e| e.0.len() as u32);
+                        if emitter_count > 0 {
+                            pass_arr.set_pipeline(emitter_array);
+                            pass_arr.dispatch_workgroups(dispatch_groups(emitter_count, WORKGROUP_SIZE), 1, 1);
+                        }
+                        span.end(&mut pass_arr);
+                    }
                 }
 
+                // Whole agent buffer, including inactive spawn-brush headroom (skipped in the shader)
+                let agent_groups = dispatch_groups(
+                    world.resource::<crate::agents::AgentGpuBuffer>().capacity,
+                    AGENT_WORKGROUP_SIZE,
+                );
+                // Same-species avoidance: recount the grid from this frame's
+                // positions. Skipped entirely when no species avoids.
+                let any_avoidance = world.get_resource::<SpeciesGpuBuffer>().is_some_and(|s| s.any_avoidance);
+                if run_config.run_agents
+                    && any_avoidance
+                    && let Some(grid) = world.get_resource::<AgentGridBuffer>()
+                    && let Some(grid_pipeline) = pipeline_cache.get_compute_pipeline(pipeline.agent_grid_pipeline)
+                {
+                    let encoder = render_context.command_encoder();
+                    encoder.clear_buffer(&grid.buffer, 0, None);
+                    let mut pass_grid = encoder.begin_compute_pass(&ComputePassDescriptor::default());
+                    let span = diagnostics.pass_span(&mut pass_grid, PASS_AGENT_GRID);
+                    pass_grid.set_bind_group(0, &bind_groups[index], &[]);
+                    pass_grid.set_pipeline(grid_pipeline);
+                    pass_grid.dispatch_workgroups(agent_groups, 1, 1);
+                    span.end(&mut pass_grid);
+                }
                 if run_config.run_agents {
                     let mut pass2 = render_context
                         .command_encoder()
                         .begin_compute_pass(&ComputePassDescriptor::default());
+                    let span = diagnostics.pass_span(&mut pass2, PASS_AGENTS);
                     pass2.set_bind_group(0, &bind_groups[index], &[]);
                     // No group(1) needed
                     pass2.set_pipeline(agent_pipeline);
-                    let agent_groups =
-                        NUM_AGENTS.div_ceil(AGENT_WORKGROUP_SIZE);
                     pass2.dispatch_workgroups(agent_groups, 1, 1);
+                    span.end(&mut pass2);
                 }
 
                 // Legacy extract/composite removed
 
-                // Array-based composite (array -> RGBA). Note: writes to the bind-target chosen when creating array composite groups.
-                if let Some(arr_comp) = phero_array_comp {
+                // Agent view replaces the composite with a fade of last frame's
+                // display; the dots below then draw this frame's positions
+                let agent_view = world.get_resource::<AgentView>().is_some_and(|v| v.enabled);
+                if agent_view {
+                    if let Some(fade_groups) = world.get_resource::<DisplayFadeBindGroups>()
+                        && let Some(fade) = pipeline_cache.get_compute_pipeline(pipeline.display_fade_pipeline)
+                    {
+                        let mut pass_fade = render_context
+                            .command_encoder()
+                            .begin_compute_pass(&ComputePassDescriptor::default());
+                        let span = diagnostics.pass_span(&mut pass_fade, PASS_COMPOSITE);
+                        pass_fade.set_bind_group(0, &fade_groups.0[index], &[]);
+                        pass_fade.set_pipeline(fade);
+                        pass_fade.dispatch_workgroups(groups_x, groups_y, 1);
+                        span.end(&mut pass_fade);
+                    }
+                } else if let Some(arr_comp) = phero_array_comp {
+                    // Array-based composite (array -> RGBA). Note: writes to the bind-target chosen when creating array composite groups.
                     let Some(comp_array) =
                         pipeline_cache.get_compute_pipeline(pipeline.composite_array_pipeline)
                     else {
@@ -539,9 +1114,85 @@ impl render_graph::Node for AgentSimNode {
                     let mut pass_comp = render_context
                         .command_encoder()
                         .begin_compute_pass(&ComputePassDescriptor::default());
+                    let span = diagnostics.pass_span(&mut pass_comp, PASS_COMPOSITE);
                     pass_comp.set_bind_group(0, &arr_comp.0[index], &[]);
                     pass_comp.set_pipeline(comp_array);
                     pass_comp.dispatch_workgroups(groups_x, groups_y, 1);
+                    span.end(&mut pass_comp);
+                }
+
+                // Optional agent dots and sensor markers over the composited
+                // display; agent view always draws the dots
+                let draw_dots = run_config.draw_agent_dots || agent_view;
+                if (draw_dots || run_config.draw_sensors)
+                    && let Some(dots_groups) = world.get_resource::<AgentDotsBindGroups>()
+                    && let Some(dots) = pipeline_cache.get_compute_pipeline(pipeline.agent_dots_pipeline)
+                    && let Some(markers) = pipeline_cache.get_compute_pipeline(pipeline.sensor_markers_pipeline)
+                {
+                    let mut pass_dots = render_context
+                        .command_encoder()
+                        .begin_compute_pass(&ComputePassDescriptor::default());
+                    let span = diagnostics.pass_span(&mut pass_dots, PASS_AGENT_DOTS);
+                    pass_dots.set_bind_group(0, &dots_groups.0[index], &[]);
+                    if draw_dots {
+                        pass_dots.set_pipeline(dots);
+                        pass_dots.dispatch_workgroups(agent_groups, 1, 1);
+                    }
+                    // After the dots, so markers draw on top
+                    if run_config.draw_sensors {
+                        pass_dots.set_pipeline(markers);
+                        let marker_groups =
+                            dispatch_groups(crate::agents::SENSOR_MARKER_AGENTS, AGENT_WORKGROUP_SIZE);
+                        pass_dots.dispatch_workgroups(marker_groups, 1, 1);
+                    }
+                    span.end(&mut pass_dots);
+                }
+
+                // Optional 8-bit sRGB copy of the finished display for presentation
+                let srgb_display = world.get_resource::<SrgbDisplay>().is_some_and(|s| s.0);
+                if srgb_display
+                    && let Some(encode_groups) = world.get_resource::<DisplayEncodeBindGroups>()
+                    && let Some(encode) =
+                        pipeline_cache.get_compute_pipeline(pipeline.display_encode_pipeline)
+                {
+                    let mut pass_encode = render_context
+                        .command_encoder()
+                        .begin_compute_pass(&ComputePassDescriptor::default());
+                    let span = diagnostics.pass_span(&mut pass_encode, PASS_DISPLAY_ENCODE);
+                    pass_encode.set_bind_group(0, &encode_groups.0[index], &[]);
+                    pass_encode.set_pipeline(encode);
+                    pass_encode.dispatch_workgroups(groups_x, groups_y, 1);
+                    span.end(&mut pass_encode);
+                }
+
+                // Copy the composited array's values under the cursor for readback
+                if let Some(probe_groups) = world.get_resource::<PheroArrayProbeBindGroups>()
+                    && let Some(probe_array) =
+                        pipeline_cache.get_compute_pipeline(pipeline.probe_array_pipeline)
+                {
+                    let mut pass_probe = render_context
+                        .command_encoder()
+                        .begin_compute_pass(&ComputePassDescriptor::default());
+                    pass_probe.set_bind_group(0, &probe_groups.0[index], &[]);
+                    pass_probe.set_pipeline(probe_array);
+                    pass_probe.dispatch_workgroups(1, 1, 1);
+                }
+
+                // Optional per-layer min/max/mean, one workgroup per layer
+                let stats_enabled = world
+                    .get_resource::<crate::stats::PheromoneStatsEnabled>()
+                    .is_some_and(|e| e.0);
+                if stats_enabled
+                    && let Some(stats_groups) = world.get_resource::<PheroArrayStatsBindGroups>()
+                    && let Some(stats_array) =
+                        pipeline_cache.get_compute_pipeline(pipeline.stats_array_pipeline)
+                {
+                    let mut pass_stats = render_context
+                        .command_encoder()
+                        .begin_compute_pass(&ComputePassDescriptor::default());
+                    pass_stats.set_bind_group(0, &stats_groups.0[index], &[]);
+                    pass_stats.set_pipeline(stats_array);
+                    pass_stats.dispatch_workgroups(1, 1, layer_count.min(crate::probe::MAX_PROBE_LAYERS as u32));
                 }
 
                 // Legacy per-pheromone copy-only pass remains disabled (array path active now)
@@ -551,3 +1202,35 @@ impl render_graph::Node for AgentSimNode {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn workgroup_sizes_fit_limits_and_cover_dispatch() {
+        // wgpu's default max_compute_invocations_per_workgroup is 256
+        assert!(WORKGROUP_SIZE * WORKGROUP_SIZE <= 256);
+        assert!(AGENT_WORKGROUP_SIZE <= 256);
+        // The dispatch covers every texel/agent with less than one spare group
+        for (extent, size) in [
+            (crate::SIZE.x, WORKGROUP_SIZE),
+            (crate::SIZE.y, WORKGROUP_SIZE),
+            (crate::NUM_AGENTS, AGENT_WORKGROUP_SIZE),
+        ] {
+            let groups = dispatch_groups(extent, size);
+            assert!(groups * size >= extent);
+            assert!((groups - 1) * size < extent);
+        }
+        assert_eq!(dispatch_groups(1920, 16), 120);
+        assert_eq!(dispatch_groups(1080, 16), 68);
+    }
+
+    #[test]
+    fn workgroup_shader_defs_match_consts() {
+        let defs = workgroup_shader_defs();
+        assert!(defs.contains(&ShaderDefVal::UInt("WORKGROUP_SIZE".into(), WORKGROUP_SIZE)));
+        assert!(defs.contains(&ShaderDefVal::UInt("AGENT_WORKGROUP_SIZE".into(), AGENT_WORKGROUP_SIZE)));
+        assert!(defs.contains(&ShaderDefVal::Int("EMITTER_RADIUS".into(), EMITTER_RADIUS as i32)));
+    }
+}
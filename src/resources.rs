@@ -8,6 +8,22 @@
 use bevy::prelude::*;
 use bevy::render::extract_resource::ExtractResource;
 use bytemuck::{Pod, Zeroable};
+use serde::{Deserialize, Serialize};
+
+// Deposit targets per species beyond `emit_layer` (`SpeciesSettings.extra_emit_*`)
+pub const MAX_EXTRA_EMITS: usize = 4;
+
+// Largest accepted `Sensor::size`; keeps the per-sensor block at most 17x17
+pub const MAX_SENSOR_SIZE: f32 = 8.0;
+
+// Most sensors per species; matches MAX_SENSORS in agents.wgsl
+pub const MAX_SENSORS: u32 = 16;
+
+// Largest accepted deposit footprint radius; a stamp covers at most 9x9 texels
+pub const MAX_DEPOSIT_RADIUS: f32 = 4.0;
+
+// `SpeciesSettings.max_turn_per_step` default: large enough to never bind
+pub const DEFAULT_MAX_TURN: f32 = 1.0e6;
 
 // Species settings (moved from main)
 #[repr(C)]
@@ -17,15 +33,75 @@ pub struct SpeciesSettings {
     pub turn_speed: f32,
     pub sensor_angle_degrees: f32,
     pub sensor_offset_dst: f32,
+    /// Sensor radius in texels (see `Sensor::size`); truncated on the GPU.
     pub sensor_size: f32,
-    pub _pad0: f32,
-    pub _pad1: f32,
-    pub _pad2: f32,
+    // Sensors spread evenly over [-angle, +angle]; 3 is the classic L/F/R
+    pub sensor_count: u32,
+    /// How `emit_amount` is scaled per step (see `DepositMode`).
+    pub deposit_mode: u32,
+    /// Max random heading change per second (radians); 0 = deterministic steering.
+    pub wander_strength: f32,
     pub color: Vec4,
-    // New emission model: single target layer with a scalar amount
+    // New emission model: single target layer with a scalar amount. Not tied
+    // to the sensing weights; any layer can be written, sensed or not.
     pub emit_layer: u32,
     pub emit_amount: f32,
-    pub _pad_emit: UVec2,
+    /// Multiplier on the decay rate of `emit_layer` (CPU-side; folded into
+    /// the layer params via `LayerDecayBias`, mirrored here for layout).
+    pub emit_decay_bias: f32,
+    /// Nonzero: the sensor offset moves between `sensor_offset_dst` (strong
+    /// field) and `sensor_offset_max` (weak field) with the forward reading.
+    pub sensor_adaptive: u32,
+    pub sensor_offset_max: f32,
+    /// Cap on the heading change applied in one step (radians), so large
+    /// frame times can't spin agents around. Default is effectively no cap.
+    pub max_turn_per_step: f32,
+    /// `SteerMode` as u32.
+    pub steer_mode: u32,
+    /// Per-agent speed spread: each agent moves at `move_speed * (1 + j * r)`
+    /// with a fixed r in [-1, 1] derived from its index. 0 = uniform speed.
+    pub speed_jitter: f32,
+    /// Further (layer, amount) deposits next to `emit_layer`, using the same
+    /// `deposit_mode`; amount 0 marks an unused slot. These layers do not
+    /// get the species' `emit_decay_bias`.
+    pub extra_emit_layers: UVec4,
+    pub extra_emit_amounts: Vec4,
+    /// Lifespan in seconds: agents reaching it respawn at a random open spot
+    /// with a fresh heading. 0 = immortal.
+    pub max_age: f32,
+    /// Constant heading drift in radians per second, added after steering.
+    /// Positive turns clockwise on screen; 0 = symmetric.
+    pub turn_bias: f32,
+    /// Deposits are spread over a disc of this radius in texels (< 1 = one
+    /// texel); the total amount deposited is unchanged.
+    pub deposit_radius: f32,
+    /// `ResponseCurve` as u32, applied to each layer's value before weighting.
+    pub response_curve: u32,
+    /// Max heading change per second (radians) steering away from crowded
+    /// neighbouring cells of the same species (see `agents::AVOID_CELL_SIZE`).
+    /// 0 = off: the species is neither binned nor steered.
+    pub avoid_strength: f32,
+    /// Deposit only while the weighted reading straight ahead (the centre
+    /// sensor) is at least this, so agents reinforce trails they are following
+    /// instead of covering everything. 0 = always deposit.
+    pub deposit_threshold: f32,
+    /// Zero: the species' agents hold still and deposit nothing (toggled at
+    /// runtime with `U`, see `species::SpeciesEnabled`).
+    pub enabled: u32,
+    /// Rate (per second) at which deposits weaken while an agent sits on
+    /// strong `emit_layer` values, and recover on weak ones: each agent's
+    /// `fatigue` moves by `deposit_fatigue * (2 * level - 1) * dt` within
+    /// [0, 1], level being the emit layer here clamped to [0, 1], and scales
+    /// its deposits by `1 - fatigue`. 0 = off.
+    pub deposit_fatigue: f32,
+    /// Half-arc in degrees the `sensor_count` sensors spread evenly over;
+    /// 0 = use `sensor_angle_degrees` (the classic L/F/R angle).
+    pub sensor_spread_degrees: f32,
+    /// Amount per second deposited into `PheromoneConfig::shared_layer`
+    /// (same deposit mode, radius and fatigue as `emit_amount`); 0 = none.
+    pub shared_emit_amount: f32,
+    // Keeps the stride a multiple of 16 bytes
+    pub _pad: [u32; 2],
 }
 impl Default for SpeciesSettings {
     fn default() -> Self {
@@ -35,19 +111,83 @@ impl Default for SpeciesSettings {
             sensor_angle_degrees: 30.0,
             sensor_offset_dst: 35.0,
             sensor_size: 1.0,
-            _pad0: 0.0,
-            _pad1: 0.0,
-            _pad2: 0.0,
+            sensor_count: 3,
+            deposit_mode: DepositMode::Constant as u32,
+            wander_strength: 0.0,
             color: Vec4::new(1.0, 1.0, 1.0, 1.0),
             emit_layer: 0,
             emit_amount: 0.0,
-            _pad_emit: UVec2::ZERO,
+            emit_decay_bias: 1.0,
+            sensor_adaptive: 0,
+            sensor_offset_max: 35.0,
+            max_turn_per_step: DEFAULT_MAX_TURN,
+            steer_mode: SteerMode::Sensors as u32,
+            speed_jitter: 0.0,
+            extra_emit_layers: UVec4::ZERO,
+            extra_emit_amounts: Vec4::ZERO,
+            max_age: 0.0,
+            turn_bias: 0.0,
+            deposit_radius: 0.0,
+            response_curve: ResponseCurve::Linear as u32,
+            avoid_strength: 0.0,
+            deposit_threshold: 0.0,
+            enabled: 1,
+            deposit_fatigue: 0.0,
+            sensor_spread_degrees: 0.0,
+            shared_emit_amount: 0.0,
+            _pad: [0; 2],
         }
     }
 }
 
+impl SpeciesSettings {
+    /// Whole entries in a species buffer of `bytes` bytes.
+    pub fn count_in(bytes: u64) -> u32 {
+        (bytes / std::mem::size_of::<Self>() as u64) as u32
+    }
+}
+
+// Deposit scaling for a species (stored as `SpeciesSettings.deposit_mode`)
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DepositMode {
+    /// `emit_amount` per second regardless of movement. Legacy default.
+    #[default]
+    Constant = 0,
+    /// Scaled by the distance actually moved this step relative to
+    /// `move_speed * dt`, so blocked agents deposit less.
+    SpeedScaled = 1,
+}
+
+// How a species picks its heading (stored as `SpeciesSettings.steer_mode`)
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SteerMode {
+    /// Discrete sensors fanned out ahead; turn toward the strongest side.
+    #[default]
+    Sensors = 0,
+    /// Finite-difference gradient of the weighted field at the agent; turn
+    /// toward it at up to `turn_speed` (repellent layers push away).
+    Gradient = 1,
+}
+
+// How sensed layer values map to signal before weighting (stored as
+// `SpeciesSettings.response_curve`). The nonlinear curves make faint trails
+// count for relatively more and saturate at high concentration.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ResponseCurve {
+    /// `value`, the classic response.
+    #[default]
+    Linear = 0,
+    /// `sqrt(value)`.
+    Sqrt = 1,
+    /// `ln(1 + value)`.
+    Log = 2,
+}
+
 // Runtime-configurable pheromone system options. Defaults preserve current behavior.
-#[derive(Resource, Clone, ExtractResource)]
+#[derive(Resource, Clone, ExtractResource, Serialize, Deserialize, Debug, PartialEq)]
 pub struct PheromoneConfig {
     /// Number of pheromone layers (texture array depth). Default 3 to match legacy RGB.
     pub layer_count: u32,
@@ -56,7 +196,122 @@ pub struct PheromoneConfig {
     /// Layers that are universally attractive (positive weight for all species) and paint-only.
     pub universal_love_layers: Vec<u32>,
     /// Layers that are universally repulsive (negative weight for all species) and paint-only.
+    /// Their decay is unaffected by species `emit_decay_bias` unless some species emits there.
     pub universal_hate_layers: Vec<u32>,
+    /// Diffusion and flow neighbors wrap to the opposite edge instead of
+    /// clamping, so pheromone does not pile up at the borders. Diffusion
+    /// always wraps while agents do (`BoundaryMode::Wrap`, toggled with B);
+    /// this also wraps it under bouncing edges. See `diffusion_wraps`.
+    #[serde(default)]
+    pub wrap_diffusion: bool,
+    /// Layer painted by the left, right and middle mouse buttons. `BRUSH_LAYER`
+    /// follows `brush_target_layer`; on the right button it erases it instead
+    /// (the original right click), while any explicit layer is painted.
+    #[serde(default = "default_brush_bindings")]
+    pub brush_bindings: [u32; 3],
+    /// Common trail every species deposits its `shared_emit_amount` into, on
+    /// top of its own emits, for coordination fields all species can follow.
+    /// `None` = no shared layer.
+    #[serde(default)]
+    pub shared_layer: Option<u32>,
+}
+
+/// `PheromoneConfig::brush_bindings` entry meaning "the current brush layer".
+pub const BRUSH_LAYER: u32 = u32::MAX;
+
+fn default_brush_bindings() -> [u32; 3] {
+    [BRUSH_LAYER; 3]
+}
+
+impl PheromoneConfig {
+    /// Whether diffusion neighbors wrap under `boundary`: always in a
+    /// toroidal world, so trails cross the seam with the agents that laid
+    /// them, and with bouncing edges when `wrap_diffusion` asks for it.
+    pub fn diffusion_wraps(&self, boundary: BoundaryMode) -> bool {
+        self.wrap_diffusion || boundary == BoundaryMode::Wrap
+    }
+
+    /// Bring every layer index into `0..layer_count`, returning one message
+    /// per fix: a zero layer count becomes 1, the brush layer is clamped,
+    /// out-of-range or duplicate love/hate entries and out-of-range button
+    /// bindings (back to `BRUSH_LAYER`) and shared layer are dropped, and a
+    /// layer listed as both love and hate stays hate only (hate already wins
+    /// when weighting).
+    pub fn validate(&mut self) -> Vec<String> {
+        let mut fixes = Vec::new();
+        if self.layer_count == 0 {
+            fixes.push("layer_count 0 raised to 1".to_string());
+            self.layer_count = 1;
+        }
+        let count = self.layer_count;
+        if self.brush_target_layer >= count {
+            fixes.push(format!("brush_target_layer {} clamped to {}", self.brush_target_layer, count - 1));
+            self.brush_target_layer = count - 1;
+        }
+        let lists = [
+            ("universal_hate_layers", &mut self.universal_hate_layers),
+            ("universal_love_layers", &mut self.universal_love_layers),
+        ];
+        for (name, layers) in lists {
+            let mut kept: Vec<u32> = Vec::with_capacity(layers.len());
+            for &layer in layers.iter() {
+                if layer >= count {
+                    fixes.push(format!("{name}: layer {layer} dropped (only {count} layers)"));
+                } else if kept.contains(&layer) {
+                    fixes.push(format!("{name}: duplicate layer {layer} dropped"));
+                } else {
+                    kept.push(layer);
+                }
+            }
+            *layers = kept;
+        }
+        let hate = &self.universal_hate_layers;
+        self.universal_love_layers.retain(|layer| {
+            let overlaps = hate.contains(layer);
+            if overlaps {
+                fixes.push(format!("layer {layer} is both love and hate; kept as hate"));
+            }
+            !overlaps
+        });
+        for (button, binding) in ["left", "right", "middle"].iter().zip(self.brush_bindings.iter_mut()) {
+            if *binding != BRUSH_LAYER && *binding >= count {
+                fixes.push(format!("{button} button layer {binding} reset to the brush layer"));
+                *binding = BRUSH_LAYER;
+            }
+        }
+        if let Some(layer) = self.shared_layer.filter(|&l| l >= count) {
+            fixes.push(format!("shared_layer {layer} dropped (only {count} layers)"));
+            self.shared_layer = None;
+        }
+        fixes
+    }
+
+    /// Layer to paint and whether to erase it for the held `[left, right,
+    /// middle]` buttons, or `None` with none held. Left wins over middle,
+    /// middle over right.
+    pub fn brush_stroke(&self, held: [bool; 3]) -> Option<(u32, bool)> {
+        let resolve = |binding: u32| if binding == BRUSH_LAYER { self.brush_target_layer } else { binding };
+        let [left, right, middle] = held;
+        if left {
+            Some((resolve(self.brush_bindings[0]), false))
+        } else if middle {
+            Some((resolve(self.brush_bindings[2]), false))
+        } else if right {
+            Some((resolve(self.brush_bindings[1]), self.brush_bindings[1] == BRUSH_LAYER))
+        } else {
+            None
+        }
+    }
+
+    /// Bitmask of the paint-only (universal love/hate) layers, bit `i` for
+    /// layer `i`. The agent shader drops species deposits into these layers.
+    pub fn paint_only_mask(&self) -> u32 {
+        self.universal_love_layers
+            .iter()
+            .chain(&self.universal_hate_layers)
+            .filter(|&&layer| layer < 32)
+            .fold(0, |mask, &layer| mask | (1 << layer))
+    }
 }
 
 impl Default for PheromoneConfig {
@@ -66,6 +321,9 @@ impl Default for PheromoneConfig {
             brush_target_layer: 0,
             universal_love_layers: Vec::new(),
             universal_hate_layers: Vec::new(),
+            wrap_diffusion: false,
+            brush_bindings: default_brush_bindings(),
+            shared_layer: None,
         }
     }
 }
@@ -86,6 +344,19 @@ pub struct GlobalUniforms {
     pub right_button_pressed: u32,
     pub species_offset: u32,
     pub species_count: u32,
+    pub wrap_edges: u32,
+    /// `PheromoneConfig::diffusion_wraps` for the current `BoundaryMode`.
+    pub wrap_diffusion: u32,
+}
+
+// How agents treat the screen edges. Mirrored into `GlobalUniforms.wrap_edges`.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BoundaryMode {
+    /// Reflect heading and position at the edges.
+    #[default]
+    Bounce,
+    /// Toroidal world: leaving one edge re-enters on the opposite one.
+    Wrap,
 }
 
 // Removed legacy PheromoneUniforms (RGBA-era). Diffusion/decay now live in per-layer params.
@@ -96,9 +367,86 @@ pub struct GlobalUniforms {
 pub struct PheromoneLayerParam {
     pub diffusion: f32,
     pub decay: f32,
-    pub _pad0: f32,
-    pub _pad1: f32,
+    /// Blur kernel used by `diffuse_phero_array` (see `DiffusionKernel`).
+    pub kernel: u32,
+    /// Nonzero: `diffuse_phero_array` copies the layer unchanged (no
+    /// diffusion or decay), so painted values persist as a static field.
+    pub frozen: u32,
     pub color: Vec4,
+    /// Cap applied by brush painting and agent deposits; <= 0 = unbounded.
+    pub max_value: f32,
+    /// Weight of diagonal neighbors relative to edge neighbors in the blur;
+    /// <= 0 keeps the kernel's own (box: none, Gaussian: 0.5). 1/sqrt(2)
+    /// gives more isotropic spreading.
+    pub diagonal_weight: f32,
+    /// Zero: `diffuse_phero_array` skips the blur (decay still applies).
+    pub diffuse_enabled: u32,
+    /// Zero: `diffuse_phero_array` skips decay (diffusion still applies).
+    pub decay_enabled: u32,
+    /// Direction the layer drifts in (normalized on the GPU).
+    pub flow_dir: Vec2,
+    /// Drift speed along `flow_dir` in texels per second (at most one texel
+    /// per step); 0 keeps diffusion isotropic.
+    pub flow_strength: f32,
+    /// Baseline decay stops at: values above it decay toward it instead of
+    /// toward 0, leaving a faint ambient trail; lower values (and unpainted
+    /// ground) decay to 0 as usual. 0 = plain decay.
+    pub decay_floor: f32,
+    /// Brush strength on this layer: painting pulls toward this value
+    /// instead of 1.0 and beacons add this multiple of their amplitude.
+    /// Erasing is unaffected.
+    pub brush_amount: f32,
+    /// Nonzero: `diffuse_phero_array` trades a share of each texel with its
+    /// edge neighbors instead of blurring, and skips decay and flow, so the
+    /// layer's total only changes through painting and deposits.
+    pub conserve_mass: u32,
+    // Keeps the stride a multiple of 16 bytes
+    pub _pad: [u32; 2],
+}
+
+/// Per-layer decay multiplier derived from the species that emit into each
+/// layer (the mean of their `emit_decay_bias`; 1.0 for layers nobody emits
+/// into). Applied on top of the base decay in `update_layer_params_buffer`.
+///
+/// The bias is per layer, not per deposit: it also changes how fast brush
+/// paint fades. Layers in `paint_only_mask` (universal love/hate) always
+/// keep 1.0: agents never deposit there, even if a species names one.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct LayerDecayBias(pub Vec<f32>);
+
+impl LayerDecayBias {
+    pub fn from_species(species: &[SpeciesSettings], layer_count: u32, paint_only_mask: u32) -> Self {
+        let mut sum = vec![0.0f32; layer_count as usize];
+        let mut n = vec![0u32; layer_count as usize];
+        let paint_only = |layer: u32| layer < 32 && paint_only_mask & (1 << layer) != 0;
+        for s in species.iter().filter(|s| s.emit_amount != 0.0 && !paint_only(s.emit_layer)) {
+            if let Some(i) = (s.emit_layer < layer_count).then_some(s.emit_layer as usize) {
+                sum[i] += s.emit_decay_bias;
+                n[i] += 1;
+            }
+        }
+        Self(
+            sum.iter()
+                .zip(&n)
+                .map(|(&total, &count)| if count > 0 { total / count as f32 } else { 1.0 })
+                .collect(),
+        )
+    }
+
+    pub fn get(&self, layer: usize) -> f32 {
+        self.0.get(layer).copied().unwrap_or(1.0)
+    }
+}
+
+// Blur kernel selection for a pheromone layer (stored as `PheromoneLayerParam.kernel`)
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DiffusionKernel {
+    /// 5-tap box-style cross (center weighted 4, edge neighbors 1). Legacy default.
+    #[default]
+    Box = 0,
+    /// 3x3 Gaussian (1-2-1 separable weights, includes diagonals).
+    Gaussian = 1,
 }
 
 // Uniform used by agents to control extended pheromone path (layer count and enable flag)
@@ -106,33 +454,509 @@ pub struct PheromoneLayerParam {
 #[derive(Clone, Copy, Pod, Zeroable, bevy::render::render_resource::ShaderType)]
 pub struct PheroControlUniform {
     pub layer_count: u32,
-    pub _pad: bevy::math::UVec3,
+    /// Nonzero: sensors sample bilinearly (see `SensorSampling`).
+    pub bilinear_sensing: u32,
+    /// `PheromoneConfig::paint_only_mask`: layers agents must not deposit into.
+    pub paint_only_mask: u32,
+    /// Entries in the bound species buffer; shaders clamp species indices
+    /// below it so agents and species can't get out of sync into OOB reads.
+    pub species_count: u32,
+    /// `PheromoneConfig::shared_layer`, or `NO_SHARED_LAYER`.
+    pub shared_layer: u32,
+    /// `AgentRenderConfig::point_size`.
+    pub dot_size: u32,
+    /// Nonzero: dots add `dot_strength` of the species color instead of
+    /// overwriting.
+    pub dot_additive: u32,
+    pub dot_strength: f32,
+}
+
+/// `PheroControlUniform::shared_layer` without a shared layer.
+pub const NO_SHARED_LAYER: u32 = u32::MAX;
+
+/// How agent sensors read the pheromone array. Bilinear (the default) blends
+/// the four texels around the sensor's sub-pixel position so steering does
+/// not snap to the texel grid; turning it off saves three fetches per tap.
+#[derive(Resource, Clone, Copy, Debug, ExtractResource)]
+pub struct SensorSampling {
+    pub bilinear: bool,
+}
+
+impl Default for SensorSampling {
+    fn default() -> Self {
+        Self { bilinear: true }
+    }
+}
+
+/// Upper bound on the simulation time step in seconds. Frame hitches are
+/// simulated as this much time instead of the real gap, so fast species move
+/// at most `move_speed * max` per step and can't jump through walls or past
+/// trails. Applied to agent movement and layer diffusion/decay alike.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct MaxDeltaTime(pub f32);
+
+impl Default for MaxDeltaTime {
+    fn default() -> Self {
+        Self(1.0 / 30.0)
+    }
+}
+
+impl MaxDeltaTime {
+    /// Clamp a frame's delta to the cap (a non-positive cap disables it).
+    pub fn clamp(&self, dt: f32) -> f32 {
+        if self.0 > 0.0 { dt.min(self.0) } else { dt }
+    }
+}
+
+/// Where each frame's simulation step comes from. `Variable` follows the
+/// real frame time (capped by `MaxDeltaTime`); `Fixed` always steps the given
+/// seconds however long the frame took, so runs (recordings, `--bench`)
+/// evolve the same on any machine. `time_scale` applies on top of either.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+pub enum SimTimestep {
+    #[default]
+    Variable,
+    Fixed(f32),
+}
+
+impl SimTimestep {
+    /// Unscaled simulation step for a frame that really took `real_dt`.
+    pub fn frame_dt(&self, real_dt: f32, max_dt: &MaxDeltaTime) -> f32 {
+        match *self {
+            SimTimestep::Variable => max_dt.clamp(real_dt),
+            SimTimestep::Fixed(dt) => dt.max(0.0),
+        }
+    }
 }
 
 // Uniform passed to the input/brush compute shader
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable, bevy::render::render_resource::ShaderType)]
 pub struct BrushControlUniform {
+    /// Layer the held button paints (see `PheromoneConfig::brush_stroke`).
     pub target_layer: u32,
-    pub _mode: u32, // reserved
+    /// `BrushFalloff` as u32.
+    pub falloff: u32,
+    /// Nonzero in `BrushMode::Beacon`: left click adds the beacon stamp to
+    /// `target_layer` instead of painting it.
+    pub beacon: u32,
+    /// Layer the input pass seeds the screen-edge "avoid" border into: the
+    /// first universal hate layer (0 without one), so it follows reorders.
+    pub border_layer: u32,
+    pub beacon_radius: f32,
+    /// Added per second at the beacon centre.
+    pub beacon_amplitude: f32,
+    /// Nonzero while a brush button is held.
+    pub active: u32,
+    /// Nonzero: the stroke erases `target_layer` to 0 instead of painting it.
+    pub erase: u32,
+}
+
+/// Brush strength from the cursor out to the brush edge (cycled with K).
+#[repr(u32)]
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, ExtractResource)]
+pub enum BrushFalloff {
+    /// `(1 - d/r)^2`: strong centre, soft edge. Legacy default.
+    #[default]
+    Quadratic = 0,
+    /// Full strength across the whole disc (hard edge).
+    Constant = 1,
+    /// `1 - d/r`.
+    Linear = 2,
+    /// Gaussian bump reaching ~1% at the edge.
+    Gaussian = 3,
+}
+
+impl BrushFalloff {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Quadratic => Self::Constant,
+            Self::Constant => Self::Linear,
+            Self::Linear => Self::Gaussian,
+            Self::Gaussian => Self::Quadratic,
+        }
+    }
+}
+
+/// What the left mouse button does (A toggles spawning, E the beacon). Right
+/// click always erases the brush layer (the beacon layer in beacon mode).
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, ExtractResource)]
+pub enum BrushMode {
+    /// Paint pheromone into the brush layer.
+    #[default]
+    Pheromone,
+    /// Add agents at the cursor, of the species mapped from the brush layer.
+    SpawnAgents,
+    /// Drag a strong, small attractor: while held, the cursor adds a
+    /// `BeaconSettings` stamp to the beacon layer every frame, which then
+    /// diffuses and decays like any trail.
+    Beacon,
+}
+
+impl BrushMode {
+    /// Switch to `mode`, or back to painting when already in it.
+    pub fn toggle(self, mode: Self) -> Self {
+        if self == mode { Self::Pheromone } else { mode }
+    }
+}
+
+/// The beacon stamp used in `BrushMode::Beacon`.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, ExtractResource)]
+pub struct BeaconSettings {
+    /// Layer the beacon writes (clamped to the layer count). The default is
+    /// the universal love layer, which every species follows.
+    pub layer: u32,
+    /// Stamp radius in texels.
+    pub radius: f32,
+    /// Value added per second at the centre (scaled by the brush falloff and
+    /// capped by the layer's `max_value`).
+    pub amplitude: f32,
+}
+
+impl Default for BeaconSettings {
+    fn default() -> Self {
+        Self { layer: 1, radius: 6.0, amplitude: 200.0 }
+    }
+}
+
+// Stamp radius of an `Emitter` in texels; passed to pheromones.wgsl as a
+// shader def (see `render::workgroup_shader_defs`)
+pub const EMITTER_RADIUS: u32 = 4;
+
+// Most emitters kept at once; placing another drops the oldest
+pub const MAX_EMITTERS: usize = 256;
+
+/// A persistent point source (e.g. food) that `stamp_emitters` adds to the
+/// pheromone array every frame, after the brush. `amount` is per second at
+/// `pos` (texels) and falls off quadratically to 0 at `EMITTER_RADIUS`,
+/// capped by the layer's `max_value`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable, bevy::render::render_resource::ShaderType)]
+pub struct Emitter {
+    pub pos: Vec2,
+    pub layer: u32,
+    pub amount: f32,
+}
+
+/// All placed emitters (Shift + left click adds one, Shift + C clears them).
+#[derive(Resource, Clone, Debug, Default, ExtractResource)]
+pub struct Emitters(pub Vec<Emitter>);
+
+impl Emitters {
+    /// Append `emitter`, dropping the oldest beyond `MAX_EMITTERS`.
+    pub fn push(&mut self, emitter: Emitter) {
+        self.0.push(emitter);
+        if self.0.len() > MAX_EMITTERS {
+            let excess = self.0.len() - MAX_EMITTERS;
+            self.0.drain(..excess);
+        }
+    }
+}
+
+/// Where the brush layer comes from (toggled with G).
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BrushTarget {
+    /// Picked directly with 0-9 / the mouse wheel.
+    #[default]
+    Manual,
+    /// Tracks the active species' emit layer, so painting seeds its trail.
+    FollowSpecies,
+}
+
+/// Species selected for live weight editing (Tab cycles; `=` / `-` nudge its
+/// weight for the brush layer). `weight` is that species' effective weight
+/// for the brush layer, shown in the overlay; `None` when no species exist.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+pub struct ActiveSpecies {
+    pub index: usize,
+    pub weight: Option<f32>,
+}
+
+// Maximum number of simulation regions passed to the shaders
+pub const MAX_REGIONS: usize = 16;
+
+/// Independent rectangular regions in simulation texels. When non-empty,
+/// each agent is confined to the region it spawned in (region edges act like
+/// walls for movement and sensing) and the brush only paints inside the
+/// region under the cursor. Agents are spread round-robin across regions at
+/// generation. Empty (the default) means one region covering the field.
+/// Pheromones still diffuse across region edges and all regions share the
+/// species set; the composite writes the single display texture as before.
+#[derive(Resource, Clone, Debug, Default, ExtractResource)]
+pub struct SimRegions(pub Vec<Rect>);
+
+impl SimRegions {
+    /// Split the field into four equal quadrants.
+    pub fn quadrants(size: UVec2) -> Self {
+        let half = size.as_vec2() * 0.5;
+        Self(vec![
+            Rect::from_corners(Vec2::ZERO, half),
+            Rect::from_corners(Vec2::new(half.x, 0.0), Vec2::new(size.x as f32, half.y)),
+            Rect::from_corners(Vec2::new(0.0, half.y), Vec2::new(half.x, size.y as f32)),
+            Rect::from_corners(half, size.as_vec2()),
+        ])
+    }
+}
+
+// Uniform mirror of `SimRegions` (rects as min.xy, max.xy; `count` 0 = off)
+#[derive(Clone, Copy, bevy::render::render_resource::ShaderType)]
+pub struct RegionsUniform {
+    pub count: u32,
+    pub _pad: UVec3,
+    pub rects: [Vec4; MAX_REGIONS],
+}
+
+impl RegionsUniform {
+    pub fn from_regions(regions: &[Rect]) -> Self {
+        let mut rects = [Vec4::ZERO; MAX_REGIONS];
+        for (dst, r) in rects.iter_mut().zip(regions) {
+            *dst = Vec4::new(r.min.x, r.min.y, r.max.x, r.max.y);
+        }
+        Self {
+            count: regions.len().min(MAX_REGIONS) as u32,
+            _pad: UVec3::ZERO,
+            rects,
+        }
+    }
+}
+
+// Uniform passed to the composite shader (display-only settings)
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable, bevy::render::render_resource::ShaderType)]
+pub struct CompositeControlUniform {
+    pub blend_mode: u32,
+    pub exposure: f32,
+    pub tonemap: u32,
+    /// Nonzero: pixels with every layer at zero are written with alpha 0.
+    pub transparent_background: u32,
+    /// Linear RGBA shown where the field is empty (see `Background`).
+    pub background: Vec4,
+    /// Nonzero: output `solo_layer` alone as grayscale instead of the blend.
+    pub solo: u32,
+    pub solo_layer: u32,
     pub _pad: bevy::math::UVec2,
 }
 
+// How overlapping layers combine in `composite_pheromones_array`
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CompositeBlendMode {
+    /// Intensity-weighted average hue with soft brightness. Legacy default.
+    #[default]
+    WeightedAverage = 0,
+    /// Sum of each layer's value times its color.
+    Additive = 1,
+    /// Per-channel maximum of each layer's value times its color.
+    Max = 2,
+}
+
+impl CompositeBlendMode {
+    pub fn next(self) -> Self {
+        match self {
+            Self::WeightedAverage => Self::Additive,
+            Self::Additive => Self::Max,
+            Self::Max => Self::WeightedAverage,
+        }
+    }
+}
+
+/// Agent ("firefly") view, toggled with Y: the layer composite is skipped
+/// and agents are drawn as points over the previous frame's display, faded
+/// toward the background, so each leaves a short trail. The pheromone field
+/// keeps simulating underneath.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, ExtractResource)]
+pub struct AgentView {
+    pub enabled: bool,
+    /// Fraction of trail brightness lost per second (see `per_frame_factor`).
+    pub fade_rate: f32,
+}
+
+impl Default for AgentView {
+    fn default() -> Self {
+        Self { enabled: false, fade_rate: 0.95 }
+    }
+}
+
+/// How agents are splatted by the dot overlay (D) and agent view (Y):
+/// `point_size` x `point_size` pixel squares (cycled with Shift + D) that
+/// either overwrite the display with the species color or, when `additive`
+/// (W), add `additive_strength` of it so dense regions glow.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, ExtractResource)]
+pub struct AgentRenderConfig {
+    pub point_size: u32,
+    pub additive: bool,
+    pub additive_strength: f32,
+}
+
+pub const MAX_AGENT_POINT_SIZE: u32 = 5;
+
+impl Default for AgentRenderConfig {
+    fn default() -> Self {
+        Self { point_size: 1, additive: false, additive_strength: 0.35 }
+    }
+}
+
+impl AgentRenderConfig {
+    /// Next point size, wrapping from `MAX_AGENT_POINT_SIZE` back to 1.
+    pub fn next_point_size(&self) -> u32 {
+        self.point_size.clamp(1, MAX_AGENT_POINT_SIZE) % MAX_AGENT_POINT_SIZE + 1
+    }
+}
+
+// Uniform for `fade_display`: out = mix(background, previous, keep)
+#[derive(Clone, Copy, bevy::render::render_resource::ShaderType)]
+pub struct DisplayFadeUniform {
+    pub background: Vec4,
+    pub keep: f32,
+}
+
+// Runtime display settings for the composite pass
+#[derive(Resource, Clone, ExtractResource)]
+pub struct CompositeSettings {
+    pub blend_mode: CompositeBlendMode,
+    /// Linear multiplier applied to the blended color before output.
+    pub exposure: f32,
+    /// Apply Reinhard (`c / (1 + c)`) after exposure.
+    pub tonemap: bool,
+    /// Show only the brush layer's raw value in grayscale (exposure and
+    /// tone mapping still apply) instead of the colorized composite.
+    pub solo_layer: bool,
+}
+
+impl Default for CompositeSettings {
+    fn default() -> Self {
+        // Exposure 1.0 with tonemap off reproduces the untouched blend output
+        Self {
+            blend_mode: CompositeBlendMode::default(),
+            exposure: 1.0,
+            tonemap: false,
+            solo_layer: false,
+        }
+    }
+}
+
+/// Background behind the pheromone composite. `color` fills pixels where the
+/// field is empty (fading out as intensity rises, so black reproduces the
+/// plain composite) and is also the window clear color. With `transparent`
+/// set, pixels where every layer is zero get alpha 0, so PNG captures can be
+/// layered over other art.
+#[derive(Resource, Clone, Copy, Debug, ExtractResource)]
+pub struct Background {
+    pub color: Color,
+    pub transparent: bool,
+}
+
+impl Default for Background {
+    fn default() -> Self {
+        Self { color: Color::BLACK, transparent: false }
+    }
+}
+
+impl Background {
+    /// Linear RGBA as uploaded to the composite pass.
+    pub fn linear(&self) -> Vec4 {
+        self.color.to_linear().to_vec4()
+    }
+}
+
 #[derive(Resource, Clone, ExtractResource)]
 pub struct PheromoneLayerParamsBuffer {
     #[allow(dead_code)]
     pub buffer: bevy::render::render_resource::Buffer,
 }
 
+// Current simulation texture size in texels. Starts at `SIZE / DISPLAY_FACTOR`
+// and follows the window (divided by `DISPLAY_FACTOR`) when it is resized,
+// unless a fixed `SimResolution` is set.
+#[derive(Resource, Clone, Copy, Debug, Deref, ExtractResource)]
+pub struct SimulationSize(pub UVec2);
+
+/// Fixed internal simulation resolution (pheromone arrays, display textures,
+/// agent space). The display sprite is stretched over the window, so e.g.
+/// half the window size quarters the per-texel work. `None` (the default)
+/// runs the simulation at window resolution divided by `DISPLAY_FACTOR`.
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct SimResolution(pub Option<UVec2>);
+
+/// Starting pheromone field, loaded once by `setup` (see
+/// `pheromones::load_initial_field`). Images must match the simulation size
+/// exactly (`SIZE / DISPLAY_FACTOR` unless `SimResolution` is set); a mismatch or unreadable
+/// file is logged as an error and the field starts empty. Image row 0 is the
+/// top of the display. Resizes and config reloads start empty again.
+#[derive(Resource, Clone, Debug, Default, PartialEq)]
+pub enum InitialField {
+    #[default]
+    Empty,
+    /// One image whose R, G, B, A channels seed layers 0-3.
+    Channels(String),
+    /// One image per layer from layer 0 up, by luminance; later layers start empty.
+    PerLayer(Vec<String>),
+}
+
 #[derive(Resource, Clone, ExtractResource)]
 pub struct PheromoneImages {
     pub texture_a: Handle<Image>,
     pub texture_b: Handle<Image>,
+    // 8-bit sRGB copy of the current display (written when `SrgbDisplay` is on)
+    pub display_srgb: Handle<Image>,
+}
+
+/// Encode the composited display into an 8-bit sRGB texture each frame and
+/// show that instead of the `Rgba32Float` targets. The float pair stays the
+/// composite output; only presentation (and PNG capture) reads the 8-bit copy,
+/// which is a quarter of the bandwidth and already in PNG's byte layout.
+#[derive(Resource, Clone, Copy, Debug, ExtractResource)]
+pub struct SrgbDisplay(pub bool);
+
+impl Default for SrgbDisplay {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+/// Sampling filter for the display sprite when the simulation is stretched
+/// over more pixels (`DISPLAY_FACTOR > 1` or a smaller `SimResolution`).
+/// `Nearest` keeps texels crisp for inspection; `Linear` smooths them. Only
+/// the 8-bit `SrgbDisplay` target honours it: `Rgba32Float` isn't filterable
+/// on every adapter, so the float targets always sample nearest.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DisplayFilter {
+    #[default]
+    Nearest,
+    Linear,
+}
+
+impl DisplayFilter {
+    pub fn toggle(self) -> Self {
+        match self {
+            Self::Nearest => Self::Linear,
+            Self::Linear => Self::Nearest,
+        }
+    }
+
+    pub fn sampler(self) -> bevy::image::ImageSampler {
+        match self {
+            Self::Nearest => bevy::image::ImageSampler::nearest(),
+            Self::Linear => bevy::image::ImageSampler::linear(),
+        }
+    }
 }
 
 #[derive(Resource, Clone, ExtractResource)]
 pub struct SpeciesGpuBuffer {
     pub buffer: bevy::render::render_resource::Buffer,
+    /// Some species has `avoid_strength > 0`; otherwise the render node skips
+    /// building the agent grid.
+    pub any_avoidance: bool,
+}
+
+// Render-world agent count grid for same-species avoidance: one atomic u32
+// per (cell, species slot), cleared and rebuilt each frame by `bin_agents`.
+// Reallocated by `prepare_bind_group` when the field or species count changes.
+#[derive(Resource)]
+pub struct AgentGridBuffer {
+    pub buffer: bevy::render::render_resource::Buffer,
+    pub len: u32,
 }
 
 #[derive(Resource)]
@@ -145,6 +969,32 @@ pub struct PheroArrayEnvBindGroups(pub [bevy::render::render_resource::BindGroup
 #[derive(Resource)]
 pub struct PheroArrayCompositeBindGroups(pub [bevy::render::render_resource::BindGroup; 2]);
 
+// Clear bind groups, one per array ([prev, next])
+#[derive(Resource)]
+pub struct PheroArrayClearBindGroups(pub [bevy::render::render_resource::BindGroup; 2]);
+
+// Agent dot overlay bind groups per ping (agents + the display texture the
+// composite wrote that frame)
+#[derive(Resource)]
+pub struct AgentDotsBindGroups(pub [bevy::render::render_resource::BindGroup; 2]);
+
+// Display encode bind groups per ping (float display the composite wrote ->
+// `display_srgb`)
+#[derive(Resource)]
+pub struct DisplayEncodeBindGroups(pub [bevy::render::render_resource::BindGroup; 2]);
+
+// Agent view fade bind groups per ping (last frame's display -> this ping's)
+#[derive(Resource)]
+pub struct DisplayFadeBindGroups(pub [bevy::render::render_resource::BindGroup; 2]);
+
+// Cursor probe bind groups per ping (see `probe`)
+#[derive(Resource)]
+pub struct PheroArrayProbeBindGroups(pub [bevy::render::render_resource::BindGroup; 2]);
+
+// Layer stats bind groups per ping (probe layout, stats buffer; see `stats`)
+#[derive(Resource)]
+pub struct PheroArrayStatsBindGroups(pub [bevy::render::render_resource::BindGroup; 2]);
+
 // Extended per-species, per-layer weights/emission buffers (dense L floats per species)
 #[derive(Resource, Clone, ExtractResource)]
 pub struct SpeciesLayerWeights {
@@ -158,9 +1008,89 @@ pub struct AgentSimRunConfig {
     // - `run_copy_and_input`: enable the copy/input (brush) pass for pheromones
     // - `run_diffuse`: enable the diffusion/decay pass for pheromones
     // - `run_agents`: enable the agent compute pass
+    // - `draw_agent_dots`: after the composite, splat each agent onto the
+    //   display texture in its species color
+    // - `draw_sensors`: after the composite, mark the sensor sample points of
+    //   `agents::SENSOR_MARKER_AGENTS` agents (steering debug)
+    // - `diffuse_iterations`: diffusion passes per frame (see
+    //   `diffuse_steps`); decay and flow are split across them
+    // - `time_scale`: simulation speed multiplier on the frame delta (agent
+    //   movement, deposits, diffusion, decay). 0 freezes the simulation while
+    //   the passes keep running, unlike turning the stages off
     pub run_copy_and_input: bool,
     pub run_diffuse: bool,
     pub run_agents: bool,
+    pub draw_agent_dots: bool,
+    pub draw_sensors: bool,
+    pub diffuse_iterations: u32,
+    pub time_scale: f32,
+}
+
+// Upper bound on diffusion passes per frame
+pub const MAX_DIFFUSE_ITERATIONS: u32 = 16;
+
+// Speeds the time scale hotkeys step through (0 = frozen)
+pub const TIME_SCALE_STEPS: [f32; 7] = [0.0, 0.125, 0.25, 0.5, 1.0, 2.0, 4.0];
+
+/// Next entry of `TIME_SCALE_STEPS` above (`faster`) or below `current`,
+/// staying put at either end. Off-list values snap to the nearest step in
+/// that direction.
+pub fn step_time_scale(current: f32, faster: bool) -> f32 {
+    if faster {
+        TIME_SCALE_STEPS.iter().copied().find(|&s| s > current).unwrap_or(current)
+    } else {
+        TIME_SCALE_STEPS.iter().rev().copied().find(|&s| s < current).unwrap_or(current)
+    }
+}
+
+impl AgentSimRunConfig {
+    /// Diffusion passes to run this frame, clamped to `1..=MAX_DIFFUSE_ITERATIONS`.
+    pub fn diffuse_steps(&self) -> u32 {
+        self.diffuse_iterations.clamp(1, MAX_DIFFUSE_ITERATIONS)
+    }
+
+    /// Overlay line listing each toggleable stage and the speed, or `None`
+    /// while all run with a single diffusion pass at normal speed.
+    pub fn stages_label(&self) -> Option<String> {
+        let steps = self.diffuse_steps();
+        let normal_speed = self.time_scale == 1.0;
+        if self.run_copy_and_input && self.run_diffuse && self.run_agents && steps == 1 && normal_speed {
+            return None;
+        }
+        let state = |on: bool| if on { "on" } else { "off" };
+        let diffuse = match (self.run_diffuse, steps) {
+            (true, 1) => "on".to_string(),
+            (true, n) => format!("x{n}"),
+            (false, _) => "off".to_string(),
+        };
+        let speed = if normal_speed {
+            String::new()
+        } else if self.time_scale <= 0.0 {
+            ", speed x0 (frozen)".to_string()
+        } else {
+            format!(", speed x{}", self.time_scale)
+        };
+        Some(format!(
+            "Stages: input {}, diffuse {diffuse}, agents {}{speed}",
+            state(self.run_copy_and_input),
+            state(self.run_agents)
+        ))
+    }
+}
+
+// One-shot request to zero every pheromone layer. Set by input, consumed by
+// the render node for a single frame, then reset at the start of the next one.
+#[derive(Resource, Clone, Default, ExtractResource)]
+pub struct ClearPheromonesRequest {
+    pub pending: bool,
+}
+
+// One-shot request to exchange two layers of the pheromone arrays, set with
+// the matching CPU-side permutation (see `layer_order`). Consumed by the
+// render node before diffusion, then reset at the start of the next frame.
+#[derive(Resource, Clone, Default, ExtractResource)]
+pub struct LayerSwapRequest {
+    pub pending: Option<(u32, u32)>,
 }
 
 #[cfg(test)]
@@ -185,4 +1115,260 @@ mod tests {
         assert_eq!(s.emit_layer, 0);
         assert_eq!(s.emit_amount, 0.0);
     }
+
+    #[test]
+    fn layer_decay_bias_averages_emitters() {
+        let emitter = |layer: u32, bias: f32| SpeciesSettings {
+            emit_layer: layer,
+            emit_amount: 1.0,
+            emit_decay_bias: bias,
+            ..Default::default()
+        };
+        // non-emitting species are ignored, as are out-of-range layers
+        let silent = SpeciesSettings { emit_decay_bias: 5.0, ..Default::default() };
+        let species = [emitter(2, 2.0), emitter(2, 1.0), emitter(3, 0.5), emitter(9, 4.0), silent];
+        let bias = LayerDecayBias::from_species(&species, 5, 0);
+        assert_eq!(bias.0, vec![1.0, 1.0, 1.5, 0.5, 1.0]);
+        assert_eq!(bias.get(7), 1.0);
+        // paint-only layers never take a species' bias
+        let bias = LayerDecayBias::from_species(&species, 5, 1 << 3);
+        assert_eq!(bias.0, vec![1.0, 1.0, 1.5, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn time_scale_steps_clamp_at_both_ends() {
+        assert_eq!(step_time_scale(1.0, true), 2.0);
+        assert_eq!(step_time_scale(1.0, false), 0.5);
+        assert_eq!(step_time_scale(4.0, true), 4.0);
+        assert_eq!(step_time_scale(0.125, false), 0.0);
+        assert_eq!(step_time_scale(0.0, false), 0.0);
+        assert_eq!(step_time_scale(0.0, true), 0.125);
+        // Off-list speeds snap onto the list
+        assert_eq!(step_time_scale(0.3, true), 0.5);
+        assert_eq!(step_time_scale(0.3, false), 0.25);
+    }
+
+    #[test]
+    fn stages_label_only_when_something_is_off() {
+        let mut run = AgentSimRunConfig {
+            run_copy_and_input: true,
+            run_diffuse: true,
+            run_agents: true,
+            draw_agent_dots: false,
+            draw_sensors: false,
+            diffuse_iterations: 1,
+            time_scale: 1.0,
+        };
+        assert_eq!(run.stages_label(), None);
+        run.run_diffuse = false;
+        assert_eq!(run.stages_label().as_deref(), Some("Stages: input on, diffuse off, agents on"));
+        run.run_diffuse = true;
+        run.diffuse_iterations = 4;
+        assert_eq!(run.stages_label().as_deref(), Some("Stages: input on, diffuse x4, agents on"));
+        run.diffuse_iterations = 1;
+        run.time_scale = 0.5;
+        assert_eq!(run.stages_label().as_deref(), Some("Stages: input on, diffuse on, agents on, speed x0.5"));
+        run.time_scale = 0.0;
+        assert_eq!(
+            run.stages_label().as_deref(),
+            Some("Stages: input on, diffuse on, agents on, speed x0 (frozen)")
+        );
+        run.diffuse_iterations = 0;
+        assert_eq!(run.diffuse_steps(), 1);
+        run.diffuse_iterations = 1000;
+        assert_eq!(run.diffuse_steps(), MAX_DIFFUSE_ITERATIONS);
+    }
+
+    #[test]
+    fn paint_only_mask_covers_universal_layers() {
+        let cfg = PheromoneConfig {
+            layer_count: 5,
+            universal_love_layers: vec![1],
+            universal_hate_layers: vec![0, 4, 40],
+            ..Default::default()
+        };
+        assert_eq!(cfg.paint_only_mask(), 0b10011);
+        assert_eq!(PheromoneConfig::default().paint_only_mask(), 0);
+    }
+
+    #[test]
+    fn validate_clamps_and_dedupes_layers() {
+        let mut ok = PheromoneConfig {
+            layer_count: 5,
+            brush_target_layer: 1,
+            universal_love_layers: vec![1],
+            universal_hate_layers: vec![0],
+            ..Default::default()
+        };
+        assert!(ok.validate().is_empty());
+
+        let mut cfg = PheromoneConfig {
+            layer_count: 3,
+            brush_target_layer: 7,
+            universal_love_layers: vec![1, 1, 2, 9],
+            universal_hate_layers: vec![2, 5],
+            wrap_diffusion: false,
+            brush_bindings: [BRUSH_LAYER, 0, 3],
+            shared_layer: Some(3),
+        };
+        let fixes = cfg.validate();
+        assert_eq!(cfg.brush_target_layer, 2);
+        assert_eq!(cfg.universal_hate_layers, vec![2]);
+        assert_eq!(cfg.universal_love_layers, vec![1]);
+        assert_eq!(cfg.brush_bindings, [BRUSH_LAYER, 0, BRUSH_LAYER]);
+        assert_eq!(cfg.shared_layer, None);
+        // brush layer, hate 5, love dup 1, love 9, love/hate 2, middle button, shared layer
+        assert_eq!(fixes.len(), 7);
+
+        let mut empty = PheromoneConfig { layer_count: 0, brush_target_layer: 3, ..Default::default() };
+        empty.validate();
+        assert_eq!((empty.layer_count, empty.brush_target_layer), (1, 0));
+    }
+
+    #[test]
+    fn brush_bindings_pick_the_held_buttons_layer() {
+        let mut cfg = PheromoneConfig { layer_count: 5, brush_target_layer: 2, ..Default::default() };
+        // Defaults: left/middle paint the brush layer, right erases it
+        assert_eq!(cfg.brush_stroke([false; 3]), None);
+        assert_eq!(cfg.brush_stroke([true, false, false]), Some((2, false)));
+        assert_eq!(cfg.brush_stroke([false, true, false]), Some((2, true)));
+        assert_eq!(cfg.brush_stroke([false, false, true]), Some((2, false)));
+        // Bound: right paints its layer; left wins over middle over right
+        cfg.brush_bindings = [BRUSH_LAYER, 0, 4];
+        assert_eq!(cfg.brush_stroke([false, true, false]), Some((0, false)));
+        assert_eq!(cfg.brush_stroke([false, true, true]), Some((4, false)));
+        assert_eq!(cfg.brush_stroke([true, true, true]), Some((2, false)));
+    }
+
+    #[test]
+    fn display_filter_defaults_nearest_and_toggles() {
+        assert_eq!(DisplayFilter::default(), DisplayFilter::Nearest);
+        assert_eq!(DisplayFilter::Nearest.toggle(), DisplayFilter::Linear);
+        assert_eq!(DisplayFilter::Linear.toggle(), DisplayFilter::Nearest);
+    }
+
+    #[test]
+    fn species_count_in_ignores_partial_entries() {
+        let size = std::mem::size_of::<SpeciesSettings>() as u64;
+        assert_eq!(SpeciesSettings::count_in(0), 0);
+        assert_eq!(SpeciesSettings::count_in(3 * size), 3);
+        assert_eq!(SpeciesSettings::count_in(3 * size + 4), 3);
+    }
+
+    #[test]
+    fn species_settings_default_enabled_and_16_byte_stride() {
+        assert_eq!(SpeciesSettings::default().enabled, 1);
+        assert_eq!(std::mem::size_of::<SpeciesSettings>() % 16, 0);
+    }
+
+    #[test]
+    fn regions_uniform_packs_and_caps() {
+        let quads = SimRegions::quadrants(UVec2::new(100, 50));
+        let u = RegionsUniform::from_regions(&quads.0);
+        assert_eq!(u.count, 4);
+        assert_eq!(u.rects[3], Vec4::new(50.0, 25.0, 100.0, 50.0));
+        assert_eq!(u.rects[4], Vec4::ZERO);
+        let many = vec![Rect::new(0.0, 0.0, 1.0, 1.0); MAX_REGIONS + 3];
+        assert_eq!(RegionsUniform::from_regions(&many).count, MAX_REGIONS as u32);
+        assert_eq!(RegionsUniform::from_regions(&[]).count, 0);
+    }
+
+    #[test]
+    fn max_delta_time_caps_hitches() {
+        let cap = MaxDeltaTime(0.05);
+        assert_eq!(cap.clamp(0.016), 0.016);
+        assert_eq!(cap.clamp(0.5), 0.05);
+        assert_eq!(MaxDeltaTime(0.0).clamp(0.5), 0.5);
+        assert_eq!(MaxDeltaTime::default().clamp(1.0), 1.0 / 30.0);
+    }
+
+    #[test]
+    fn fixed_timestep_ignores_frame_time() {
+        let cap = MaxDeltaTime(0.05);
+        assert_eq!(SimTimestep::default(), SimTimestep::Variable);
+        assert_eq!(SimTimestep::Variable.frame_dt(0.5, &cap), 0.05);
+        let fixed = SimTimestep::Fixed(1.0 / 60.0);
+        for real_dt in [0.0, 0.001, 0.016, 0.5] {
+            assert_eq!(fixed.frame_dt(real_dt, &cap), 1.0 / 60.0);
+        }
+        assert_eq!(SimTimestep::Fixed(-1.0).frame_dt(0.016, &cap), 0.0);
+    }
+
+    #[test]
+    fn diffusion_wraps_with_wrapping_agents() {
+        let bounded = PheromoneConfig::default();
+        assert!(!bounded.diffusion_wraps(BoundaryMode::Bounce));
+        assert!(bounded.diffusion_wraps(BoundaryMode::Wrap));
+        let wrapped = PheromoneConfig { wrap_diffusion: true, ..Default::default() };
+        assert!(wrapped.diffusion_wraps(BoundaryMode::Bounce));
+    }
+
+    #[test]
+    fn composite_blend_mode_cycles() {
+        let start = CompositeBlendMode::default();
+        assert_eq!(start, CompositeBlendMode::WeightedAverage);
+        assert_eq!(start.next().next().next(), start);
+        assert_eq!(CompositeBlendMode::Max as u32, 2);
+    }
+
+    #[test]
+    fn quadrants_tile_the_field() {
+        let size = UVec2::new(100, 60);
+        let regions = SimRegions::quadrants(size);
+        assert_eq!(regions.0.len(), 4);
+        let area: f32 = regions.0.iter().map(|r| r.width() * r.height()).sum();
+        assert_eq!(area, 100.0 * 60.0);
+        assert_eq!(regions.0[3].max, Vec2::new(100.0, 60.0));
+        assert!(regions.0.len() <= MAX_REGIONS);
+    }
+
+    #[test]
+    fn agent_point_size_cycles_within_range() {
+        let mut cfg = AgentRenderConfig::default();
+        assert_eq!(cfg.point_size, 1);
+        assert!(!cfg.additive);
+        let mut sizes = Vec::new();
+        for _ in 0..MAX_AGENT_POINT_SIZE {
+            cfg.point_size = cfg.next_point_size();
+            sizes.push(cfg.point_size);
+        }
+        assert_eq!(sizes, vec![2, 3, 4, 5, 1]);
+        assert_eq!(AgentRenderConfig { point_size: 0, ..cfg }.next_point_size(), 2);
+    }
+
+    #[test]
+    fn brush_falloff_cycles() {
+        let start = BrushFalloff::default();
+        assert_eq!(start, BrushFalloff::Quadratic);
+        assert_eq!(start.next().next().next().next(), start);
+        assert_eq!(BrushFalloff::Gaussian as u32, 3);
+    }
+
+    #[test]
+    fn brush_mode_toggles_back_to_painting() {
+        let paint = BrushMode::default();
+        assert_eq!(paint, BrushMode::Pheromone);
+        assert_eq!(paint.toggle(BrushMode::Beacon), BrushMode::Beacon);
+        assert_eq!(BrushMode::Beacon.toggle(BrushMode::Beacon), paint);
+        // Switching straight between the other modes
+        assert_eq!(BrushMode::Beacon.toggle(BrushMode::SpawnAgents), BrushMode::SpawnAgents);
+        assert_eq!(std::mem::size_of::<BrushControlUniform>(), 32);
+    }
+
+    #[test]
+    fn background_defaults_to_opaque_black() {
+        let bg = Background::default();
+        assert_eq!(bg.linear(), Vec4::new(0.0, 0.0, 0.0, 1.0));
+        assert!(!bg.transparent);
+        let white = Background { color: Color::WHITE, transparent: true };
+        assert_eq!(white.linear(), Vec4::ONE);
+    }
+
+    #[test]
+    fn composite_settings_default_is_identity() {
+        let c = CompositeSettings::default();
+        assert_eq!(c.exposure, 1.0);
+        assert!(!c.tonemap);
+        assert!(!c.solo_layer);
+    }
 }
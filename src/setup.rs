@@ -8,20 +8,56 @@
 
 use bevy::prelude::*;
 // Using Text2D-style overlay for the layer indicator
-use bevy::render::render_resource::{BufferInitDescriptor, BufferUsages};
+use bevy::render::render_resource::{
+    BufferInitDescriptor, BufferUsages, TextureFormat, TextureUsages, TextureViewDescriptor,
+};
 use bevy::render::renderer::RenderDevice;
+use bevy::window::PrimaryWindow;
 
 use crate::agents;
-use crate::pheromones::{PheromoneArrayImages, make_pheromone_array_images};
+use crate::color::{PALETTES, layer_color_hsv, palette_colors};
+use crate::obstacles::init_obstacle_mask;
+use crate::probe::PheromoneProbe;
+use crate::species::{AgentSpecies, SpeciesHeading, authored_species_count, species_heading_modes};
+use crate::stats::{PheromoneStats, stats_label};
+use crate::pheromones::{
+    PheromoneArrayImages, load_initial_field, make_pheromone_array_images, make_pheromone_array_images_from,
+};
+use crate::resources::{
+    ActiveSpecies, AgentSimRunConfig, BrushTarget, ClearPheromonesRequest, CompositeSettings, GlobalUniforms,
+    DisplayFilter, InitialField, PheromoneConfig, PheromoneImages, SimRegions, SimResolution, SimulationSize, SrgbDisplay,
+};
 use crate::resources::{
-    AgentSimRunConfig, GlobalUniforms, PheromoneConfig, PheromoneImages,
+    DiffusionKernel, LayerDecayBias, PheromoneLayerParam, PheromoneLayerParamsBuffer,
 };
-use crate::resources::{PheromoneLayerParam, PheromoneLayerParamsBuffer};
-use crate::{DISPLAY_FACTOR, SIZE, NUM_AGENTS};
+use crate::{DISPLAY_FACTOR, SIZE};
 
 #[derive(Component)]
 pub struct BrushLayerText;
 
+// Seconds an `OverlayNotice` stays on the overlay label
+const NOTICE_SECS: f32 = 4.0;
+
+/// Short-lived message appended to the overlay label (errors, reload
+/// confirmations). Counts down in `update_fps_counter`.
+#[derive(Resource, Clone, Default)]
+pub struct OverlayNotice {
+    pub text: String,
+    pub secs_left: f32,
+}
+
+impl OverlayNotice {
+    pub fn flash(&mut self, text: impl Into<String>) {
+        self.text = text.into();
+        self.secs_left = NOTICE_SECS;
+    }
+
+    /// Text to show right now, if any.
+    pub fn visible(&self) -> Option<&str> {
+        (self.secs_left > 0.0 && !self.text.is_empty()).then_some(self.text.as_str())
+    }
+}
+
 #[derive(Resource, Clone, Copy)]
 pub struct FpsCounter {
     pub acc_time: f32,
@@ -32,43 +68,57 @@ pub struct FpsCounter {
 
 #[derive(Resource, Clone)]
 pub struct PheromoneLayerParamsCpu {
-    pub params: Vec<PheromoneLayerParam>, // diffusion/decay as base rates; color as display
+    pub params: Vec<PheromoneLayerParam>, // diffusion/decay as base rates; kernel; color as display
 }
 
+/// Startup check of the configured `PheromoneConfig` (see
+/// `PheromoneConfig::validate`); runs before anything sizes buffers by it.
+pub fn validate_pheromone_config(mut phero_cfg: ResMut<PheromoneConfig>) {
+    for fix in phero_cfg.validate() {
+        warn!("Pheromone config: {fix}");
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn setup(
     mut commands: Commands,
     mut images: ResMut<Assets<Image>>,
     render_device: Res<RenderDevice>,
     phero_cfg: Res<PheromoneConfig>,
+    population: Res<agents::AgentPopulation>,
+    species_mix: Res<agents::AgentSpeciesMix>,
+    spawn_pattern: Res<agents::SpawnPattern>,
+    seed: Res<agents::SimSeed>,
+    regions: Res<SimRegions>,
+    resolution: Res<SimResolution>,
+    display_filter: Res<DisplayFilter>,
+    initial_field: Res<InitialField>,
+    // Spawned earlier in the Startup chain
+    species: Query<Option<&SpeciesHeading>, With<AgentSpecies>>,
 ) {
     // Create two RGBA render targets (texture_a/texture_b) used for display
     // ping-ponging. No separate temp texture is required for the current pipeline.
     // TEXTURES
-    let mut image = Image::new_target_texture(
-        SIZE.x,
-        SIZE.y,
-        bevy::render::render_resource::TextureFormat::Rgba32Float,
-    );
-    image.asset_usage = bevy::asset::RenderAssetUsages::RENDER_WORLD;
-    image.texture_descriptor.usage = bevy::render::render_resource::TextureUsages::COPY_DST
-        | bevy::render::render_resource::TextureUsages::STORAGE_BINDING
-        | bevy::render::render_resource::TextureUsages::TEXTURE_BINDING;
-    let image0 = images.add(image.clone());
-    let image1 = images.add(image.clone());
-    // No temp texture required
+    // Simulation texels; the sprite below always covers the display (`SIZE`)
+    let sim_size = sim_size_for_display(SIZE, resolution.0);
+    info!("Simulation: {sim_size} texels for a {SIZE} display");
+    commands.insert_resource(SimulationSize(sim_size));
+    let display_images = make_display_images(&mut images, sim_size, *display_filter);
+    let image0 = display_images.texture_a.clone();
 
     commands.spawn((
         Sprite {
             image: image0.clone(),
-            custom_size: Some(SIZE.as_vec2()),
+            custom_size: Some(sim_size.as_vec2()),
             ..default()
         },
-        Transform::from_scale(Vec3::splat(DISPLAY_FACTOR as f32)),
+        Transform::from_scale(display_scale(SIZE, sim_size).extend(1.0)),
     ));
     commands.spawn(Camera2d);
 
     // Minimal on-screen text: show current brush target layer and FPS (top-left-ish)
     commands.insert_resource(FpsCounter { acc_time: 0.0, frames: 0, fps: 0.0, ms: 0.0 });
+    commands.insert_resource(OverlayNotice::default());
     commands.spawn((
         Text::new(format!("Layer: {} | FPS: -- | ms: --", phero_cfg.brush_target_layer)),
         TextFont { font_size: 18.0, ..default() },
@@ -81,53 +131,158 @@ pub fn setup(
         BrushLayerText,
     ));
 
-    commands.insert_resource(PheromoneImages { texture_a: image0, texture_b: image1 });
+    commands.insert_resource(display_images);
 
     // ARRAY PHEROMONE IMAGES (prev/next)
     let layer_count = phero_cfg.layer_count.max(1);
     info!("Pheromones: layers = {layer_count}");
-    let phero_array = make_pheromone_array_images(&mut images, layer_count);
+    let keyed = crate::input::DIGIT_LAYER_LIMIT;
+    if layer_count > keyed {
+        info!("Pheromones: layers {keyed}-{} have no number key; use the mouse wheel", layer_count - 1);
+    }
+    let phero_array = match load_initial_field(&initial_field, layer_count, sim_size) {
+        Ok(Some(values)) => {
+            info!("Pheromones: initial field from {:?}", *initial_field);
+            make_pheromone_array_images_from(&mut images, layer_count, sim_size, &values)
+        }
+        Ok(None) => make_pheromone_array_images(&mut images, layer_count, sim_size),
+        Err(err) => {
+            error!("Pheromones: initial field not loaded ({err}); starting empty");
+            make_pheromone_array_images(&mut images, layer_count, sim_size)
+        }
+    };
     commands.insert_resource::<PheromoneArrayImages>(phero_array);
 
+    // OBSTACLE MASK (walls agents cannot cross; empty unless a map is present)
+    init_obstacle_mask(&mut commands, &mut images, sim_size);
+
+    // Agents and shaders use the species actually authored, however many
+    let species_count = authored_species_count(&species);
+
     // GLOBAL UNIFORMS
     commands.insert_resource(GlobalUniforms {
         delta_time: 0.01,
         frame: 0,
         mouse_position: Vec2::new(-10000.0, -10000.0),
-        screen_size: SIZE.as_vec2(),
+        screen_size: sim_size.as_vec2(),
         left_button_pressed: 0,
         right_button_pressed: 0,
         species_offset: 0,
-        species_count: crate::NUM_SPECIES,
+        species_count,
+        wrap_edges: 0,
+        wrap_diffusion: 0,
     });
 
     // Legacy PheromoneUniforms removed; using per-layer param buffer below
 
-    // Per-layer params (diffusion, decay, color)
-    // Define explicit colors for the first five layers:
-    // 0: hate (red), 1: love (green), 2..4: agent-specific (purple, yellow, blue)
+    // Per-layer params (diffusion, decay, kernel, color)
+    insert_layer_params(&mut commands, &render_device, default_layer_params(layer_count));
+
+    // Run config
+    commands.insert_resource(AgentSimRunConfig {
+        run_copy_and_input: true,
+        run_diffuse: true,
+        run_agents: true,
+        draw_agent_dots: false,
+        draw_sensors: false,
+        diffuse_iterations: 1,
+        time_scale: 1.0,
+    });
+    commands.insert_resource(ClearPheromonesRequest::default());
+    commands.insert_resource(CompositeSettings::default());
+
+    // Initialize agents (agent module takes care of CPU/GPU agent resources)
+    agents::init_agents(
+        &mut commands,
+        &render_device,
+        sim_size,
+        population.0,
+        species_count,
+        &species_mix.0,
+        &species_heading_modes(&species),
+        *spawn_pattern,
+        seed.0,
+        &regions.0,
+    );
+
+    // Species GPU buffer is uploaded by species::upload_species_to_gpu during Startup
+}
+
+// Hue step for layers past the authored palette; keeps neighbours far apart
+const GOLDEN_ANGLE_DEGREES: f32 = 137.507_77;
+
+/// Default per-layer params for `layer_count` layers.
+/// Define explicit colors (HSV: degrees, saturation, value) for the first five layers:
+/// 0: hate (black), 1: love (green), 2..4: agent-specific (purple, yellow, blue).
+/// Further layers get distinct hues stepped by the golden angle.
+pub fn default_layer_params(layer_count: u32) -> Vec<PheromoneLayerParam> {
     let mut layer_params: Vec<PheromoneLayerParam> = Vec::with_capacity(layer_count as usize);
     let defaults = [
-        (0.4, 0.7, Vec4::new(0.0, 0.0, 0.0, 1.0)), // 0 hate
-        (0.4, 0.7, Vec4::new(0.2, 0.95, 0.2, 1.0)), // 1 love
-        (0.5, 0.8, Vec4::new(0.8, 80.0 / 255.0, 120.0 / 255.0, 1.0)), // 2 purple
-        (0.6, 0.85, Vec4::new(0.5, 0.9, 0.2, 1.0)), // 3 yellow
-        (0.7, 0.9, Vec4::new(0.1, 0.2, 0.85, 1.0)), // 4 blue
+        (0.4, 0.7, DiffusionKernel::Box, (0.0, 0.0, 0.0)), // 0 hate
+        (0.4, 0.7, DiffusionKernel::Box, (120.0, 0.504, 0.978)), // 1 love
+        (0.5, 0.8, DiffusionKernel::Box, (336.9, 0.343, 0.906)), // 2 purple
+        (0.6, 0.85, DiffusionKernel::Box, (88.0, 0.492, 0.955)), // 3 yellow
+        (0.7, 0.9, DiffusionKernel::Box, (226.0, 0.625, 0.931)), // 4 blue
     ];
     for i in 0..layer_count {
-        let (diff, dec, col) = if (i as usize) < defaults.len() {
+        let (diff, dec, kernel, (h, s, v)) = if (i as usize) < defaults.len() {
             defaults[i as usize]
         } else {
-            (0.5, 0.8, Vec4::new(0.6, 0.6, 0.6, 1.0))
+            let h = (i as f32 * GOLDEN_ANGLE_DEGREES).rem_euclid(360.0);
+            (0.5, 0.8, DiffusionKernel::Box, (h, 0.55, 0.95))
         };
         layer_params.push(PheromoneLayerParam {
             diffusion: diff,
             decay: dec,
-            _pad0: 0.0,
-            _pad1: 0.0,
-            color: col,
+            kernel: kernel as u32,
+            frozen: 0,
+            color: layer_color_hsv(h, s, v),
+            max_value: 0.0,
+            diagonal_weight: 0.0,
+            diffuse_enabled: 1,
+            decay_enabled: 1,
+            flow_dir: Vec2::ZERO,
+            flow_strength: 0.0,
+            decay_floor: 0.0,
+            brush_amount: 1.0,
+            conserve_mass: 0,
+            _pad: [0; 2],
         });
     }
+    layer_params
+}
+
+/// Palettes `apply_palette` accepts, in cycling order; "default" restores the
+/// authored colors from `default_layer_params`.
+pub fn palette_names() -> impl Iterator<Item = &'static str> {
+    std::iter::once("default").chain(PALETTES.iter().map(|(name, _)| *name))
+}
+
+/// Recolor every layer from palette `name`, leaving rates untouched. Returns
+/// false (and changes nothing) for unknown names. `update_layer_params_buffer`
+/// uploads the new colors on the next frame.
+pub fn apply_palette(params: &mut [PheromoneLayerParam], name: &str) -> bool {
+    let colors: Vec<Vec4> = if name == "default" {
+        default_layer_params(params.len() as u32).iter().map(|p| p.color).collect()
+    } else {
+        match palette_colors(name, params.len()) {
+            Some(colors) => colors,
+            None => return false,
+        }
+    };
+    for (param, color) in params.iter_mut().zip(colors) {
+        param.color = color;
+    }
+    true
+}
+
+/// Create the GPU layer params buffer and insert it alongside the CPU copy.
+/// Used at startup and whenever the layer set is replaced (e.g. config load).
+pub fn insert_layer_params(
+    commands: &mut Commands,
+    render_device: &RenderDevice,
+    layer_params: Vec<PheromoneLayerParam>,
+) {
     let layer_param_buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
         label: Some("Pheromone layer params"),
         contents: bytemuck::cast_slice(&layer_params),
@@ -138,28 +293,131 @@ pub fn setup(
     });
     // Keep CPU copy of base rates/colors
     commands.insert_resource(PheromoneLayerParamsCpu { params: layer_params });
+}
 
-    // Run config
-    commands.insert_resource(AgentSimRunConfig {
-        run_copy_and_input: true,
-        run_diffuse: true,
-        run_agents: true,
+/// Allocate the two ping-ponged `Rgba32Float` display targets and the 8-bit
+/// sRGB presentation target (`SrgbDisplay`), sampled with `filter`.
+pub fn make_display_images(images: &mut Assets<Image>, size: UVec2, filter: DisplayFilter) -> PheromoneImages {
+    let mut image = Image::new_target_texture(size.x, size.y, TextureFormat::Rgba32Float);
+    image.asset_usage = bevy::asset::RenderAssetUsages::RENDER_WORLD;
+    // COPY_SRC allows GPU readback of the display for PNG capture
+    image.texture_descriptor.usage = TextureUsages::COPY_DST
+        | TextureUsages::COPY_SRC
+        | TextureUsages::STORAGE_BINDING
+        | TextureUsages::TEXTURE_BINDING;
+    // Not filterable everywhere (see `DisplayFilter`)
+    image.sampler = DisplayFilter::Nearest.sampler();
+    PheromoneImages {
+        texture_a: images.add(image.clone()),
+        texture_b: images.add(image),
+        display_srgb: images.add(srgb_display_image(size, filter)),
+    }
+}
+
+/// The 8-bit sRGB presentation target alone, so a `DisplayFilter` change
+/// only replaces this one.
+pub fn srgb_display_image(size: UVec2, filter: DisplayFilter) -> Image {
+    // The encode pass stores sRGB-encoded bytes through an Rgba8Unorm storage
+    // view (sRGB formats can't be storage bound); the sprite samples through
+    // an Rgba8UnormSrgb view so the bytes decode back to linear
+    let mut srgb = Image::new_target_texture(size.x, size.y, TextureFormat::Rgba8Unorm);
+    srgb.asset_usage = bevy::asset::RenderAssetUsages::RENDER_WORLD;
+    srgb.texture_descriptor.usage = TextureUsages::COPY_DST
+        | TextureUsages::COPY_SRC
+        | TextureUsages::STORAGE_BINDING
+        | TextureUsages::TEXTURE_BINDING;
+    srgb.texture_descriptor.view_formats = &[TextureFormat::Rgba8UnormSrgb];
+    srgb.texture_view_descriptor = Some(TextureViewDescriptor {
+        format: Some(TextureFormat::Rgba8UnormSrgb),
+        ..default()
     });
+    srgb.sampler = filter.sampler();
+    srgb
+}
 
-    // Initialize agents (agent module takes care of CPU/GPU agent resources)
-    let species_count = crate::NUM_SPECIES; // kept simple; authoring plugin can update later
-    agents::init_agents(
-        &mut commands,
-        &render_device,
-        SIZE,
-        NUM_AGENTS,
-        species_count,
-    );
+/// Follow the window size: reallocate the display, pheromone array, and
+/// obstacle textures at the new size, update `screen_size`, and clamp agents
+/// back inside the new bounds. Pheromone state is not carried over. With a
+/// fixed `SimResolution` only the sprite is rescaled to cover the window.
+///
+/// The size is read from the primary window itself rather than from resize
+/// events, so a windowed launch smaller or larger than `SIZE` (which may not
+/// report a resize) is picked up on the first frame and the cursor mapping,
+/// which goes through the sprite's size and scale, matches what is on screen.
+#[allow(clippy::too_many_arguments)]
+pub fn handle_window_resize(
+    mut commands: Commands,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut images: ResMut<Assets<Image>>,
+    mut sim_size: ResMut<SimulationSize>,
+    mut globals: ResMut<GlobalUniforms>,
+    mut agents_cpu: ResMut<agents::AgentsCpu>,
+    phero_cfg: Res<PheromoneConfig>,
+    resolution: Res<SimResolution>,
+    display_filter: Res<DisplayFilter>,
+    display: Single<(&mut Sprite, &mut Transform)>,
+    mut last_window: Local<Option<UVec2>>,
+) {
+    // None in headless bench runs
+    let Ok(window) = windows.single() else {
+        return;
+    };
+    let display_size = window.size().as_uvec2().max(UVec2::ONE);
+    if *last_window == Some(display_size) {
+        return;
+    }
+    *last_window = Some(display_size);
+    let (mut sprite, mut transform) = display.into_inner();
+    let new_size = sim_size_for_display(display_size, resolution.0);
+    sprite.custom_size = Some(new_size.as_vec2());
+    transform.scale = display_scale(display_size, new_size).extend(1.0);
+    if new_size == sim_size.0 {
+        return;
+    }
+    info!("Simulation: resizing {} -> {}", sim_size.0, new_size);
+    sim_size.0 = new_size;
 
-    // Species GPU buffer is uploaded by species::upload_species_to_gpu during Startup
+    let display_images = make_display_images(&mut images, new_size, *display_filter);
+    sprite.image = display_images.texture_a.clone();
+    commands.insert_resource(display_images);
+
+    let layer_count = phero_cfg.layer_count.max(1);
+    commands.insert_resource(make_pheromone_array_images(&mut images, layer_count, new_size));
+    init_obstacle_mask(&mut commands, &mut images, new_size);
+
+    globals.screen_size = new_size.as_vec2();
+    let max = (new_size.as_vec2() - Vec2::ONE).max(Vec2::ZERO);
+    for agent in agents_cpu.list.iter_mut() {
+        agent.position = agent.position.clamp(Vec2::ZERO, max);
+    }
+    agents_cpu.upload_from = 0;
 }
 
-pub fn switch_textures(images: Res<PheromoneImages>, mut sprite: Single<&mut Sprite>) {
+/// Simulation size for a display of `display` pixels: the fixed `resolution`
+/// if set, else the display divided by `DISPLAY_FACTOR`.
+pub fn sim_size_for_display(display: UVec2, resolution: Option<UVec2>) -> UVec2 {
+    resolution.unwrap_or(display / DISPLAY_FACTOR.max(1)).max(UVec2::ONE)
+}
+
+/// Sprite `Transform` scale that stretches a `sim`-sized sprite (its
+/// `custom_size`) over `display` pixels; per axis, since a fixed
+/// `SimResolution` need not share the window's aspect ratio.
+pub fn display_scale(display: UVec2, sim: UVec2) -> Vec2 {
+    display.as_vec2() / sim.max(UVec2::ONE).as_vec2()
+}
+
+pub fn switch_textures(
+    images: Res<PheromoneImages>,
+    srgb_display: Res<SrgbDisplay>,
+    mut sprite: Single<&mut Sprite>,
+) {
+    if srgb_display.0 {
+        // One encode target, rewritten every frame from whichever ping is current
+        if sprite.image != images.display_srgb {
+            sprite.image = images.display_srgb.clone();
+        }
+        return;
+    }
     if sprite.image == images.texture_a {
         sprite.image = images.texture_b.clone();
     } else {
@@ -167,20 +425,29 @@ pub fn switch_textures(images: Res<PheromoneImages>, mut sprite: Single<&mut Spr
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn update_globals_uniform(
     mouse_pos: Res<crate::input::MouseWorldPos>,
     mouse_button_state: Res<crate::input::MouseButtonState>,
+    boundary: Res<crate::resources::BoundaryMode>,
+    phero_cfg: Res<PheromoneConfig>,
     mut globals: ResMut<GlobalUniforms>,
+    sprites: Query<(&Sprite, &Transform)>,
     time: Res<Time>,
+    max_dt: Res<crate::resources::MaxDeltaTime>,
+    timestep: Res<crate::resources::SimTimestep>,
+    run_config: Res<AgentSimRunConfig>,
 ) {
-    // Convert world coordinates (affected by sprite display scale) to texture pixel coords
-    let mut tex = (mouse_pos.0 / (crate::DISPLAY_FACTOR as f32)) + globals.screen_size / 2.0;
-    // Clamp to texture bounds to avoid NaNs in shaders when off-screen
-    tex.x = tex.x.clamp(0.0, globals.screen_size.x - 1.0);
-    tex.y = tex.y.clamp(0.0, globals.screen_size.y - 1.0);
-    tex.y = globals.screen_size.y - tex.y;
-    globals.mouse_position = tex;
-    globals.delta_time = time.delta_secs();
+    // On-screen size of the display sprite: its sim-sized `custom_size`
+    // times the display scale
+    let display_size = sprites
+        .iter()
+        .next()
+        .and_then(|(s, t)| s.custom_size.map(|size| size * t.scale.truncate()))
+        .unwrap_or(globals.screen_size);
+    globals.mouse_position = crate::input::cursor_to_texel(mouse_pos.0, display_size, globals.screen_size);
+    // Scaled after the hitch cap, so fast-forward still takes larger steps
+    globals.delta_time = timestep.frame_dt(time.delta_secs(), &max_dt) * run_config.time_scale;
     globals.frame += 1;
     globals.left_button_pressed = if mouse_button_state.left_pressed {
         1
@@ -192,29 +459,140 @@ pub fn update_globals_uniform(
     } else {
         0
     };
+    globals.wrap_edges = match *boundary {
+        crate::resources::BoundaryMode::Bounce => 0,
+        crate::resources::BoundaryMode::Wrap => 1,
+    };
+    globals.wrap_diffusion = u32::from(phero_cfg.diffusion_wraps(*boundary));
+}
+
+// Overlay label: brush layer with its base rates, values under the cursor,
+// the active species' weight for that layer, agent count, frame timing, then
+// any notice.
+// Paused stages and layer stats (when enabled) go on their own lines.
+#[allow(clippy::too_many_arguments)]
+fn layer_label(
+    layer: u32,
+    layers: &PheromoneLayerParamsCpu,
+    probe: &PheromoneProbe,
+    active: &ActiveSpecies,
+    notice: &OverlayNotice,
+    stats: &PheromoneStats,
+    run_config: &AgentSimRunConfig,
+    target: BrushTarget,
+    agent_count: usize,
+    fps_disp: &str,
+    ms_disp: &str,
+) -> String {
+    let follows = match target {
+        BrushTarget::Manual => String::new(),
+        BrushTarget::FollowSpecies => format!(" (species {})", active.index),
+    };
+    let rates = layers
+        .params
+        .get(layer as usize)
+        .map(|p| {
+            let frozen = if p.frozen != 0 { ", frozen" } else { "" };
+            let conserving = if p.conserve_mass != 0 { ", conserving" } else { "" };
+            let flow = if p.flow_strength > 0.0 {
+                let degrees = p.flow_dir.y.atan2(p.flow_dir.x).to_degrees();
+                format!(", flow {:.0}px/s @ {degrees:.0}deg", p.flow_strength)
+            } else {
+                String::new()
+            };
+            let floor = if p.decay_floor > 0.0 { format!(" to {:.2}", p.decay_floor) } else { String::new() };
+            let amount = if p.brush_amount != 1.0 { format!(", brush x{:.2}", p.brush_amount) } else { String::new() };
+            format!(" (diff {:.2}, decay {:.2}{floor}{frozen}{conserving}{flow}{amount})", p.diffusion, p.decay)
+        })
+        .unwrap_or_default();
+    let values: Vec<String> = probe.values.iter().map(|v| format!("{v:.3}")).collect();
+    let cursor = if values.is_empty() { "--".to_string() } else { values.join(" ") };
+    let species = active
+        .weight
+        .map(|w| format!(" | Species {}: w {w:+.2}", active.index))
+        .unwrap_or_default();
+    let notice = notice.visible().map(|n| format!(" | {n}")).unwrap_or_default();
+    let stats = if stats.layers.is_empty() {
+        String::new()
+    } else {
+        format!("\nMin/mean/max: {}", stats_label(&stats.layers))
+    };
+    let stages = run_config.stages_label().map(|s| format!("\n{s}")).unwrap_or_default();
+    format!(
+        "Layer: {layer}{follows}{rates} | Cursor: {cursor}{species} | Agents: {agent_count} | FPS: {fps_disp} | ms: {ms_disp}{notice}{stages}{stats}"
+    )
 }
 
-// Keep the on-screen label in sync with the current brush layer
+// The window clear color follows `Background`, so transparent composite
+// pixels still show the chosen color on screen
+pub fn sync_clear_color(background: Res<crate::resources::Background>, mut clear: ResMut<ClearColor>) {
+    if background.is_changed() {
+        clear.0 = background.color;
+    }
+}
+
+// Keep the on-screen label in sync with the current brush layer and its rates
+#[allow(clippy::too_many_arguments)]
 pub fn update_brush_layer_text(
     cfg: Res<crate::resources::PheromoneConfig>,
+    layers: Res<PheromoneLayerParamsCpu>,
+    probe: Res<PheromoneProbe>,
+    active: Res<ActiveSpecies>,
+    notice: Res<OverlayNotice>,
+    stats: Res<PheromoneStats>,
+    run_config: Res<AgentSimRunConfig>,
+    target: Res<BrushTarget>,
+    agents: Res<agents::AgentsCpu>,
     fps: Res<FpsCounter>,
+    mut last_agent_count: Local<usize>,
     mut q: Query<&mut Text, With<BrushLayerText>>,
 ) {
-    if !cfg.is_changed() { return; }
+    let changed = cfg.is_changed() || layers.is_changed() || probe.is_changed() || active.is_changed();
+    let overlay_changed = notice.is_changed() || stats.is_changed() || run_config.is_changed() || target.is_changed();
+    // AgentsCpu also changes on plain re-uploads; only a new count matters here
+    let agent_count = agents.list.len();
+    let count_changed = agent_count != *last_agent_count;
+    *last_agent_count = agent_count;
+    if !changed && !overlay_changed && !count_changed { return; }
     for mut t in &mut q {
         let fps_disp = if fps.fps > 0.0 { format!("{:.0}", fps.fps) } else { "--".to_string() };
         let ms_disp = if fps.ms > 0.0 { format!("{:.1}", fps.ms) } else { "--".to_string() };
-        *t = Text::new(format!("Layer: {} | FPS: {} | ms: {}", cfg.brush_target_layer, fps_disp, ms_disp));
+        *t = Text::new(layer_label(
+            cfg.brush_target_layer,
+            &layers,
+            &probe,
+            &active,
+            &notice,
+            &stats,
+            &run_config,
+            *target,
+            agent_count,
+            &fps_disp,
+            &ms_disp,
+        ));
     }
 }
 
 // Update FPS every ~0.25s and refresh the label text
+#[allow(clippy::too_many_arguments)]
 pub fn update_fps_counter(
     time: Res<Time>,
     cfg: Res<crate::resources::PheromoneConfig>,
+    layers: Res<PheromoneLayerParamsCpu>,
+    probe: Res<PheromoneProbe>,
+    active: Res<ActiveSpecies>,
+    mut notice: ResMut<OverlayNotice>,
+    stats: Res<PheromoneStats>,
+    run_config: Res<AgentSimRunConfig>,
+    target: Res<BrushTarget>,
+    agents: Res<agents::AgentsCpu>,
     mut counter: ResMut<FpsCounter>,
     mut q: Query<&mut Text, With<BrushLayerText>>,
 ) {
+    // Only touch the notice while it counts down so change detection stays quiet
+    if notice.secs_left > 0.0 {
+        notice.secs_left -= time.delta_secs();
+    }
     counter.acc_time += time.delta_secs();
     counter.frames += 1;
     if counter.acc_time >= 0.25 {
@@ -227,34 +605,155 @@ pub fn update_fps_counter(
         let fps_disp = format!("{:.0}", counter.fps);
         let ms_disp = format!("{:.1}", counter.ms);
         for mut t in &mut q {
-            *t = Text::new(format!("Layer: {} | FPS: {} | ms: {}", cfg.brush_target_layer, fps_disp, ms_disp));
+            *t = Text::new(layer_label(
+                cfg.brush_target_layer,
+                &layers,
+                &probe,
+                &active,
+                &notice,
+                &stats,
+                &run_config,
+                *target,
+                agents.list.len(),
+                &fps_disp,
+                &ms_disp,
+            ));
         }
     }
 }
 
 // Precompute per-frame diffusion/decay factors on CPU and upload to GPU buffer
+#[allow(clippy::too_many_arguments)]
 pub fn update_layer_params_buffer(
     time: Res<Time>,
+    max_dt: Res<crate::resources::MaxDeltaTime>,
+    timestep: Res<crate::resources::SimTimestep>,
+    run_config: Res<AgentSimRunConfig>,
+    quality: Res<crate::auto_quality::QualityLevel>,
     cpu: Res<PheromoneLayerParamsCpu>,
+    decay_bias: Option<Res<LayerDecayBias>>,
     params_buf: Res<PheromoneLayerParamsBuffer>,
     queue: Res<bevy::render::renderer::RenderQueue>,
 ) {
-    let dt = time.delta_secs();
-    if dt <= 0.0 { return; }
-    // Helper: per-frame factor = 1 - (1 - rate)^dt
-    fn per_frame_factor(rate: f32, dt: f32) -> f32 {
-        let base = 1.0 - rate;
-        1.0 - base.powf(dt)
-    }
+    // Same capped, scaled step as the agents so trails and movement stay in
+    // sync; a frozen sim (scale 0) uploads zero factors
+    let frame_dt = timestep.frame_dt(time.delta_secs(), &max_dt);
+    if frame_dt <= 0.0 { return; }
+    let dt = frame_dt * run_config.time_scale;
+    // Each diffusion pass blurs fully but decays and drifts for its share of
+    // the frame, so extra passes speed up spreading without shortening trails.
+    // Split over the passes the render node actually runs (auto quality may cap them)
+    let steps = quality.diffuse_steps(run_config.diffuse_steps()) as f32;
     let mut upload: Vec<PheromoneLayerParam> = Vec::with_capacity(cpu.params.len());
-    for p in cpu.params.iter() {
+    for (i, p) in cpu.params.iter().enumerate() {
+        // Species-driven trail lifetime: scale the base decay rate for this layer
+        let bias = decay_bias.as_ref().map_or(1.0, |b| b.get(i));
+        let decay = (p.decay * bias).clamp(0.0, 1.0);
         upload.push(PheromoneLayerParam {
             diffusion: per_frame_factor(p.diffusion, dt),
-            decay: per_frame_factor(p.decay, dt),
-            _pad0: 0.0,
-            _pad1: 0.0,
+            decay: per_frame_factor(decay, dt / steps),
+            kernel: p.kernel,
+            frozen: p.frozen,
             color: p.color,
+            max_value: p.max_value,
+            diagonal_weight: p.diagonal_weight,
+            diffuse_enabled: p.diffuse_enabled,
+            decay_enabled: p.decay_enabled,
+            // Flow stays in texels/second; the shader scales it by delta_time
+            flow_dir: p.flow_dir,
+            flow_strength: p.flow_strength / steps,
+            decay_floor: p.decay_floor,
+            brush_amount: p.brush_amount,
+            conserve_mass: p.conserve_mass,
+            _pad: [0; 2],
         });
     }
     queue.write_buffer(&params_buf.buffer, 0, bytemuck::cast_slice(&upload));
 }
+
+/// Per-frame factor for a per-second rate: `1 - (1 - rate)^dt`. Rate 0 gives
+/// exactly 0 (no change) and rate 1 gives exactly 1 for any dt > 0. The rate
+/// is clamped to [0, 1] and dt to >= 0 (non-finite inputs count as 0), so the
+/// factor always lands in [0, 1] however the params are tweaked.
+pub fn per_frame_factor(rate: f32, dt: f32) -> f32 {
+    let rate = if rate.is_finite() { rate.clamp(0.0, 1.0) } else { 0.0 };
+    let dt = if dt.is_finite() { dt.max(0.0) } else { 0.0 };
+    let base = 1.0 - rate;
+    1.0 - base.powf(dt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sim_size_divides_the_display_and_scale_restores_it() {
+        let f = DISPLAY_FACTOR;
+        let display = UVec2::new(800, 600) * f;
+        let sim = sim_size_for_display(display, None);
+        assert_eq!(sim, UVec2::new(800, 600));
+        assert_eq!(display_scale(display, sim), Vec2::splat(f as f32));
+        // A fixed resolution wins, scaled per axis to fill the display
+        let fixed = sim_size_for_display(display, Some(UVec2::new(400, 100)));
+        assert_eq!(fixed, UVec2::new(400, 100));
+        assert_eq!(display_scale(display, fixed) * fixed.as_vec2(), display.as_vec2());
+        // A minimized window still gets a 1x1 simulation
+        assert_eq!(sim_size_for_display(UVec2::ZERO, None), UVec2::ONE);
+    }
+
+    #[test]
+    fn per_frame_factor_edge_rates() {
+        for dt in [1.0 / 240.0, 1.0 / 60.0, 0.1, 1.0] {
+            assert_eq!(per_frame_factor(0.0, dt), 0.0);
+            assert_eq!(per_frame_factor(1.0, dt), 1.0);
+        }
+        // one second at rate r removes exactly r
+        assert!((per_frame_factor(0.7, 1.0) - 0.7).abs() < 1e-6);
+        // two half steps compound to one full step
+        let half = per_frame_factor(0.5, 0.5);
+        assert!((1.0 - (1.0 - half) * (1.0 - half) - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn per_frame_factor_stays_finite_and_bounded() {
+        let rates = (0..=20).map(|i| i as f32 / 20.0).chain([-0.5, 1.5, f32::NAN, f32::INFINITY]);
+        for rate in rates {
+            for dt in [0.0, 1e-6, 1.0 / 240.0, 1.0 / 30.0, 1.0, 10.0, f32::NAN, f32::INFINITY, -1.0] {
+                let f = per_frame_factor(rate, dt);
+                assert!(f.is_finite() && (0.0..=1.0).contains(&f), "rate {rate}, dt {dt}: {f}");
+            }
+        }
+        // out-of-range rates behave like the nearest valid one
+        assert_eq!(per_frame_factor(1.5, 0.5), per_frame_factor(1.0, 0.5));
+        assert_eq!(per_frame_factor(-0.5, 0.5), 0.0);
+    }
+
+    #[test]
+    fn overlay_notice_expires() {
+        let mut notice = OverlayNotice::default();
+        assert_eq!(notice.visible(), None);
+        notice.flash("reload failed");
+        assert_eq!(notice.visible(), Some("reload failed"));
+        notice.secs_left -= NOTICE_SECS;
+        assert_eq!(notice.visible(), None);
+    }
+
+    #[test]
+    fn default_layer_params_enable_diffusion_and_decay() {
+        let params = default_layer_params(7);
+        assert!(params.iter().all(|p| p.diffuse_enabled == 1 && p.decay_enabled == 1));
+    }
+
+    #[test]
+    fn apply_palette_recolors_and_restores() {
+        let defaults = default_layer_params(5);
+        let mut params = defaults.clone();
+        assert!(apply_palette(&mut params, "ocean"));
+        assert_ne!(params[2].color, defaults[2].color);
+        assert_eq!(params[2].decay, defaults[2].decay);
+        assert!(!apply_palette(&mut params, "plaid"));
+        assert!(apply_palette(&mut params, "default"));
+        assert!(params.iter().zip(&defaults).all(|(a, b)| a.color == b.color));
+        assert!(palette_names().all(|name| apply_palette(&mut params, name)));
+    }
+}
@@ -2,16 +2,23 @@
 //
 // This module provides a compact authoring API (components such as
 // `MoveSpeed`, `Sensor`, and `EmitsPheromone`) that are attached to a small
-// number of species entities. Those entities are then converted into a tightly
-// packed `SpeciesSettings` buffer uploaded to the GPU for use by the agent
-// compute shader.
+// number of species entities, plus `SpeciesBuilder` which assembles and
+// validates those components against the configured layer count. Those
+// entities are then converted into a tightly packed `SpeciesSettings` buffer
+// uploaded to the GPU for use by the agent compute shader.
 
-use crate::resources::SpeciesSettings;
+use crate::agents::HeadingMode;
+use crate::resources::{
+    DepositMode, MAX_DEPOSIT_RADIUS, MAX_EXTRA_EMITS, MAX_SENSORS, MAX_SENSOR_SIZE, ResponseCurve, SpeciesSettings,
+    SteerMode,
+};
 use bevy::math::Vec4;
 use bevy::prelude::*;
 use bevy::render::render_resource::{BufferInitDescriptor, BufferUsages};
 use bevy::render::renderer::RenderDevice;
-use crate::resources::{PheromoneConfig, SpeciesLayerWeights};
+use crate::resources::{GlobalUniforms, LayerDecayBias, PheromoneConfig, SpeciesLayerWeights};
+use crate::setup::OverlayNotice;
+use serde::{Deserialize, Serialize};
 
 // Authoring helpers used by the app to assemble `SpeciesSettings` that are
 // uploaded to the GPU. These helpers are intentionally small and tested below.
@@ -33,7 +40,18 @@ pub struct TurnSpeed(pub f32);
 pub struct Sensor {
     pub angle_degrees: f32,
     pub offset_dst: f32,
+    /// Radius in texels: each reading averages the (2*size+1)^2 block around
+    /// the sensor position (fractional part dropped). 0 reads a single texel,
+    /// bilinearly filtered when `SensorSampling` allows it.
     pub size: f32,
+    pub count: u32,
+    /// Search wider in weak fields: the offset grows from `offset_dst`
+    /// towards `offset_max` as the forward reading drops.
+    pub adaptive: bool,
+    pub offset_max: f32,
+    /// Half-arc in degrees the `count` sensors spread evenly over, set apart
+    /// from `angle_degrees`; 0 = spread over `angle_degrees`.
+    pub spread_degrees: f32,
 }
 
 #[derive(Component, Deref, DerefMut)]
@@ -45,37 +63,600 @@ pub struct AgentColor(pub Vec4);
 pub struct EmitsPheromone {
     pub channel: u32,
     pub amount: f32,
+    pub mode: DepositMode,
+    /// Scales the decay of the emit layer (>1 shorter trails, <1 longer)
+    pub decay_bias: f32,
+}
+
+// Further deposits beyond the primary `EmitsPheromone` (at most
+// `MAX_EXTRA_EMITS` are uploaded). The primary's mode applies to all of them.
+#[derive(Component, Default, Deref, DerefMut)]
+pub struct ExtraEmits(pub Vec<EmitsPheromone>);
+
+// Optional random heading jitter (radians per second); absent = none
+#[derive(Component, Deref, DerefMut)]
+pub struct Wander(pub f32);
+
+// Optional cap on heading change per step (radians); absent = uncapped
+#[derive(Component, Deref, DerefMut)]
+pub struct MaxTurn(pub f32);
+
+// Optional per-agent speed spread (fraction of move_speed, 0..=1); absent = none
+#[derive(Component, Deref, DerefMut)]
+pub struct SpeedJitter(pub f32);
+
+// Optional agent lifespan in seconds; absent = immortal
+#[derive(Component, Deref, DerefMut)]
+pub struct MaxAge(pub f32);
+
+// Optional constant heading drift (radians/s, positive = clockwise); absent = none
+#[derive(Component, Deref, DerefMut)]
+pub struct TurnBias(pub f32);
+
+// Optional deposit footprint radius in texels; absent = single texel
+#[derive(Component, Deref, DerefMut)]
+pub struct DepositRadius(pub f32);
+
+// Optional steering mode; absent = discrete sensors
+#[derive(Component, Deref, DerefMut)]
+pub struct Steering(pub SteerMode);
+
+// Optional sensing response curve; absent = linear
+#[derive(Component, Deref, DerefMut)]
+pub struct SensorResponse(pub ResponseCurve);
+
+// Optional initial heading; absent = the spawn pattern's
+#[derive(Component, Deref, DerefMut)]
+pub struct SpeciesHeading(pub HeadingMode);
+
+// Optional same-species avoidance (max turn away from crowding, radians/s);
+// absent = agents ignore each other
+#[derive(Component, Deref, DerefMut)]
+pub struct Avoidance(pub f32);
+
+// Optional deposit gate (min weighted reading ahead); absent = always deposit
+#[derive(Component, Deref, DerefMut)]
+pub struct DepositThreshold(pub f32);
+
+// Optional deposit fatigue rate (see `SpeciesSettings::deposit_fatigue`);
+// absent = deposits never weaken
+#[derive(Component, Deref, DerefMut)]
+pub struct DepositFatigue(pub f32);
+
+// Optional deposit into `PheromoneConfig::shared_layer` (amount per second);
+// absent = none
+#[derive(Component, Deref, DerefMut)]
+pub struct SharedEmit(pub f32);
+
+// Runtime-only pause flag: false = the species' agents hold still and deposit
+// nothing. Always present and flipped in place, so pausing never moves the
+// entity to another archetype and reshuffles species indices. Not authored
+// in species files.
+#[derive(Component, Clone, Copy, Deref, DerefMut)]
+pub struct SpeciesEnabled(pub bool);
+
+impl Default for SpeciesEnabled {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+/// Flip the pause flag of the species at `index` (upload order) and return
+/// its new state; `None` if there is no such species.
+pub fn toggle_species_enabled(
+    species: &mut Query<Option<&mut SpeciesEnabled>, With<AgentSpecies>>,
+    index: usize,
+) -> Option<bool> {
+    let mut enabled = species.iter_mut().nth(index)??;
+    enabled.0 = !enabled.0;
+    Some(enabled.0)
 }
 
 // Optional per-species arrays for L-layer sensing weights
 #[derive(Component, Deref, DerefMut)]
 pub struct LayerWeights(pub Vec<f32>);
 
-#[allow(dead_code)]
-pub struct SpeciesAuthoringPlugin;
+/// All authoring components for one species entity, as produced by
+/// `SpeciesBuilder::build`. No emission is `amount: 0.0`; no weight overrides
+/// is an empty `LayerWeights`; single-emit species have an empty `ExtraEmits`.
+#[derive(Bundle)]
+pub struct SpeciesBundle {
+    pub marker: AgentSpecies,
+    pub color: AgentColor,
+    pub move_speed: MoveSpeed,
+    pub turn_speed: TurnSpeed,
+    pub sensor: Sensor,
+    pub emit: EmitsPheromone,
+    pub extra_emits: ExtraEmits,
+    pub weights: LayerWeights,
+    pub wander: Wander,
+    pub max_turn: MaxTurn,
+    pub steering: Steering,
+    pub speed_jitter: SpeedJitter,
+    pub max_age: MaxAge,
+    pub turn_bias: TurnBias,
+    pub deposit_radius: DepositRadius,
+    pub response: SensorResponse,
+    pub heading: SpeciesHeading,
+    pub avoidance: Avoidance,
+    pub deposit_threshold: DepositThreshold,
+    pub deposit_fatigue: DepositFatigue,
+    pub shared_emit: SharedEmit,
+    pub enabled: SpeciesEnabled,
+}
+
+/// Chained construction of a species with layer-index validation.
+///
+/// ```ignore
+/// let bundle = SpeciesBuilder::new()
+///     .color(Vec4::new(1.0, 0.0, 0.0, 1.0))
+///     .move_speed(60.0)
+///     .follows(2)
+///     .avoids(3)
+///     .emits(2, 0.8)
+///     .build(phero_cfg.layer_count)?;
+/// commands.spawn(bundle);
+/// ```
+#[derive(Clone, Debug)]
+pub struct SpeciesBuilder {
+    color: Vec4,
+    move_speed: f32,
+    turn_speed: f32,
+    sensor_angle_degrees: f32,
+    sensor_offset_dst: f32,
+    sensor_size: f32,
+    sensor_count: u32,
+    sensor_spread_degrees: f32,
+    sensor_offset_max: Option<f32>,
+    emit: Option<(u32, f32)>,
+    extra_emits: Vec<(u32, f32)>,
+    deposit_mode: DepositMode,
+    emit_decay_bias: f32,
+    weights: Vec<(u32, f32)>,
+    wander: f32,
+    max_turn: f32,
+    steer_mode: SteerMode,
+    speed_jitter: f32,
+    max_age: f32,
+    turn_bias: f32,
+    deposit_radius: f32,
+    response_curve: ResponseCurve,
+    heading_mode: HeadingMode,
+    avoid_strength: f32,
+    deposit_threshold: f32,
+    deposit_fatigue: f32,
+    shared_emit_amount: f32,
+}
 
-impl Plugin for SpeciesAuthoringPlugin {
-    fn build(&self, app: &mut App) {
-        app.add_systems(Startup, spawn_default_species);
+impl Default for SpeciesBuilder {
+    fn default() -> Self {
+        let s = SpeciesSettings::default();
+        Self {
+            color: s.color,
+            move_speed: s.move_speed,
+            turn_speed: s.turn_speed,
+            sensor_angle_degrees: s.sensor_angle_degrees,
+            sensor_offset_dst: s.sensor_offset_dst,
+            sensor_size: s.sensor_size,
+            sensor_count: s.sensor_count,
+            sensor_spread_degrees: s.sensor_spread_degrees,
+            sensor_offset_max: None,
+            emit: None,
+            extra_emits: Vec::new(),
+            deposit_mode: DepositMode::Constant,
+            emit_decay_bias: s.emit_decay_bias,
+            weights: Vec::new(),
+            wander: s.wander_strength,
+            max_turn: s.max_turn_per_step,
+            steer_mode: SteerMode::Sensors,
+            speed_jitter: s.speed_jitter,
+            max_age: s.max_age,
+            turn_bias: s.turn_bias,
+            deposit_radius: s.deposit_radius,
+            response_curve: ResponseCurve::Linear,
+            heading_mode: HeadingMode::Pattern,
+            avoid_strength: s.avoid_strength,
+            deposit_threshold: s.deposit_threshold,
+            deposit_fatigue: s.deposit_fatigue,
+            shared_emit_amount: s.shared_emit_amount,
+        }
     }
 }
 
-// Helper to construct a species entity from authored components directly
+impl SpeciesBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder pre-filled from a file-side definition.
+    pub fn from_def(def: &SpeciesDef) -> Self {
+        let mut b = Self::new()
+            .color(Vec4::from_array(def.color))
+            .move_speed(def.move_speed)
+            .turn_speed(def.turn_speed)
+            .sensor(def.sensor.angle_degrees, def.sensor.offset_dst, def.sensor.size)
+            .sensor_count(def.sensor.count)
+            .sensor_spread(def.sensor.spread_degrees)
+            .wander(def.wander.unwrap_or(0.0));
+        if let Some(max_turn) = def.max_turn {
+            b = b.max_turn(max_turn);
+        }
+        if let Some(mode) = def.steer_mode {
+            b = b.steer_mode(mode);
+        }
+        if let Some(jitter) = def.speed_jitter {
+            b = b.speed_jitter(jitter);
+        }
+        if let Some(max_age) = def.max_age {
+            b = b.max_age(max_age);
+        }
+        if let Some(bias) = def.turn_bias {
+            b = b.turn_bias(bias);
+        }
+        if let Some(radius) = def.deposit_radius {
+            b = b.deposit_radius(radius);
+        }
+        if let Some(curve) = def.response_curve {
+            b = b.response_curve(curve);
+        }
+        if let Some(mode) = def.heading_mode {
+            b = b.heading_mode(mode);
+        }
+        if let Some(strength) = def.avoidance {
+            b = b.avoidance(strength);
+        }
+        if let Some(threshold) = def.deposit_threshold {
+            b = b.deposit_threshold(threshold);
+        }
+        if let Some(fatigue) = def.deposit_fatigue {
+            b = b.deposit_fatigue(fatigue);
+        }
+        if let Some(amount) = def.shared_emit {
+            b = b.shared_emit(amount);
+        }
+        if def.sensor.adaptive {
+            b = b.adaptive_sensor(def.sensor.offset_max);
+        }
+        if let Some(emit) = &def.emit {
+            b = b
+                .emits(emit.channel, emit.amount)
+                .deposit_mode(emit.mode)
+                .emit_decay_bias(emit.decay_bias);
+        }
+        for extra in &def.extra_emits {
+            b = b.also_emits(extra.channel, extra.amount);
+        }
+        for (layer, w) in def.layer_weights.iter().flatten().enumerate() {
+            b = b.layer_weight(layer as u32, *w);
+        }
+        b
+    }
+
+    pub fn color(mut self, color: Vec4) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn move_speed(mut self, speed: f32) -> Self {
+        self.move_speed = speed;
+        self
+    }
+
+    pub fn turn_speed(mut self, speed: f32) -> Self {
+        self.turn_speed = speed;
+        self
+    }
+
+    pub fn sensor(mut self, angle_degrees: f32, offset_dst: f32, size: f32) -> Self {
+        self.sensor_angle_degrees = angle_degrees;
+        self.sensor_offset_dst = offset_dst;
+        self.sensor_size = size;
+        self
+    }
+
+    /// Sensors fanned over the sensor arc (1 to `MAX_SENSORS`). One sensor
+    /// points straight ahead with no left/right pair, so the fan never turns
+    /// the agent: it runs straight through the field (wander, turn bias and
+    /// edges still turn it) and only reads it for adaptive offsets and
+    /// deposit gating.
+    pub fn sensor_count(mut self, count: u32) -> Self {
+        self.sensor_count = count;
+        self
+    }
+
+    /// Spread the sensors evenly over +/-`degrees` instead of the sensor
+    /// angle (0 = use the sensor angle).
+    pub fn sensor_spread(mut self, degrees: f32) -> Self {
+        self.sensor_spread_degrees = degrees;
+        self
+    }
+
+    /// Let the sensor offset grow up to `offset_max` when the field is weak.
+    pub fn adaptive_sensor(mut self, offset_max: f32) -> Self {
+        self.sensor_offset_max = Some(offset_max);
+        self
+    }
+
+    /// Deposit `amount` into `layer` each step.
+    pub fn emits(mut self, layer: u32, amount: f32) -> Self {
+        self.emit = Some((layer, amount));
+        self
+    }
+
+    /// Additionally deposit `amount` into `layer` (e.g. a food trail next to
+    /// the species' own). Uses the primary emit's deposit mode.
+    pub fn also_emits(mut self, layer: u32, amount: f32) -> Self {
+        self.extra_emits.push((layer, amount));
+        self
+    }
+
+    /// Random heading jitter in radians per second.
+    pub fn wander(mut self, strength: f32) -> Self {
+        self.wander = strength;
+        self
+    }
+
+    /// Largest heading change applied in one step, in radians.
+    pub fn max_turn(mut self, radians: f32) -> Self {
+        self.max_turn = radians;
+        self
+    }
+
+    /// Discrete sensors (default) or gradient following.
+    pub fn steer_mode(mut self, mode: SteerMode) -> Self {
+        self.steer_mode = mode;
+        self
+    }
+
+    /// Per-agent speed spread as a fraction of `move_speed` (0..=1).
+    pub fn speed_jitter(mut self, jitter: f32) -> Self {
+        self.speed_jitter = jitter;
+        self
+    }
+
+    /// Agent lifespan in seconds; 0 = immortal.
+    pub fn max_age(mut self, seconds: f32) -> Self {
+        self.max_age = seconds;
+        self
+    }
+
+    /// Constant heading drift in radians per second (positive = clockwise).
+    pub fn turn_bias(mut self, radians_per_sec: f32) -> Self {
+        self.turn_bias = radians_per_sec;
+        self
+    }
+
+    /// Spread deposits over a disc of `radius` texels (< 1 = one texel).
+    pub fn deposit_radius(mut self, radius: f32) -> Self {
+        self.deposit_radius = radius;
+        self
+    }
+
+    /// How sensed layer values are shaped before weighting (default linear).
+    pub fn response_curve(mut self, curve: ResponseCurve) -> Self {
+        self.response_curve = curve;
+        self
+    }
+
+    /// Initial heading when agents are generated (default: the spawn pattern's).
+    pub fn heading_mode(mut self, mode: HeadingMode) -> Self {
+        self.heading_mode = mode;
+        self
+    }
+
+    /// Steer away from nearby agents of the same species at up to `strength`
+    /// radians per second (0 = off).
+    pub fn avoidance(mut self, strength: f32) -> Self {
+        self.avoid_strength = strength;
+        self
+    }
+
+    /// Only deposit while the weighted reading ahead is at least `threshold`
+    /// (0 = always deposit).
+    pub fn deposit_threshold(mut self, threshold: f32) -> Self {
+        self.deposit_threshold = threshold;
+        self
+    }
+
+    /// Weaken deposits at `rate` per second while agents linger on their own
+    /// strong trail, recovering on fresh ground (0 = off).
+    pub fn deposit_fatigue(mut self, rate: f32) -> Self {
+        self.deposit_fatigue = rate;
+        self
+    }
+
+    /// Also deposit `amount` per second into the config's shared layer (0 =
+    /// none; ignored without a shared layer).
+    pub fn shared_emit(mut self, amount: f32) -> Self {
+        self.shared_emit_amount = amount;
+        self
+    }
+
+    /// Decay multiplier for the emit layer (see `LayerDecayBias`).
+    pub fn emit_decay_bias(mut self, bias: f32) -> Self {
+        self.emit_decay_bias = bias;
+        self
+    }
+
+    pub fn deposit_mode(mut self, mode: DepositMode) -> Self {
+        self.deposit_mode = mode;
+        self
+    }
+
+    /// Sensing weight for one layer (positive attracts, negative repels).
+    pub fn layer_weight(mut self, layer: u32, weight: f32) -> Self {
+        self.weights.retain(|(l, _)| *l != layer);
+        self.weights.push((layer, weight));
+        self
+    }
+
+    pub fn follows(self, layer: u32) -> Self {
+        self.layer_weight(layer, 1.0)
+    }
+
+    pub fn avoids(self, layer: u32) -> Self {
+        self.layer_weight(layer, -1.0)
+    }
+
+    /// Check every referenced layer index against `layer_count`.
+    pub fn validate(&self, layer_count: u32) -> Result<(), String> {
+        if let Some((layer, _)) = self.emit
+            && layer >= layer_count
+        {
+            return Err(format!(
+                "emit layer {layer} is out of range (only {layer_count} pheromone layers)"
+            ));
+        }
+        if let Some((layer, _)) = self.extra_emits.iter().find(|(l, _)| *l >= layer_count) {
+            return Err(format!(
+                "extra emit layer {layer} is out of range (only {layer_count} pheromone layers)"
+            ));
+        }
+        if self.extra_emits.len() > MAX_EXTRA_EMITS {
+            return Err(format!(
+                "{} extra emits (at most {MAX_EXTRA_EMITS})",
+                self.extra_emits.len()
+            ));
+        }
+        if !(0.0..=MAX_SENSOR_SIZE).contains(&self.sensor_size) {
+            return Err(format!(
+                "sensor size {} is out of range (0 to {MAX_SENSOR_SIZE})",
+                self.sensor_size
+            ));
+        }
+        if !(1..=MAX_SENSORS).contains(&self.sensor_count) {
+            return Err(format!("sensor count {} is out of range (1 to {MAX_SENSORS})", self.sensor_count));
+        }
+        if !(0.0..=180.0).contains(&self.sensor_spread_degrees) {
+            return Err(format!(
+                "sensor spread {} is out of range (0 to 180 degrees)",
+                self.sensor_spread_degrees
+            ));
+        }
+        if self.max_age.is_nan() || self.max_age < 0.0 {
+            return Err(format!("max age {} must be >= 0", self.max_age));
+        }
+        if !(0.0..=MAX_DEPOSIT_RADIUS).contains(&self.deposit_radius) {
+            return Err(format!(
+                "deposit radius {} is out of range (0 to {MAX_DEPOSIT_RADIUS})",
+                self.deposit_radius
+            ));
+        }
+        if self.avoid_strength.is_nan() || self.avoid_strength < 0.0 {
+            return Err(format!("avoidance {} must be >= 0", self.avoid_strength));
+        }
+        if !(self.deposit_threshold.is_finite() && self.deposit_threshold >= 0.0) {
+            return Err(format!("deposit threshold {} must be finite and >= 0", self.deposit_threshold));
+        }
+        if !(self.deposit_fatigue.is_finite() && self.deposit_fatigue >= 0.0) {
+            return Err(format!("deposit fatigue {} must be finite and >= 0", self.deposit_fatigue));
+        }
+        if !self.shared_emit_amount.is_finite() {
+            return Err(format!("shared emit {} must be finite", self.shared_emit_amount));
+        }
+        if !self.turn_bias.is_finite() {
+            return Err(format!("turn bias {} must be finite", self.turn_bias));
+        }
+        if !(0.0..=1.0).contains(&self.speed_jitter) {
+            return Err(format!("speed jitter {} is out of range (0 to 1)", self.speed_jitter));
+        }
+        if let Some((layer, _)) = self.weights.iter().find(|(l, _)| *l >= layer_count) {
+            return Err(format!(
+                "sensing weight for layer {layer} is out of range (only {layer_count} pheromone layers)"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Validate and produce the component bundle.
+    pub fn build(self, layer_count: u32) -> Result<SpeciesBundle, String> {
+        self.validate(layer_count)?;
+        let mut weights = Vec::new();
+        if let Some(max_layer) = self.weights.iter().map(|(l, _)| *l).max() {
+            weights.resize(max_layer as usize + 1, 0.0);
+            for (layer, w) in &self.weights {
+                weights[*layer as usize] = *w;
+            }
+        }
+        let (channel, amount) = self.emit.unwrap_or((0, 0.0));
+        Ok(SpeciesBundle {
+            marker: AgentSpecies,
+            color: AgentColor(self.color),
+            move_speed: MoveSpeed(self.move_speed),
+            turn_speed: TurnSpeed(self.turn_speed),
+            sensor: Sensor {
+                angle_degrees: self.sensor_angle_degrees,
+                offset_dst: self.sensor_offset_dst,
+                size: self.sensor_size,
+                count: self.sensor_count,
+                adaptive: self.sensor_offset_max.is_some(),
+                offset_max: self.sensor_offset_max.unwrap_or(self.sensor_offset_dst),
+                spread_degrees: self.sensor_spread_degrees,
+            },
+            emit: EmitsPheromone {
+                channel,
+                amount,
+                mode: self.deposit_mode,
+                decay_bias: self.emit_decay_bias,
+            },
+            extra_emits: ExtraEmits(
+                self.extra_emits
+                    .iter()
+                    .map(|&(channel, amount)| EmitsPheromone {
+                        channel,
+                        amount,
+                        mode: self.deposit_mode,
+                        decay_bias: 1.0,
+                    })
+                    .collect(),
+            ),
+            weights: LayerWeights(weights),
+            wander: Wander(self.wander),
+            max_turn: MaxTurn(self.max_turn),
+            steering: Steering(self.steer_mode),
+            speed_jitter: SpeedJitter(self.speed_jitter),
+            max_age: MaxAge(self.max_age),
+            turn_bias: TurnBias(self.turn_bias),
+            deposit_radius: DepositRadius(self.deposit_radius),
+            response: SensorResponse(self.response_curve),
+            heading: SpeciesHeading(self.heading_mode),
+            avoidance: Avoidance(self.avoid_strength),
+            deposit_threshold: DepositThreshold(self.deposit_threshold),
+            deposit_fatigue: DepositFatigue(self.deposit_fatigue),
+            shared_emit: SharedEmit(self.shared_emit_amount),
+            enabled: SpeciesEnabled::default(),
+        })
+    }
+}
+
+// Helper to construct a species entity from authored components directly.
+// `emits` lists every deposit: the first is the primary (`emit_layer`, which
+// sets the deposit mode and decay bias), the rest fill the extra slots.
+// Deposit layers are independent of the sensing weights (`LayerWeights`):
+// a species may follow one layer and write another it never senses.
 pub fn build_species_settings_from_components(
     color: &AgentColor,
     move_speed: &MoveSpeed,
     turn_speed: &TurnSpeed,
     sensor: &Sensor,
-    emit: Option<&EmitsPheromone>,
+    emits: &[&EmitsPheromone],
 ) -> SpeciesSettings {
-    // Build emission: single layer index + amount (weights now handled directly into dense buffer)
-
-    // Build emission: single layer index + amount
+    // Build emission: primary layer index + amount (weights now handled directly into dense buffer)
     let mut emit_layer = 0u32;
     let mut emit_amount = 0.0f32;
-    if let Some(e) = emit {
+    let mut deposit_mode = DepositMode::Constant;
+    let mut emit_decay_bias = 1.0f32;
+    if let Some(e) = emits.first() {
         emit_layer = e.channel;
         emit_amount = e.amount;
+        deposit_mode = e.mode;
+        emit_decay_bias = e.decay_bias;
+    }
+    let mut extra_emit_layers = UVec4::ZERO;
+    let mut extra_emit_amounts = Vec4::ZERO;
+    for (slot, e) in emits.iter().skip(1).take(MAX_EXTRA_EMITS).enumerate() {
+        extra_emit_layers[slot] = e.channel;
+        extra_emit_amounts[slot] = e.amount;
     }
 
     SpeciesSettings {
@@ -84,75 +665,493 @@ pub fn build_species_settings_from_components(
         sensor_angle_degrees: sensor.angle_degrees,
         sensor_offset_dst: sensor.offset_dst,
         sensor_size: sensor.size,
+        sensor_count: sensor.count,
+        sensor_spread_degrees: sensor.spread_degrees,
+        sensor_adaptive: sensor.adaptive as u32,
+        sensor_offset_max: sensor.offset_max,
+        deposit_mode: deposit_mode as u32,
         color: **color,
         emit_layer,
         emit_amount,
-        _pad_emit: UVec2::ZERO,
+        emit_decay_bias,
+        extra_emit_layers,
+        extra_emit_amounts,
         ..Default::default()
     }
 }
 
-// Spawn three default agent species to match the current shader/channel assumptions (RGB)
-pub fn spawn_default_species(mut commands: Commands) {
-    // With extended layers: 0=hate, 1=love, 2..4 agent-specific
-    // Red species (channel 2): sprinter (very fast, low turning, narrow sensing)
-    commands.spawn((
+// File-side species description (see `assets/species.ron`). Colors are plain
+// arrays so the file format doesn't depend on glam's serde support.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SpeciesDef {
+    pub color: [f32; 4],
+    pub move_speed: f32,
+    pub turn_speed: f32,
+    pub sensor: SensorDef,
+    #[serde(default)]
+    pub emit: Option<EmitDef>,
+    /// Deposits in addition to `emit` (only `channel`/`amount` are used).
+    #[serde(default)]
+    pub extra_emits: Vec<EmitDef>,
+    #[serde(default)]
+    pub layer_weights: Option<Vec<f32>>,
+    #[serde(default)]
+    pub wander: Option<f32>,
+    /// Max heading change per step (radians); absent = uncapped.
+    #[serde(default)]
+    pub max_turn: Option<f32>,
+    /// `Sensors` or `Gradient`; absent = sensors.
+    #[serde(default)]
+    pub steer_mode: Option<SteerMode>,
+    /// Per-agent speed spread (0..=1); absent = uniform speed.
+    #[serde(default)]
+    pub speed_jitter: Option<f32>,
+    /// Agent lifespan in seconds; absent = immortal.
+    #[serde(default)]
+    pub max_age: Option<f32>,
+    /// Heading drift in radians/s (positive = clockwise); absent = none.
+    #[serde(default)]
+    pub turn_bias: Option<f32>,
+    /// Deposit footprint radius in texels; absent = single texel.
+    #[serde(default)]
+    pub deposit_radius: Option<f32>,
+    /// `Linear`, `Sqrt`, or `Log` shaping of sensed values; absent = linear.
+    #[serde(default)]
+    pub response_curve: Option<ResponseCurve>,
+    /// `Pattern`, `Inward`, `Outward`, or `Random` initial heading; absent = pattern.
+    #[serde(default)]
+    pub heading_mode: Option<HeadingMode>,
+    /// Turn away from crowding by the same species (radians/s); absent = off.
+    #[serde(default)]
+    pub avoidance: Option<f32>,
+    /// Deposit only while the weighted reading ahead reaches this; absent = always.
+    #[serde(default)]
+    pub deposit_threshold: Option<f32>,
+    /// Deposit weakening rate on the species' own strong trail (per second);
+    /// absent = off.
+    #[serde(default)]
+    pub deposit_fatigue: Option<f32>,
+    /// Amount per second also deposited into the config's `shared_layer`;
+    /// absent = none.
+    #[serde(default)]
+    pub shared_emit: Option<f32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SensorDef {
+    pub angle_degrees: f32,
+    pub offset_dst: f32,
+    #[serde(default)]
+    pub size: f32,
+    #[serde(default = "default_sensor_count")]
+    pub count: u32,
+    #[serde(default)]
+    pub adaptive: bool,
+    #[serde(default)]
+    pub offset_max: f32,
+    /// Half-arc (degrees) the sensors spread over; 0 (the default) = `angle_degrees`.
+    #[serde(default)]
+    pub spread_degrees: f32,
+}
+
+fn default_sensor_count() -> u32 {
+    3
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EmitDef {
+    pub channel: u32,
+    pub amount: f32,
+    #[serde(default)]
+    pub mode: DepositMode,
+    #[serde(default = "default_decay_bias")]
+    pub decay_bias: f32,
+}
+
+fn default_decay_bias() -> f32 {
+    1.0
+}
+
+/// Parse a RON list of `SpeciesDef`.
+pub fn parse_species_ron(text: &str) -> Result<Vec<SpeciesDef>, String> {
+    ron::from_str(text).map_err(|err| err.to_string())
+}
+
+/// Read and parse a species file from disk.
+pub fn load_species_file(path: &str) -> Result<Vec<SpeciesDef>, String> {
+    let text = std::fs::read_to_string(path).map_err(|err| format!("{path}: {err}"))?;
+    parse_species_ron(&text).map_err(|err| format!("{path}: {err}"))
+}
+
+/// Spawn one `AgentSpecies` entity from a file-side definition.
+pub fn spawn_species_def(commands: &mut Commands, def: &SpeciesDef) {
+    let mut entity = commands.spawn((
         AgentSpecies,
-        AgentColor(Vec4::new(1.0, 0.0, 0.0, 1.0)),
-        MoveSpeed(90.0),
-        TurnSpeed(2.0),
+        AgentColor(Vec4::from_array(def.color)),
+        MoveSpeed(def.move_speed),
+        TurnSpeed(def.turn_speed),
         Sensor {
-            angle_degrees: 12.0,
-            offset_dst: 25.0,
-            size: 0.0,
+            angle_degrees: def.sensor.angle_degrees,
+            offset_dst: def.sensor.offset_dst,
+            size: def.sensor.size,
+            count: def.sensor.count,
+            adaptive: def.sensor.adaptive,
+            offset_max: def.sensor.offset_max,
+            spread_degrees: def.sensor.spread_degrees,
         },
-        EmitsPheromone { channel: 2, amount: 0.6 },
-        // Layer weights override: emphasize its own channel strongly, avoid next
-        // [L0 hate, L1 love, L2 self, L3 next, L4 other]
-        LayerWeights(vec![-1.0, 1.0, 1.5, -1.0, 0.2]),
+        // Always present (possibly empty) so live weight edits never move the
+        // entity to another archetype and reshuffle species indices
+        LayerWeights(def.layer_weights.clone().unwrap_or_default()),
+        // Likewise, so pausing (`U`) toggles in place
+        SpeciesEnabled::default(),
     ));
+    if let Some(emit) = &def.emit {
+        entity.insert(EmitsPheromone {
+            channel: emit.channel,
+            amount: emit.amount,
+            mode: emit.mode,
+            decay_bias: emit.decay_bias,
+        });
+    }
+    if !def.extra_emits.is_empty() {
+        let mode = def.emit.as_ref().map_or(DepositMode::Constant, |e| e.mode);
+        entity.insert(ExtraEmits(
+            def.extra_emits
+                .iter()
+                .map(|e| EmitsPheromone { channel: e.channel, amount: e.amount, mode, decay_bias: 1.0 })
+                .collect(),
+        ));
+    }
+    if let Some(wander) = def.wander {
+        entity.insert(Wander(wander));
+    }
+    if let Some(max_turn) = def.max_turn {
+        entity.insert(MaxTurn(max_turn));
+    }
+    if let Some(mode) = def.steer_mode {
+        entity.insert(Steering(mode));
+    }
+    if let Some(jitter) = def.speed_jitter {
+        entity.insert(SpeedJitter(jitter));
+    }
+    if let Some(max_age) = def.max_age {
+        entity.insert(MaxAge(max_age));
+    }
+    if let Some(bias) = def.turn_bias {
+        entity.insert(TurnBias(bias));
+    }
+    if let Some(radius) = def.deposit_radius {
+        entity.insert(DepositRadius(radius));
+    }
+    if let Some(curve) = def.response_curve {
+        entity.insert(SensorResponse(curve));
+    }
+    if let Some(mode) = def.heading_mode {
+        entity.insert(SpeciesHeading(mode));
+    }
+    if let Some(strength) = def.avoidance {
+        entity.insert(Avoidance(strength));
+    }
+    if let Some(threshold) = def.deposit_threshold {
+        entity.insert(DepositThreshold(threshold));
+    }
+    if let Some(fatigue) = def.deposit_fatigue {
+        entity.insert(DepositFatigue(fatigue));
+    }
+    if let Some(amount) = def.shared_emit {
+        entity.insert(SharedEmit(amount));
+    }
+}
 
-    // Green species (channel 3): twitchy scout (medium speed, high turning, wide sensing)
-    commands.spawn((
-        AgentSpecies,
-        AgentColor(Vec4::new(0.0, 1.0, 0.0, 1.0)),
-        MoveSpeed(28.0),
-        TurnSpeed(12.0),
-        Sensor {
-            angle_degrees: 60.0,
-            offset_dst: 30.0,
-            size: 0.0,
+/// Inverse of `spawn_species_def`: describe an authored species entity.
+#[allow(clippy::too_many_arguments)]
+pub fn species_def_from_components(
+    color: &AgentColor,
+    move_speed: &MoveSpeed,
+    turn_speed: &TurnSpeed,
+    sensor: &Sensor,
+    emit: Option<&EmitsPheromone>,
+    extra_emits: Option<&ExtraEmits>,
+    weights: Option<&LayerWeights>,
+    wander: Option<&Wander>,
+    max_turn: Option<&MaxTurn>,
+    steering: Option<&Steering>,
+    speed_jitter: Option<&SpeedJitter>,
+    max_age: Option<&MaxAge>,
+    turn_bias: Option<&TurnBias>,
+    deposit_radius: Option<&DepositRadius>,
+    response: Option<&SensorResponse>,
+    heading: Option<&SpeciesHeading>,
+    avoidance: Option<&Avoidance>,
+    deposit_threshold: Option<&DepositThreshold>,
+    deposit_fatigue: Option<&DepositFatigue>,
+    shared_emit: Option<&SharedEmit>,
+) -> SpeciesDef {
+    SpeciesDef {
+        color: color.to_array(),
+        move_speed: **move_speed,
+        turn_speed: **turn_speed,
+        sensor: SensorDef {
+            angle_degrees: sensor.angle_degrees,
+            offset_dst: sensor.offset_dst,
+            size: sensor.size,
+            count: sensor.count,
+            adaptive: sensor.adaptive,
+            offset_max: sensor.offset_max,
+            spread_degrees: sensor.spread_degrees,
         },
-        EmitsPheromone { channel: 3, amount: 1.2 },
-        // Broader sensing with moderate biases
-        LayerWeights(vec![-1.0, 1.0, 0.2, 1.0, -0.6]),
-    ));
+        emit: emit.map(|e| EmitDef {
+            channel: e.channel,
+            amount: e.amount,
+            mode: e.mode,
+            decay_bias: e.decay_bias,
+        }),
+        extra_emits: extra_emits
+            .map(|extra| {
+                extra
+                    .iter()
+                    .map(|e| EmitDef { channel: e.channel, amount: e.amount, mode: e.mode, decay_bias: 1.0 })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        layer_weights: weights.filter(|w| !w.is_empty()).map(|w| w.0.clone()),
+        wander: wander.map(|w| w.0),
+        max_turn: max_turn.map(|m| m.0),
+        steer_mode: steering.map(|s| s.0),
+        speed_jitter: speed_jitter.map(|j| j.0),
+        max_age: max_age.map(|m| m.0),
+        turn_bias: turn_bias.map(|b| b.0),
+        deposit_radius: deposit_radius.map(|r| r.0),
+        response_curve: response.map(|r| r.0),
+        heading_mode: heading.map(|h| h.0),
+        avoidance: avoidance.map(|a| a.0),
+        deposit_threshold: deposit_threshold.map(|t| t.0),
+        deposit_fatigue: deposit_fatigue.map(|f| f.0),
+        shared_emit: shared_emit.map(|e| e.0),
+    }
+}
 
-    // Blue species (channel 4): whirl drifter (mid speed, high turning, wide sensing)
-    commands.spawn((
-        AgentSpecies,
-        AgentColor(Vec4::new(0.0, 0.0, 1.0, 1.0)),
-        MoveSpeed(42.0),
-        TurnSpeed(10.0),
-        Sensor {
-            angle_degrees: 75.0,
-            offset_dst: 28.0,
-            size: 0.0,
+// Three default agent species to match the current shader/channel assumptions (RGB)
+pub fn default_species_defs() -> Vec<SpeciesDef> {
+    // With extended layers: 0=hate, 1=love, 2..4 agent-specific
+    vec![
+        // Red species (channel 2): sprinter (very fast, low turning, narrow sensing)
+        SpeciesDef {
+            color: [1.0, 0.0, 0.0, 1.0],
+            move_speed: 90.0,
+            turn_speed: 2.0,
+            sensor: SensorDef {
+                angle_degrees: 12.0,
+                offset_dst: 25.0,
+                size: 0.0,
+                count: 3,
+                adaptive: false,
+                offset_max: 0.0,
+                spread_degrees: 0.0,
+            },
+            emit: Some(EmitDef {
+                channel: 2,
+                amount: 0.6,
+                mode: DepositMode::Constant,
+                decay_bias: 1.0,
+            }),
+            // Layer weights override: emphasize its own channel strongly, avoid next
+            // [L0 hate, L1 love, L2 self, L3 next, L4 other]
+            extra_emits: Vec::new(),
+            layer_weights: Some(vec![-1.0, 1.0, 1.5, -1.0, 0.2]),
+            wander: None,
+            max_turn: None,
+            steer_mode: None,
+            speed_jitter: None,
+            max_age: None,
+            turn_bias: None,
+            deposit_radius: None,
+            response_curve: None,
+            heading_mode: None,
+            avoidance: None,
+            deposit_threshold: None,
+            deposit_fatigue: None,
+            shared_emit: None,
         },
-        EmitsPheromone { channel: 4, amount: 2.0 },
-        // Broader curiosity: attracted to love(1) and self(4), slight avoidance of purple(2)
-        // [L0 hate, L1 love, L2 purple, L3 yellow, L4 blue]
-        LayerWeights(vec![-1.0, 1.0, -0.6, 0.2, 1.1]),
-    ));
+        // Green species (channel 3): twitchy scout (medium speed, high turning, wide sensing)
+        SpeciesDef {
+            color: [0.0, 1.0, 0.0, 1.0],
+            move_speed: 28.0,
+            turn_speed: 12.0,
+            sensor: SensorDef {
+                angle_degrees: 60.0,
+                offset_dst: 30.0,
+                size: 0.0,
+                count: 3,
+                adaptive: false,
+                offset_max: 0.0,
+                spread_degrees: 0.0,
+            },
+            emit: Some(EmitDef {
+                channel: 3,
+                amount: 1.2,
+                mode: DepositMode::Constant,
+                decay_bias: 1.0,
+            }),
+            // Broader sensing with moderate biases
+            extra_emits: Vec::new(),
+            layer_weights: Some(vec![-1.0, 1.0, 0.2, 1.0, -0.6]),
+            wander: None,
+            max_turn: None,
+            steer_mode: None,
+            speed_jitter: None,
+            max_age: None,
+            turn_bias: None,
+            deposit_radius: None,
+            response_curve: None,
+            heading_mode: None,
+            avoidance: None,
+            deposit_threshold: None,
+            deposit_fatigue: None,
+            shared_emit: None,
+        },
+        // Blue species (channel 4): whirl drifter (mid speed, high turning, wide sensing)
+        SpeciesDef {
+            color: [0.0, 0.0, 1.0, 1.0],
+            move_speed: 42.0,
+            turn_speed: 10.0,
+            sensor: SensorDef {
+                angle_degrees: 75.0,
+                offset_dst: 28.0,
+                size: 0.0,
+                count: 3,
+                adaptive: false,
+                offset_max: 0.0,
+                spread_degrees: 0.0,
+            },
+            emit: Some(EmitDef {
+                channel: 4,
+                amount: 2.0,
+                mode: DepositMode::Constant,
+                decay_bias: 1.0,
+            }),
+            // Broader curiosity: attracted to love(1) and self(4), slight avoidance of purple(2)
+            // [L0 hate, L1 love, L2 purple, L3 yellow, L4 blue]
+            extra_emits: Vec::new(),
+            layer_weights: Some(vec![-1.0, 1.0, -0.6, 0.2, 1.1]),
+            wander: None,
+            max_turn: None,
+            steer_mode: None,
+            speed_jitter: None,
+            max_age: None,
+            turn_bias: None,
+            deposit_radius: None,
+            response_curve: None,
+            heading_mode: None,
+            avoidance: None,
+            deposit_threshold: None,
+            deposit_fatigue: None,
+            shared_emit: None,
+        },
+    ]
+}
+
+/// Spawn species from `SPECIES_FILE_PATH`, falling back to the default trio
+/// (with a warning) when the file is missing, malformed, or empty. Species
+/// referencing layers beyond `PheromoneConfig.layer_count` are skipped.
+pub fn spawn_species_from_file(mut commands: Commands, phero_cfg: Res<PheromoneConfig>) {
+    let defs = match load_valid_species(crate::SPECIES_FILE_PATH, phero_cfg.layer_count.max(1)) {
+        Ok(defs) => {
+            info!("Species: loaded {} from {}", defs.len(), crate::SPECIES_FILE_PATH);
+            defs
+        }
+        Err(err) => {
+            warn!("Species: {err}; using defaults");
+            default_species_defs()
+        }
+    };
+    for def in defs.iter() {
+        spawn_species_def(&mut commands, def);
+    }
+}
+
+/// Load a species file and keep the definitions that validate against
+/// `layer_count` (logging the rest). Errors when the file can't be read or
+/// parsed, or leaves no valid species.
+pub fn load_valid_species(path: &str, layer_count: u32) -> Result<Vec<SpeciesDef>, String> {
+    let defs = load_species_file(path)?;
+    if defs.is_empty() {
+        return Err(format!("{path} is empty"));
+    }
+    let valid: Vec<SpeciesDef> = defs
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, def)| match SpeciesBuilder::from_def(&def).validate(layer_count) {
+            Ok(()) => Some(def),
+            Err(err) => {
+                warn!("Species {i}: {err}; skipped");
+                None
+            }
+        })
+        .collect();
+    if valid.is_empty() {
+        return Err(format!("no valid species in {path}"));
+    }
+    Ok(valid)
+}
+
+/// F6 re-reads `SPECIES_FILE_PATH` and replaces the authored species, then
+/// rebuilds their GPU buffers. A file that fails to load keeps the current
+/// species and flashes the error in the overlay.
+pub fn reload_species_hotkey(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    phero_cfg: Res<PheromoneConfig>,
+    species: Query<Entity, With<AgentSpecies>>,
+    mut notice: ResMut<OverlayNotice>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F6) {
+        return;
+    }
+    let path = crate::SPECIES_FILE_PATH;
+    let defs = match load_valid_species(path, phero_cfg.layer_count.max(1)) {
+        Ok(defs) => defs,
+        Err(err) => {
+            warn!("Species: reload failed ({err}); keeping current species");
+            notice.flash(format!("Species reload failed: {err}"));
+            return;
+        }
+    };
+    for entity in species.iter() {
+        commands.entity(entity).despawn();
+    }
+    for def in defs.iter() {
+        spawn_species_def(&mut commands, def);
+    }
+    commands.run_system_cached(upload_species_to_gpu);
+    info!("Species: reloaded {} from {path}", defs.len());
+    notice.flash(format!("Species reloaded ({})", defs.len()));
+}
+
+/// Species count for `generate_agents`: the authored species (at least 1),
+/// matching the `species_count` `upload_species_to_gpu` publishes.
+pub fn authored_species_count(species: &Query<Option<&SpeciesHeading>, With<AgentSpecies>>) -> u32 {
+    (species.iter().count() as u32).max(1)
+}
+
+/// Initial heading per species index for `generate_agents`. Iterates in the
+/// same order as `upload_species_to_gpu`, so entry `i` belongs to species `i`.
+pub fn species_heading_modes(
+    headings: &Query<Option<&SpeciesHeading>, With<AgentSpecies>>,
+) -> Vec<HeadingMode> {
+    headings.iter().map(|h| h.map_or(HeadingMode::Pattern, |h| h.0)).collect()
 }
 
 /// Build a GPU buffer from authored AgentSpecies entities and upload as SpeciesGpuBuffer resource.
-/// If no species are authored, falls back to the default RGB trio.
+/// If no species are authored, spawns the default RGB trio and uploads that
+/// instead, so the GPU never sees an empty species buffer.
 #[allow(clippy::type_complexity)]
 pub fn upload_species_to_gpu(
     mut commands: Commands,
     render_device: Res<RenderDevice>,
     phero_cfg: Res<PheromoneConfig>,
+    // Absent during Startup (setup inserts it afterwards with the authored count)
+    globals: Option<ResMut<GlobalUniforms>>,
     query: Query<
         (
             &AgentColor,
@@ -160,18 +1159,79 @@ pub fn upload_species_to_gpu(
             &TurnSpeed,
             &Sensor,
             Option<&EmitsPheromone>,
+            Option<&ExtraEmits>,
             Option<&LayerWeights>,
+            Option<&Wander>,
+            Option<&MaxTurn>,
+            Option<&Steering>,
+            Option<&SpeedJitter>,
+            Option<&MaxAge>,
+            Option<&TurnBias>,
+            Option<&DepositRadius>,
+            // Nested: a query tuple holds at most 15 elements
+            (
+                Option<&SensorResponse>,
+                Option<&Avoidance>,
+                Option<&DepositThreshold>,
+                Option<&DepositFatigue>,
+                Option<&SharedEmit>,
+                Option<&SpeciesEnabled>,
+            ),
         ),
         With<AgentSpecies>,
     >,
 ) {
+    if query.is_empty() {
+        // The spawns apply before the rerun, which then sees the trio
+        warn!("Species: none authored; using defaults");
+        for def in default_species_defs().iter() {
+            spawn_species_def(&mut commands, def);
+        }
+        commands.run_system_cached(upload_species_to_gpu);
+        return;
+    }
     // Collect species settings and optional extended arrays aligned by index
     let mut species: Vec<SpeciesSettings> = Vec::new();
     let mut layer_w: Vec<Option<Vec<f32>>> = Vec::new();
-    for (color, move_speed, turn_speed, sensor, emit, wext) in query.iter() {
-        species.push(build_species_settings_from_components(
-            color, move_speed, turn_speed, sensor, emit,
-        ));
+    for (
+        color,
+        move_speed,
+        turn_speed,
+        sensor,
+        emit,
+        extra,
+        wext,
+        wander,
+        max_turn,
+        steering,
+        jitter,
+        max_age,
+        turn_bias,
+        deposit_radius,
+        (response, avoidance, deposit_threshold, deposit_fatigue, shared_emit, enabled),
+    ) in query.iter()
+    {
+        // Extra emits only count alongside a primary one
+        let emits: Vec<&EmitsPheromone> = emit
+            .into_iter()
+            .chain(extra.filter(|_| emit.is_some()).into_iter().flat_map(|x| x.iter()))
+            .collect();
+        let mut settings =
+            build_species_settings_from_components(color, move_speed, turn_speed, sensor, &emits);
+        settings.wander_strength = wander.map_or(0.0, |w| w.0);
+        settings.max_turn_per_step = max_turn.map_or(crate::resources::DEFAULT_MAX_TURN, |m| m.0);
+        settings.steer_mode = steering.map_or(SteerMode::Sensors, |s| s.0) as u32;
+        settings.speed_jitter = jitter.map_or(0.0, |j| j.0);
+        settings.max_age = max_age.map_or(0.0, |m| m.0);
+        settings.turn_bias = turn_bias.map_or(0.0, |b| b.0);
+        settings.deposit_radius = deposit_radius.map_or(0.0, |r| r.0);
+        settings.response_curve = response.map_or(ResponseCurve::Linear, |r| r.0) as u32;
+        settings.avoid_strength = avoidance.map_or(0.0, |a| a.0);
+        settings.deposit_threshold = deposit_threshold.map_or(0.0, |t| t.0);
+        settings.deposit_fatigue = deposit_fatigue.map_or(0.0, |f| f.0);
+        settings.shared_emit_amount = shared_emit.map_or(0.0, |e| e.0);
+        settings.enabled = u32::from(enabled.is_none_or(|e| e.0));
+        species.push(settings);
         layer_w.push(wext.map(|v| v.0.clone()));
     }
 
@@ -180,37 +1240,18 @@ pub fn upload_species_to_gpu(
         contents: bytemuck::cast_slice(&species),
         usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
     });
-    commands.insert_resource(crate::resources::SpeciesGpuBuffer { buffer });
+    let any_avoidance = species.iter().any(|s| s.avoid_strength > 0.0 && s.enabled != 0);
+    commands.insert_resource(crate::resources::SpeciesGpuBuffer { buffer, any_avoidance });
+    // Agents map onto species modulo this, so reloads may change the count
+    if let Some(mut globals) = globals {
+        globals.species_count = (species.len() as u32).max(1);
+    }
 
-    // Build dense extended arrays (weights) sized species_count * L.
+    // Dense per-layer sensing weights (binding 7), species_count * L floats
     let layer_count = phero_cfg.layer_count.max(1);
-    let species_count = species.len() as u32;
-    let mut weights: Vec<f32> = vec![0.0; (layer_count * species_count) as usize];
-    for (si, _s) in species.iter().enumerate() {
-        let base = (si as u32) * layer_count;
-        // Extended overrides if provided
-        if let Some(w_override) = &layer_w.get(si).and_then(|o| o.as_ref()) {
-            let n = layer_count.min(w_override.len() as u32);
-            for li in 0..n { weights[(base + li) as usize] = w_override[li as usize]; }
-        }
-    }
-
-    // Apply universal and paint-only rules
-    let love_set: std::collections::HashSet<u32> = phero_cfg.universal_love_layers.iter().copied().collect();
-    let hate_set: std::collections::HashSet<u32> = phero_cfg.universal_hate_layers.iter().copied().collect();
-
-    for si in 0..species_count {
-        let base = si * layer_count;
-        for li in 0..layer_count {
-            // universal loved/hated -> override weight regardless of authored species weight
-            if love_set.contains(&li) {
-                weights[(base + li) as usize] = 1.0;
-            }
-            if hate_set.contains(&li) {
-                weights[(base + li) as usize] = -1.0;
-            }
-        }
-    }
+    // Per-layer decay multipliers from the species emitting into each layer
+    commands.insert_resource(LayerDecayBias::from_species(&species, layer_count, phero_cfg.paint_only_mask()));
+    let weights = dense_layer_weights(&layer_w, layer_count, &phero_cfg);
 
     let weights_buf = render_device.create_buffer_with_data(&BufferInitDescriptor {
         label: Some("Species extended weights"),
@@ -220,6 +1261,54 @@ pub fn upload_species_to_gpu(
     commands.insert_resource(SpeciesLayerWeights { weights: weights_buf });
 }
 
+/// Dense sensing weights for binding 7: `layer_count` floats per species,
+/// read by `agents.wgsl` at `species_index * layer_count + layer`. Missing or
+/// short authored weights are 0; universal layers override every species.
+pub fn dense_layer_weights(authored: &[Option<Vec<f32>>], layer_count: u32, cfg: &PheromoneConfig) -> Vec<f32> {
+    let layer_count = layer_count as usize;
+    let mut weights = vec![0.0; authored.len() * layer_count];
+    for (species_weights, w) in weights.chunks_exact_mut(layer_count.max(1)).zip(authored) {
+        for (li, slot) in species_weights.iter_mut().enumerate() {
+            *slot = universal_layer_weight(li as u32, cfg)
+                .or_else(|| w.as_ref().and_then(|w| w.get(li).copied()))
+                .unwrap_or(0.0);
+        }
+    }
+    weights
+}
+
+/// Universal loved/hated layers override every species' authored weight
+/// (hate wins if a layer is listed as both).
+pub fn universal_layer_weight(layer: u32, cfg: &PheromoneConfig) -> Option<f32> {
+    if cfg.universal_hate_layers.contains(&layer) {
+        Some(-1.0)
+    } else if cfg.universal_love_layers.contains(&layer) {
+        Some(1.0)
+    } else {
+        None
+    }
+}
+
+/// Weight the agent shader ends up using for `layer`, given a species'
+/// authored weights (missing entries are 0).
+pub fn effective_layer_weight(weights: &[f32], layer: u32, cfg: &PheromoneConfig) -> f32 {
+    universal_layer_weight(layer, cfg).unwrap_or_else(|| weights.get(layer as usize).copied().unwrap_or(0.0))
+}
+
+/// Add `delta` to the authored weight for `layer`, growing the list with
+/// zeros as needed. The result is clamped to +/-`WEIGHT_EDIT_LIMIT`.
+pub fn nudge_layer_weight(weights: &mut Vec<f32>, layer: u32, delta: f32) -> f32 {
+    let layer = layer as usize;
+    if weights.len() <= layer {
+        weights.resize(layer + 1, 0.0);
+    }
+    weights[layer] = (weights[layer] + delta).clamp(-WEIGHT_EDIT_LIMIT, WEIGHT_EDIT_LIMIT);
+    weights[layer]
+}
+
+// Bound for live-edited layer weights
+pub const WEIGHT_EDIT_LIMIT: f32 = 2.0;
+
 #[cfg(test)]
 /// Collect a `Vec<SpeciesSettings>` from an iterator of component references.
 /// This is a small pure helper so we can unit-test the translation from
@@ -238,7 +1327,7 @@ where
 {
     iter.into_iter()
         .map(|(color, move_speed, turn_speed, sensor, emit)| {
-            build_species_settings_from_components(color, move_speed, turn_speed, sensor, emit)
+            build_species_settings_from_components(color, move_speed, turn_speed, sensor, emit.as_slice())
         })
         .collect()
 }
@@ -247,7 +1336,463 @@ where
 mod tests {
     use super::*;
 
+    // A white species with the given fields (which include the sensor)
+    fn one_species(fields: &str) -> SpeciesDef {
+        let text = format!("[(color: (1.0, 1.0, 1.0, 1.0), move_speed: 10.0, turn_speed: 1.0, {fields})]");
+        let mut defs = parse_species_ron(&text).expect("valid species file");
+        assert_eq!(defs.len(), 1);
+        defs.remove(0)
+    }
+
+    const PLAIN_SENSOR: &str = "sensor: (angle_degrees: 30.0, offset_dst: 10.0)";
+
+    #[test]
+    fn parse_species_ron_optional_fields() {
+        let text = r#"[
+            (
+                color: (1.0, 0.5, 0.0, 1.0),
+                move_speed: 40.0,
+                turn_speed: 5.0,
+                sensor: (angle_degrees: 30.0, offset_dst: 20.0),
+                emit: Some((channel: 2, amount: 0.5)),
+                layer_weights: Some([-1.0, 1.0, 0.5]),
+            ),
+            (
+                color: (0.0, 0.0, 1.0, 1.0),
+                move_speed: 10.0,
+                turn_speed: 1.0,
+                sensor: (angle_degrees: 45.0, offset_dst: 10.0, size: 1.0, count: 5),
+                wander: Some(0.3),
+            ),
+        ]"#;
+        let defs = parse_species_ron(text).expect("valid species file");
+        assert_eq!(defs.len(), 2);
+        assert_eq!(
+            defs[0].emit,
+            Some(EmitDef {
+                channel: 2,
+                amount: 0.5,
+                mode: DepositMode::Constant,
+                decay_bias: 1.0,
+            })
+        );
+        assert_eq!(defs[0].layer_weights.as_deref(), Some(&[-1.0, 1.0, 0.5][..]));
+        assert_eq!(defs[0].sensor.size, 0.0);
+        assert_eq!(defs[0].sensor.count, 3);
+        assert_eq!(defs[1].emit, None);
+        assert_eq!(defs[1].layer_weights, None);
+        assert_eq!(defs[1].sensor.size, 1.0);
+        assert_eq!(defs[1].sensor.count, 5);
+        assert_eq!(defs[0].wander, None);
+        assert_eq!(defs[1].wander, Some(0.3));
+    }
+
+    #[test]
+    fn parse_species_ron_malformed_errors() {
+        assert!(parse_species_ron("[(color: (1.0, 0.0, 0.0, 1.0))]").is_err());
+        assert!(parse_species_ron("not ron").is_err());
+    }
+
+    #[test]
+    fn deposit_mode_parses_and_packs() {
+        let def = one_species(&format!("{PLAIN_SENSOR}, emit: Some((channel: 1, amount: 1.0, mode: SpeedScaled))"));
+        let emit = def.emit.as_ref().expect("emit");
+        assert_eq!(emit.mode, DepositMode::SpeedScaled);
+
+        let b = SpeciesBuilder::from_def(&def).build(2).expect("valid species");
+        let settings = build_species_settings_from_components(
+            &b.color,
+            &b.move_speed,
+            &b.turn_speed,
+            &b.sensor,
+            &[&b.emit],
+        );
+        assert_eq!(settings.deposit_mode, DepositMode::SpeedScaled as u32);
+        assert_eq!(SpeciesSettings::default().deposit_mode, DepositMode::Constant as u32);
+    }
+
+    #[test]
+    fn adaptive_sensor_parses_and_packs() {
+        let def = one_species("sensor: (angle_degrees: 30.0, offset_dst: 10.0, adaptive: true, offset_max: 40.0)");
+        let b = SpeciesBuilder::from_def(&def).build(2).expect("valid species");
+        assert!(b.sensor.adaptive);
+        let settings = build_species_settings_from_components(
+            &b.color,
+            &b.move_speed,
+            &b.turn_speed,
+            &b.sensor,
+            &[],
+        );
+        assert_eq!(settings.sensor_adaptive, 1);
+        assert_eq!(settings.sensor_offset_max, 40.0);
+
+        // off by default: the fixed offset is kept
+        let fixed = SpeciesBuilder::new().build(2).expect("valid species");
+        assert!(!fixed.sensor.adaptive);
+        assert_eq!(fixed.sensor.offset_max, fixed.sensor.offset_dst);
+        assert_eq!(SpeciesSettings::default().sensor_adaptive, 0);
+    }
+
+    // CPU copy of the shader's `sensor_offset_angle`: heading offset in
+    // radians of sensor `i` of `n`, spread evenly over [-angle, +angle]
+    // (positive = left); the centre of an odd fan and a lone sensor look ahead
+    fn sensor_offset_angle(i: u32, n: u32, angle: f32) -> f32 {
+        if n <= 1 || 2 * i == n - 1 {
+            return 0.0;
+        }
+        -angle + 2.0 * angle * i as f32 / (n - 1) as f32
+    }
+
+    #[test]
+    fn sensor_count_is_validated_and_one_sensor_only_looks_ahead() {
+        assert!(SpeciesBuilder::new().sensor_count(0).validate(2).is_err());
+        assert!(SpeciesBuilder::new().sensor_count(MAX_SENSORS + 1).validate(2).is_err());
+        assert!(SpeciesBuilder::new().sensor_count(MAX_SENSORS).validate(2).is_ok());
+        let one = SpeciesBuilder::new().sensor_count(1).build(2).expect("one sensor is valid");
+        assert_eq!(one.sensor.count, 1);
+        // The fan's only sensor is straight ahead: no left/right pair to steer by
+        assert_eq!(sensor_offset_angle(0, 1, 0.5), 0.0);
+        // Three is the classic left/forward/right rule
+        let three: Vec<f32> = (0..3).map(|i| sensor_offset_angle(i, 3, 0.5)).collect();
+        assert_eq!(three, vec![-0.5, 0.0, 0.5]);
+    }
+
+    #[test]
+    fn sensor_spread_parses_packs_and_validates() {
+        let def = one_species("sensor: (angle_degrees: 30.0, offset_dst: 10.0, count: 7, spread_degrees: 45.0)");
+        let b = SpeciesBuilder::from_def(&def).build(2).expect("valid species");
+        let settings = build_species_settings_from_components(
+            &b.color,
+            &b.move_speed,
+            &b.turn_speed,
+            &b.sensor,
+            &[],
+        );
+        // The spread is independent of the classic angle, which is kept
+        assert_eq!((settings.sensor_spread_degrees, settings.sensor_angle_degrees), (45.0, 30.0));
+        assert_eq!(settings.sensor_count, 7);
+        // Unset falls back to the sensor angle
+        assert_eq!(SpeciesSettings::default().sensor_spread_degrees, 0.0);
+        assert!(SpeciesBuilder::new().sensor_spread(-1.0).validate(2).is_err());
+        assert!(SpeciesBuilder::new().sensor_spread(181.0).validate(2).is_err());
+    }
+
+    #[test]
+    fn extra_emits_pack_into_settings() {
+        let def = one_species(&format!(
+            "{PLAIN_SENSOR}, emit: Some((channel: 2, amount: 1.0)), extra_emits: [(channel: 1, amount: 0.25)]"
+        ));
+        let b = SpeciesBuilder::from_def(&def).build(3).expect("valid species");
+        assert_eq!(b.extra_emits.len(), 1);
+        let emits: Vec<&EmitsPheromone> = std::iter::once(&b.emit).chain(b.extra_emits.iter()).collect();
+        let settings =
+            build_species_settings_from_components(&b.color, &b.move_speed, &b.turn_speed, &b.sensor, &emits);
+        assert_eq!((settings.emit_layer, settings.emit_amount), (2, 1.0));
+        assert_eq!(settings.extra_emit_layers.x, 1);
+        assert_eq!(settings.extra_emit_amounts, Vec4::new(0.25, 0.0, 0.0, 0.0));
+
+        // extra layers are validated, and the slot count is capped
+        assert!(SpeciesBuilder::new().emits(0, 1.0).also_emits(3, 1.0).validate(3).is_err());
+        let mut many = SpeciesBuilder::new().emits(0, 1.0);
+        for _ in 0..=MAX_EXTRA_EMITS {
+            many = many.also_emits(1, 1.0);
+        }
+        assert!(many.validate(3).is_err());
+    }
+
+    #[test]
+    fn generated_agents_cover_every_authored_species() {
+        use crate::agents::{SpawnPattern, generate_agents};
+        use bevy::ecs::system::RunSystemOnce;
+
+        let mut defs = default_species_defs();
+        defs.extend(default_species_defs().into_iter().take(2));
+        let mut world = World::new();
+        world
+            .run_system_once(move |mut commands: Commands| {
+                for def in &defs {
+                    spawn_species_def(&mut commands, def);
+                }
+            })
+            .expect("spawns species");
+        let (count, agents) = world
+            .run_system_once(|species: Query<Option<&SpeciesHeading>, With<AgentSpecies>>| {
+                let count = authored_species_count(&species);
+                let modes = species_heading_modes(&species);
+                (count, generate_agents(UVec2::splat(64), 50, count, &[], &modes, SpawnPattern::UniformRandom, 1))
+            })
+            .expect("generates agents");
+        assert_eq!(count, 5);
+        for index in 0..5 {
+            assert!(agents.iter().any(|a| a.species_index == index));
+        }
+        assert!(agents.iter().all(|a| a.species_index < 5));
+        // No species still yields one valid index
+        let empty = World::new().run_system_once(|species: Query<Option<&SpeciesHeading>, With<AgentSpecies>>| {
+            authored_species_count(&species)
+        });
+        assert_eq!(empty.expect("counts"), 1);
+    }
+
+    #[test]
+    fn pausing_a_species_keeps_the_upload_order() {
+        use bevy::ecs::system::RunSystemOnce;
+
+        let mut world = World::new();
+        world
+            .run_system_once(|mut commands: Commands| {
+                for (i, def) in default_species_defs().iter().enumerate() {
+                    let mut def = def.clone();
+                    def.move_speed = i as f32;
+                    spawn_species_def(&mut commands, &def);
+                }
+            })
+            .expect("spawns species");
+        // Same filter as `upload_species_to_gpu`
+        let order = |world: &mut World| {
+            world
+                .run_system_once(|species: Query<(&MoveSpeed, Option<&SpeciesEnabled>), With<AgentSpecies>>| {
+                    species.iter().map(|(m, e)| (m.0, e.is_none_or(|e| e.0))).collect::<Vec<_>>()
+                })
+                .expect("reads species")
+        };
+        assert_eq!(order(&mut world), vec![(0.0, true), (1.0, true), (2.0, true)]);
+        let toggle = |world: &mut World| {
+            world
+                .run_system_once(|mut species: Query<Option<&mut SpeciesEnabled>, With<AgentSpecies>>| {
+                    toggle_species_enabled(&mut species, 1)
+                })
+                .expect("toggles")
+        };
+        assert_eq!(toggle(&mut world), Some(false));
+        assert_eq!(order(&mut world), vec![(0.0, true), (1.0, false), (2.0, true)]);
+        // The second press hits the same species
+        assert_eq!(toggle(&mut world), Some(true));
+        assert_eq!(order(&mut world), vec![(0.0, true), (1.0, true), (2.0, true)]);
+    }
+
+    #[test]
+    fn optional_fields_parse_and_default() {
+        // (field as written, check of the parsed def and built bundle, check
+        // of the bundle built without the field)
+        type Check = fn(&SpeciesDef, &SpeciesBundle);
+        let cases: [(&str, Check, fn(&SpeciesBundle)); 12] = [
+            (
+                "speed_jitter: Some(0.25)",
+                |d, b| assert_eq!((d.speed_jitter, *b.speed_jitter), (Some(0.25), 0.25)),
+                |b| assert_eq!(*b.speed_jitter, 0.0),
+            ),
+            (
+                "max_age: Some(12.5)",
+                |d, b| assert_eq!((d.max_age, *b.max_age), (Some(12.5), 12.5)),
+                // Immortal
+                |b| assert_eq!(*b.max_age, 0.0),
+            ),
+            (
+                "turn_bias: Some(-0.75)",
+                |d, b| assert_eq!((d.turn_bias, *b.turn_bias), (Some(-0.75), -0.75)),
+                |b| assert_eq!(*b.turn_bias, 0.0),
+            ),
+            (
+                "deposit_radius: Some(2.5)",
+                |d, b| assert_eq!((d.deposit_radius, *b.deposit_radius), (Some(2.5), 2.5)),
+                |b| assert_eq!(*b.deposit_radius, 0.0),
+            ),
+            (
+                "response_curve: Some(Sqrt)",
+                |d, b| assert_eq!((d.response_curve, *b.response), (Some(ResponseCurve::Sqrt), ResponseCurve::Sqrt)),
+                |b| assert_eq!(*b.response, ResponseCurve::Linear),
+            ),
+            (
+                "heading_mode: Some(Outward)",
+                |d, b| assert_eq!((d.heading_mode, *b.heading), (Some(HeadingMode::Outward), HeadingMode::Outward)),
+                |b| assert_eq!(*b.heading, HeadingMode::Pattern),
+            ),
+            (
+                "avoidance: Some(4.0)",
+                |d, b| assert_eq!((d.avoidance, *b.avoidance), (Some(4.0), 4.0)),
+                |b| assert_eq!(*b.avoidance, 0.0),
+            ),
+            (
+                "deposit_threshold: Some(0.25)",
+                |d, b| assert_eq!((d.deposit_threshold, *b.deposit_threshold), (Some(0.25), 0.25)),
+                // Always deposits
+                |b| assert_eq!(*b.deposit_threshold, 0.0),
+            ),
+            (
+                "deposit_fatigue: Some(0.5)",
+                |d, b| assert_eq!((d.deposit_fatigue, *b.deposit_fatigue), (Some(0.5), 0.5)),
+                |b| assert_eq!(*b.deposit_fatigue, 0.0),
+            ),
+            (
+                "emit: Some((channel: 2, amount: 1.0)), shared_emit: Some(0.1)",
+                |d, b| {
+                    assert_eq!((d.shared_emit, *b.shared_emit), (Some(0.1), 0.1));
+                    // Separate from the identity layer
+                    assert_eq!((b.emit.channel, b.emit.amount), (2, 1.0));
+                },
+                |b| assert_eq!(*b.shared_emit, 0.0),
+            ),
+            (
+                "max_turn: Some(0.2)",
+                |d, b| assert_eq!((d.max_turn, *b.max_turn), (Some(0.2), 0.2)),
+                |b| assert_eq!(*b.max_turn, crate::resources::DEFAULT_MAX_TURN),
+            ),
+            (
+                "steer_mode: Some(Gradient)",
+                |d, b| assert_eq!((d.steer_mode, *b.steering), (Some(SteerMode::Gradient), SteerMode::Gradient)),
+                |b| assert_eq!(*b.steering, SteerMode::Sensors),
+            ),
+        ];
+        let unset = SpeciesBuilder::from_def(&one_species(PLAIN_SENSOR)).build(3).expect("valid species");
+        for (field, check, check_default) in cases {
+            let def = one_species(&format!("{PLAIN_SENSOR}, {field}"));
+            let bundle = SpeciesBuilder::from_def(&def).build(3).expect("valid species");
+            check(&def, &bundle);
+            check_default(&unset);
+        }
+
+        // The GPU-side defaults agree with the unset fields
+        let s = SpeciesSettings::default();
+        assert_eq!((s.speed_jitter, s.max_age, s.turn_bias, s.deposit_radius), (0.0, 0.0, 0.0, 0.0));
+        let deposits = (s.avoid_strength, s.deposit_threshold, s.deposit_fatigue, s.shared_emit_amount);
+        assert_eq!(deposits, (0.0, 0.0, 0.0, 0.0));
+        assert_eq!(s.response_curve, ResponseCurve::Linear as u32);
+        assert_eq!(s.steer_mode, SteerMode::Sensors as u32);
+        assert_eq!(s.max_turn_per_step, crate::resources::DEFAULT_MAX_TURN);
+    }
+
+    #[test]
+    fn out_of_range_optional_fields_are_rejected() {
+        let rejected = [
+            SpeciesBuilder::new().speed_jitter(1.5),
+            SpeciesBuilder::new().speed_jitter(-0.1),
+            SpeciesBuilder::new().max_age(-1.0),
+            SpeciesBuilder::new().turn_bias(f32::NAN),
+            SpeciesBuilder::new().deposit_radius(MAX_DEPOSIT_RADIUS + 1.0),
+            SpeciesBuilder::new().deposit_radius(-1.0),
+            SpeciesBuilder::new().avoidance(-1.0),
+            SpeciesBuilder::new().avoidance(f32::NAN),
+            SpeciesBuilder::new().deposit_threshold(-0.5),
+            SpeciesBuilder::new().deposit_threshold(f32::INFINITY),
+            SpeciesBuilder::new().deposit_fatigue(-1.0),
+            SpeciesBuilder::new().deposit_fatigue(f32::NAN),
+            SpeciesBuilder::new().shared_emit(f32::NAN),
+        ];
+        for builder in rejected {
+            assert!(builder.validate(2).is_err(), "{builder:?}");
+        }
+    }
+
+    #[test]
+    fn sensor_size_is_validated() {
+        assert!(SpeciesBuilder::new().sensor(30.0, 10.0, 0.0).validate(3).is_ok());
+        assert!(SpeciesBuilder::new().sensor(30.0, 10.0, MAX_SENSOR_SIZE).validate(3).is_ok());
+        assert!(SpeciesBuilder::new().sensor(30.0, 10.0, -1.0).validate(3).is_err());
+        assert!(SpeciesBuilder::new().sensor(30.0, 10.0, MAX_SENSOR_SIZE + 1.0).validate(3).is_err());
+        assert!(SpeciesBuilder::new().sensor(30.0, 10.0, f32::NAN).validate(3).is_err());
+    }
+
+    #[test]
+    fn layer_weight_edits_and_universal_overrides() {
+        let cfg = PheromoneConfig {
+            layer_count: 5,
+            brush_target_layer: 0,
+            universal_love_layers: vec![1],
+            universal_hate_layers: vec![0],
+            ..Default::default()
+        };
+        let mut weights = vec![0.0, 0.0, 0.5];
+        assert_eq!(nudge_layer_weight(&mut weights, 2, 0.25), 0.75);
+        // grows to reach the layer
+        assert_eq!(nudge_layer_weight(&mut weights, 4, -0.5), -0.5);
+        assert_eq!(weights, vec![0.0, 0.0, 0.75, 0.0, -0.5]);
+        assert_eq!(nudge_layer_weight(&mut weights, 2, 10.0), WEIGHT_EDIT_LIMIT);
+        assert_eq!(effective_layer_weight(&weights, 0, &cfg), -1.0);
+        assert_eq!(effective_layer_weight(&weights, 1, &cfg), 1.0);
+        assert_eq!(effective_layer_weight(&weights, 4, &cfg), -0.5);
+        assert_eq!(effective_layer_weight(&[], 3, &cfg), 0.0);
+    }
+
+    #[test]
+    fn species_builder_builds_bundle() {
+        let bundle = SpeciesBuilder::new()
+            .color(Vec4::new(1.0, 0.0, 0.0, 1.0))
+            .move_speed(60.0)
+            .sensor_count(5)
+            .follows(2)
+            .avoids(0)
+            .emits(2, 0.8)
+            .build(5)
+            .expect("valid species");
+        assert_eq!(*bundle.move_speed, 60.0);
+        assert_eq!(bundle.sensor.count, 5);
+        assert_eq!(bundle.emit.channel, 2);
+        assert_eq!(bundle.emit.amount, 0.8);
+        assert_eq!(bundle.weights.0, vec![-1.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn species_builder_rejects_out_of_range_layers() {
+        let err = SpeciesBuilder::new().emits(5, 1.0).build(5).err().expect("emit out of range");
+        assert!(err.contains("emit layer 5"));
+        let err = SpeciesBuilder::new().follows(7).build(5).err().expect("weight out of range");
+        assert!(err.contains("layer 7"));
+        // every default species is valid for the default five layers
+        for def in default_species_defs() {
+            assert!(SpeciesBuilder::from_def(&def).validate(5).is_ok());
+        }
+        assert!(SpeciesBuilder::from_def(&default_species_defs()[0]).validate(3).is_err());
+    }
+
+    #[test]
+    fn bundled_species_file_matches_defaults() {
+        let text = include_str!("../assets/species.ron");
+        let defs = parse_species_ron(text).expect("bundled species file parses");
+        assert_eq!(defs, default_species_defs());
+    }
+
+    #[test]
+    fn dense_layer_weights_index_by_species_and_layer() {
+        let cfg = PheromoneConfig {
+            layer_count: 6,
+            universal_love_layers: vec![1],
+            universal_hate_layers: vec![0],
+            ..Default::default()
+        };
+        let authored = [Some(vec![0.5, 0.5, 2.0, 0.0, 0.0, -3.0]), None, Some(vec![9.0, 9.0, 0.25])];
+        let w = dense_layer_weights(&authored, 6, &cfg);
+        assert_eq!(w.len(), 3 * 6);
+        // species 0 reacts to layer 5, past the old four-channel limit
+        assert_eq!(w[5], -3.0);
+        assert_eq!(w[2], 2.0);
+        // universal layers override every species, authored or not
+        assert_eq!((w[0], w[1]), (-1.0, 1.0));
+        assert_eq!((w[6], w[7], w[8]), (-1.0, 1.0, 0.0));
+        // short authored weights leave the remaining layers at 0
+        assert_eq!(&w[12..], &[-1.0, 1.0, 0.25, 0.0, 0.0, 0.0]);
+    }
 
+    #[test]
+    fn species_can_follow_one_layer_and_emit_into_another() {
+        // Food-trail setup: follow layer 2, lay an unrelated trail on layer 4
+        let def = one_species(&format!(
+            "{PLAIN_SENSOR}, emit: Some((channel: 4, amount: 1.0)), layer_weights: Some([0.0, 0.0, 1.0, 0.0, 0.0])"
+        ));
+        let b = SpeciesBuilder::from_def(&def).build(5).expect("valid species");
+        let settings = build_species_settings_from_components(
+            &b.color,
+            &b.move_speed,
+            &b.turn_speed,
+            &b.sensor,
+            &[&b.emit],
+        );
+        assert_eq!(settings.emit_layer, 4);
+        let cfg = PheromoneConfig { layer_count: 5, ..Default::default() };
+        let w = dense_layer_weights(&[Some(b.weights.0.clone())], 5, &cfg);
+        // Sensing ignores the emit layer entirely
+        assert_eq!(w, [0.0, 0.0, 1.0, 0.0, 0.0]);
+    }
 
     #[test]
     fn build_species_packs_weights_and_emit() {
@@ -258,10 +1803,16 @@ mod tests {
             angle_degrees: 10.0,
             offset_dst: 5.0,
             size: 2.0,
+            count: 3,
+            adaptive: false,
+            offset_max: 5.0,
+            spread_degrees: 0.0,
         };
         let emit = EmitsPheromone {
             channel: 2,
             amount: 0.75,
+            mode: DepositMode::Constant,
+            decay_bias: 1.0,
         };
 
         let settings = build_species_settings_from_components(
@@ -269,11 +1820,12 @@ mod tests {
             &move_speed,
             &turn_speed,
             &sensor,
-            Some(&emit),
+            &[&emit],
         );
 
-        // color is copied
+        // color and sensor count are copied
         assert_eq!(settings.color, Vec4::new(0.2, 0.3, 0.4, 1.0));
+        assert_eq!(settings.sensor_count, 3);
         // weights are no longer stored on SpeciesSettings; validated via upload path
         // emit: single-layer 2 set with amount
         assert_eq!(settings.emit_layer, 2);
@@ -289,6 +1841,10 @@ mod tests {
             angle_degrees: 15.0,
             offset_dst: 5.0,
             size: 1.0,
+            count: 3,
+            adaptive: false,
+            offset_max: 5.0,
+            spread_degrees: 0.0,
         };
 
         let settings = build_species_settings_from_components(
@@ -296,7 +1852,7 @@ mod tests {
             &move_speed,
             &turn_speed,
             &sensor,
-            None,
+            &[],
         );
 
         // no emission configured
@@ -312,10 +1868,16 @@ mod tests {
             angle_degrees: 10.0,
             offset_dst: 5.0,
             size: 2.0,
+            count: 3,
+            adaptive: false,
+            offset_max: 5.0,
+            spread_degrees: 0.0,
         };
         let emit = EmitsPheromone {
             channel: 2,
             amount: 0.75,
+            mode: DepositMode::Constant,
+            decay_bias: 1.0,
         };
 
         let items = vec![(&color, &move_speed, &turn_speed, &sensor, Some(&emit))];
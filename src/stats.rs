@@ -0,0 +1,139 @@
+// Per-layer field statistics (min / max / mean) for spotting blown-out or
+// dead layers before it's visible.
+//
+// `S` toggles `PheromoneStatsEnabled`. While on, the render node runs
+// `reduce_phero_stats` (one workgroup per layer reducing the whole array the
+// composite reads) into a small storage buffer, and a `Readback` of that
+// buffer lands in `PheromoneStats` a frame later for the overlay. Turning it
+// off skips the pass and despawns the readback, so it costs nothing unused.
+
+use bevy::prelude::*;
+use bevy::render::extract_resource::ExtractResource;
+use bevy::render::gpu_readback::{Readback, ReadbackComplete};
+use bevy::render::render_resource::BufferUsages;
+use bevy::render::storage::ShaderStorageBuffer;
+
+use crate::probe::MAX_PROBE_LAYERS;
+use crate::resources::PheromoneConfig;
+
+// f32s written per layer: min, max, mean, unused
+const FLOATS_PER_LAYER: usize = 4;
+
+/// GPU buffer `reduce_phero_stats` writes (`FLOATS_PER_LAYER` per layer).
+#[derive(Resource, Clone, ExtractResource)]
+pub struct PheromoneStatsBuffer {
+    pub buffer: Handle<ShaderStorageBuffer>,
+}
+
+/// Whether the stats pass and its readback run.
+#[derive(Resource, Clone, Copy, Default, ExtractResource)]
+pub struct PheromoneStatsEnabled(pub bool);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LayerStats {
+    pub min: f32,
+    pub max: f32,
+    pub mean: f32,
+}
+
+/// Latest stats read back, one entry per active layer (empty while off).
+#[derive(Resource, Default, Debug)]
+pub struct PheromoneStats {
+    pub layers: Vec<LayerStats>,
+}
+
+// Live readback entity while stats are enabled
+#[derive(Resource, Default)]
+struct StatsReadback(Option<Entity>);
+
+pub struct StatsPlugin;
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PheromoneStats>()
+            .init_resource::<PheromoneStatsEnabled>()
+            .init_resource::<StatsReadback>()
+            .add_systems(Startup, create_stats_buffer)
+            .add_systems(Update, handle_stats_hotkey);
+    }
+}
+
+fn create_stats_buffer(mut commands: Commands, mut buffers: ResMut<Assets<ShaderStorageBuffer>>) {
+    let mut buffer = ShaderStorageBuffer::from(vec![0.0f32; MAX_PROBE_LAYERS * FLOATS_PER_LAYER]);
+    buffer.buffer_description.usage |= BufferUsages::COPY_SRC;
+    commands.insert_resource(PheromoneStatsBuffer { buffer: buffers.add(buffer) });
+}
+
+fn handle_stats_hotkey(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    buffer: Res<PheromoneStatsBuffer>,
+    mut enabled: ResMut<PheromoneStatsEnabled>,
+    mut readback: ResMut<StatsReadback>,
+    mut stats: ResMut<PheromoneStats>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyS) {
+        return;
+    }
+    enabled.0 = !enabled.0;
+    if enabled.0 {
+        // Readback repeats every frame while the entity lives
+        let entity = commands
+            .spawn(Readback::buffer(buffer.buffer.clone()))
+            .observe(
+                |event: On<ReadbackComplete>, cfg: Res<PheromoneConfig>, mut stats: ResMut<PheromoneStats>| {
+                    stats.layers = decode_stats(&event.data, cfg.layer_count);
+                },
+            )
+            .id();
+        readback.0 = Some(entity);
+    } else {
+        if let Some(entity) = readback.0.take() {
+            commands.entity(entity).despawn();
+        }
+        stats.layers.clear();
+    }
+    info!("Layer stats: {}", if enabled.0 { "on" } else { "off" });
+}
+
+/// Decode the raw stats buffer, keeping only the first `layer_count` layers.
+pub fn decode_stats(data: &[u8], layer_count: u32) -> Vec<LayerStats> {
+    let values: Vec<f32> = bytemuck::pod_collect_to_vec(data);
+    values
+        .chunks_exact(FLOATS_PER_LAYER)
+        .take(layer_count as usize)
+        .map(|v| LayerStats { min: v[0], max: v[1], mean: v[2] })
+        .collect()
+}
+
+/// Overlay line: `L<i> min/mean/max` for each layer.
+pub fn stats_label(stats: &[LayerStats]) -> String {
+    stats
+        .iter()
+        .enumerate()
+        .map(|(i, s)| format!("L{i} {:.2}/{:.2}/{:.2}", s.min, s.mean, s.max))
+        .collect::<Vec<_>>()
+        .join("  ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_stats_groups_and_truncates() {
+        let raw = [0.0f32, 2.0, 0.5, 0.0, 0.1, 0.9, 0.3, 0.0, 7.0, 7.0, 7.0, 0.0];
+        let data: Vec<u8> = raw.iter().flat_map(|v| v.to_le_bytes()).collect();
+        let stats = decode_stats(&data, 2);
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0], LayerStats { min: 0.0, max: 2.0, mean: 0.5 });
+        assert_eq!(stats[1].mean, 0.3);
+        assert_eq!(decode_stats(&data, 8).len(), 3);
+    }
+
+    #[test]
+    fn stats_label_formats_layers() {
+        let stats = [LayerStats { min: 0.0, max: 1.0, mean: 0.25 }];
+        assert_eq!(stats_label(&stats), "L0 0.00/0.25/1.00");
+        assert_eq!(stats_label(&[]), "");
+    }
+}